@@ -0,0 +1,66 @@
+//! Scalar vs SIMD mixing kernels on one 2048-frame stereo chunk
+//!
+//! Run with `cargo bench --bench mix` (SIMD is on by default) and compare
+//! against the `scalar` group, or with `--no-default-features` to bench the
+//! fallback path.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/simd.rs"]
+mod simd;
+
+const CHUNK_SAMPLES: usize = 2048 * 2;
+
+fn signal(seed: u32) -> Vec<f32> {
+  let mut state = seed;
+  (0..CHUNK_SAMPLES)
+    .map(|_| {
+      state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+      (state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+    })
+    .collect()
+}
+
+fn bench_mix(c: &mut Criterion) {
+  let (a, b) = (signal(1), signal(2));
+  let (gain_a, gain_b) = (signal(3), signal(4));
+  let mut out = vec![0.0f32; CHUNK_SAMPLES];
+
+  let mut group = c.benchmark_group("mix_two");
+  group.bench_function("scalar", |bench| {
+    bench.iter(|| simd::scalar::mix_two(&mut out, black_box(&a), &gain_a, black_box(&b), &gain_b))
+  });
+  group.bench_function("simd", |bench| {
+    bench.iter(|| simd::mix_two(&mut out, black_box(&a), &gain_a, black_box(&b), &gain_b))
+  });
+  group.finish();
+}
+
+fn bench_peak(c: &mut Criterion) {
+  let a = signal(5);
+
+  let mut group = c.benchmark_group("peak_abs");
+  group.bench_function("scalar", |bench| {
+    bench.iter(|| simd::scalar::peak_abs(black_box(&a)))
+  });
+  group.bench_function("simd", |bench| bench.iter(|| simd::peak_abs(black_box(&a))));
+  group.finish();
+}
+
+fn bench_band_sum(c: &mut Criterion) {
+  let (low, mid, high) = (signal(6), signal(7), signal(8));
+  let mut out = vec![0.0f32; CHUNK_SAMPLES];
+
+  let mut group = c.benchmark_group("sum_three");
+  group.bench_function("scalar", |bench| {
+    bench.iter(|| simd::scalar::sum_three(&mut out, black_box(&low), 0.0, &mid, 1.0, &high, 1.0))
+  });
+  group.bench_function("simd", |bench| {
+    bench.iter(|| simd::sum_three(&mut out, black_box(&low), 0.0, &mid, 1.0, &high, 1.0))
+  });
+  group.finish();
+}
+
+criterion_group!(benches, bench_mix, bench_peak, bench_band_sum);
+criterion_main!(benches);
@@ -6,29 +6,270 @@ export declare class AudioEngine {
   /**
    * Configure audio device and start output stream
    * Can be called multiple times to switch devices without losing engine state
+   * The engine runs without a device until this succeeds; a failure (e.g. no sound card yet)
+   * is returned and reported as deviceError (update reason "device_error"), and calling
+   * again later attaches a device once one is available
    */
   configureDevice(config: DeviceConfig): void
-  /** Load PCM data onto a deck */
+  /**
+   * Set (or clear with null) the callback for discrete engine events
+   * Each track end, loop wrap, finished auto crossfade and temp cue pass fires once
+   */
+  setEventCallback(callback?: ((event: EngineEvent) => void) | undefined | null): void
+  /**
+   * Load PCM data onto a deck
+   * If the other deck holds the same track (same trackId and length) its buffer is shared
+   */
   loadTrack(deck: number, pcmData: Float32Array, bpm?: number | undefined | null, trackId?: string | undefined | null): void
+  /**
+   * Queue PCM data to follow the deck's current track without a gap
+   * When the current track ends the deck continues into it (update reason "advanced");
+   * queueing again replaces the pending track
+   */
+  queueNext(deck: number, pcmData: Float32Array, bpm?: number | undefined | null, trackId?: string | undefined | null): void
+  /** Drop the track queued on a deck */
+  clearNext(deck: number): void
+  /**
+   * Instant doubles: put the track from one deck on the other, at the same position,
+   * BPM, tempo and beat grid, and play it in phase with what the source deck is playing
+   * (soft-started like play()). The PCM is shared, not copied
+   * Like a load, the target's loop, queued track and cues are dropped
+   * Emits update reason "load"
+   */
+  instantDouble(fromDeck: number, toDeck: number): void
   /** Start playback on a deck */
   play(deck: number): void
   /** Stop playback on a deck */
   stop(deck: number): void
+  /**
+   * Silence all output and freeze every deck in place
+   * Unlike stop(), playing flags and positions are preserved for resume_all()
+   */
+  pauseAll(): void
+  /** Resume output after pause_all() exactly where it left off */
+  resumeAll(): void
+  /**
+   * Emergency silence (e.g. feedback): the output drops all queued audio at once and plays
+   * silence until release_panic(); both decks, the samplers, the mic and any automation stop
+   * Unlike pause_all() nothing resumes afterwards, and the output stream keeps running
+   */
+  panic(): void
+  /** Let audio through again after panic() (decks, samplers and mic stay stopped) */
+  releasePanic(): void
+  /** Set the temporary cue point at the deck's current position (CDJ CUE button) */
+  setCue(deck: number): void
+  /** Start previewing from the temporary cue point (CUE held while stopped) */
+  cuePreview(deck: number): void
+  /** Stop the cue preview and return to the temporary cue point (CUE released) */
+  cueRelease(deck: number): void
   /** Seek within a deck (position: 0.0 to 1.0) */
   seek(deck: number, position: number): void
+  /** Jump to the start of the track */
+  toStart(deck: number): void
+  /** Jump to the last frame of the track (a playing deck ends on the next chunk) */
+  toEnd(deck: number): void
   /** Set crossfader position (0.0 = full A, 1.0 = full B) */
   setCrossfaderPosition(position: number): void
-  /** Start auto crossfade */
-  startCrossfade(targetPosition: number | undefined | null, duration: number): void
+  /**
+   * Set the crossfader center dead zone: positions within `width` of 0.5 play as exactly
+   * center, so a jittery hardware fader doesn't wobble a blend (0 disables, max 0.25)
+   * The rest of the travel is stretched to still reach the ends without a jump
+   */
+  setCrossfaderDeadzone(width: number): void
+  /**
+   * Start auto crossfade
+   * stop_on_complete (default true) stops the outgoing deck when the fade completes;
+   * false keeps it playing so it can be brought back
+   */
+  startCrossfade(targetPosition: number | undefined | null, duration: number, stopOnComplete?: boolean | undefined | null): void
+  /**
+   * Transition from one deck to the other over `duration` seconds
+   * Sweeps the outgoing deck's high-pass from 20 Hz to 8 kHz while auto-crossfading toward
+   * the incoming deck (started if stopped), then stops the outgoing deck and restores its
+   * high-pass setting
+   */
+  filterFade(fromDeck: number, toDeck: number, duration: number): void
+  /**
+   * Start a deck exactly on master clock beat `at_beat` (see masterBeat in the state)
+   * e.g. the next downbeat is Math.ceil(masterBeat / 4) * 4
+   */
+  schedulePlay(deck: number, atBeat: number): void
+  /** Seek a deck (position: 0.0 to 1.0) exactly on master clock beat `at_beat` */
+  scheduleSeek(deck: number, atBeat: number, position: number): void
+  /** Start an auto crossfade exactly on master clock beat `at_beat` */
+  scheduleCrossfade(atBeat: number, targetPosition: number | undefined | null, duration: number): void
+  /** Drop pending scheduled actions for a deck (null = all, including crossfades) */
+  cancelScheduled(deck?: number | undefined | null): void
   /** Set master tempo (BPM) */
   setMasterTempo(bpm: number): void
-  /** Set deck gain (0.0 to 1.0) */
+  /** Designate the sync lock tempo reference (1 = A, 2 = B, null = internal clock) */
+  setSyncMaster(deck?: number | undefined | null): void
+  /**
+   * Enable sync lock for a deck so it stays phase-aligned to the master clock
+   * first_beat_seconds anchors the deck's beat grid (defaults to the previous value; 0 after a load)
+   */
+  setDeckSync(deck: number, enabled: boolean, firstBeatSeconds?: number | undefined | null): void
+  /**
+   * Get a deck's beat grid (None without a BPM)
+   * Without an imported grid the beats are the uniform grid over the loaded track
+   */
+  getBeatGrid(deck: number): BeatGridJs | null
+  /**
+   * Apply a saved beat grid to a deck (e.g. from getBeatGrid)
+   * Sets the deck BPM and anchor; with two or more beats, quantize, sync and beat phase
+   * follow the listed beats (needed for variable-tempo tracks)
+   */
+  setBeatGridFull(deck: number, grid: BeatGridJs): void
+  /** Set deck gain (0.0 to 1.0), mapped to linear gain through the deck's fader curve */
   setDeckGain(deck: number, gain: number): void
+  /**
+   * Set a deck's fader curve: "linear" (-6 dB at half travel), "square" (default, -12 dB at
+   * half travel) or "logarithmic" (linear in dB from -60 dB to 0 dB, -30 dB at half travel)
+   * The current fader position is re-mapped immediately
+   */
+  setFaderCurve(deck: number, curve: string): void
+  /**
+   * Set deck trim / input gain in dB (-inf to +12), applied before EQ and the fader
+   * Changes ramp in over the fader smoothing time
+   */
+  setDeckTrim(deck: number, gainDb: number): void
+  /**
+   * Set the target deck's trim so its recent RMS level matches the reference deck's
+   * One-shot: uses the ~3 s rolling RMS of each deck (post trim/EQ, pre-fader), so both
+   * decks should have been playing for a few seconds. Returns the new trim in dB (max +12)
+   */
+  matchLevels(referenceDeck: number, targetDeck: number): number
+  /**
+   * Set a deck's high-pass cleanup filter cutoff in Hz (20 to 20000; 0 = off)
+   * 12 dB/octave, after trim and before the EQ; independent of the EQ bands
+   */
+  setDeckHpf(deck: number, hz: number): void
+  /** Set a deck's low-pass cleanup filter cutoff in Hz (20 to 20000; 0 = off) */
+  setDeckLpf(deck: number, hz: number): void
+  /**
+   * Mute a deck on the master (the cue bus is unaffected)
+   * Independent of fader and crossfader, so unmuting restores the previous level
+   */
+  setDeckMute(deck: number, muted: boolean): void
+  /** Solo a deck: while any deck is soloed, non-soloed decks are muted on the master */
+  setDeckSolo(deck: number, soloed: boolean): void
+  /**
+   * Set a deck's pitch fader range: "6", "10", "16" (±percent) or "wide" (0.5x to 2x)
+   * The playback rate, including the tempo match, is clamped to this range
+   */
+  setPitchRange(deck: number, range: string): void
+  /**
+   * Move a deck's pitch fader (percent, limited to the pitch range)
+   * Applied on top of the master tempo match, independent of sync lock
+   */
+  setDeckPitchAdjust(deck: number, percent: number): void
+  /** Set deck pan (-1.0 left, 0.0 center, 1.0 right) */
+  setDeckPan(deck: number, pan: number): void
+  /**
+   * Assign a deck to a crossfader side: "a", "b" or "thru" (ignores the crossfader)
+   * Defaults: deck A on side A, deck B on side B
+   */
+  setCrossfaderAssign(deck: number, side: string): void
+  /**
+   * Move a deck's jog wheel by delta_frames (negative = backwards)
+   * Scratch mode plays the movement at the implied speed and direction, even
+   * while paused; bend mode nudges the tempo of a playing deck instead.
+   * Send deltas at least every chunk for a continuous scratch.
+   */
+  jog(deck: number, deltaFrames: number): void
+  /** Set what a deck's jog wheel does: "scratch" (default) or "bend" */
+  setJogMode(deck: number, mode: string): void
+  /**
+   * Report deck positions as currently audible, i.e. minus outputLatencyMs
+   * (for playheads, visuals and lighting that must match what is heard)
+   */
+  setLatencyCompensation(enabled: boolean): void
+  /** Reset the output underrun counter reported as underrunCount */
+  resetUnderrunCount(): void
+  /** Clear the latched clip indicators (deck A, deck B and master) */
+  resetClipIndicators(): void
+  /**
+   * Set peak-hold meter ballistics: hold time (0 to 10000 ms, default 1500) and
+   * fall rate afterwards (0.5 to 100 dB/s, default 6)
+   */
+  setMeterBallistics(holdMs: number, decayDbPerSec: number): void
+  /**
+   * Set smoothing time for deck gain, crossfader and mic gain changes (milliseconds)
+   * 0 disables smoothing (changes apply instantly)
+   */
+  setSmoothingTime(ms: number): void
+  /**
+   * Enable soft-clip saturation on the master (drive: 1.0 to 10.0)
+   * A hard clamp is still applied afterwards as a safety net
+   */
+  setSoftClip(enabled: boolean, drive?: number | undefined | null): void
+  /**
+   * Set the master output trim in dB (finite, up to +12, default 0)
+   * Applied to the final master mix before soft clip and the output clamp; the cue bus is unaffected
+   */
+  setMasterGain(gainDb: number): void
+  /**
+   * Set the master tone control: low shelf (100 Hz), mid peak (1 kHz), high shelf (10 kHz)
+   * Gains in dB, clamped to -12..+12; all at 0 dB bypasses the EQ
+   */
+  setMasterEq(lowDb: number, midDb: number, highDb: number): void
+  /** Bypass the master tone control, keeping its band gains */
+  setMasterEqBypass(bypassed: boolean): void
+  /**
+   * Enable the master limiter
+   * input_gain_db (-24 to +24) drives the limiter; headroom_db (0 to 40) sets its ceiling
+   * below 0 dBFS. Gain reduction is reported as masterGainReductionDb
+   */
+  setMasterLimiter(enabled: boolean, inputGainDb: number, headroomDb: number): void
+  /**
+   * Tune the master limiter: attack (0 to 100 ms), release (1 to 2000 ms) and lookahead
+   * (0 to 20 ms). The lookahead delays the master by its length (masterLimiterLatencyMs),
+   * which latency compensation accounts for while the limiter is enabled
+   */
+  setLimiterParams(attackMs: number, releaseMs: number, lookaheadMs: number): void
+  /**
+   * Set the master stereo width (0.0 = mono, 1.0 = normal, up to 2.0 = wider)
+   * Scales the side signal of a mid/side split; the cue bus is unaffected
+   */
+  setStereoWidth(width: number): void
+  /** Sum the master to mono (club systems, broadcast); the cue bus stays stereo */
+  setMonoOutput(enabled: boolean): void
+  /** Set headphone (cue) output gain (0.0 to 2.0), independent of the master */
+  setCueGain(value: number): void
+  /** Enable the cue limiter with an output ceiling in dBFS (-40 to 0) */
+  setCueLimiter(enabled: boolean, ceilingDb: number): void
+  /**
+   * Set latency profile: "low", "balanced" or "safe" (default)
+   * Lower profiles use smaller chunks and a shallower output queue at the cost of
+   * underrun headroom; switch back to "safe" if the output glitches
+   */
+  setLatencyProfile(profile: string): void
   /**
    * Set EQ cut (kill switch) for a specific band on a deck
-   * band: "low", "mid", "high"
+   * A cut band drops to the kill depth (-26 dB by default, see setEqKillDepth)
+   * band: "low", "mid", "high" (3-band) or "low", "lowmid", "highmid", "high" (4-band)
    */
   setEqCut(deck: number, band: string, enabled: boolean): void
+  /**
+   * Set EQ band gain for a deck (dB, clamped to -26..+6)
+   * band: same names as setEqCut for the deck's current mode
+   */
+  setEqGain(deck: number, band: string, gainDb: number): void
+  /**
+   * Set how far EQ cuts attenuate a band on a deck (dB, default -26)
+   * Clamped to -60..0; pass -Infinity for a true kill
+   */
+  setEqKillDepth(deck: number, depthDb: number): void
+  /**
+   * Select the EQ layout for a deck: "3band" (default) or "4band"
+   * Low/high cut and gain carry over; mid bands reset
+   */
+  setEqMode(deck: number, mode: string): void
+  /**
+   * Set EQ crossover frequencies for a deck (Hz)
+   * low_mid is clamped to 40-800 Hz, mid_high to 1-16 kHz; low_mid must be below mid_high
+   */
+  setEqCrossovers(deck: number, lowMid: number, midHigh: number): void
   /** Get EQ cut state for a deck */
   getEqCutState(deck: number): EqCutStateJs
   /** Set cue enabled for a deck */
@@ -40,12 +281,49 @@ export declare class AudioEngine {
   setChannelConfig(mainLeft: number, mainRight: number, cueLeft: number, cueRight: number): void
   /** Get current state */
   getState(): AudioEngineStateUpdate
+  /**
+   * Get downsampled peak data for a window of the deck's PCM around a position
+   * Returns `bins` peak values (0.0-1.0, at most 8192); regions before 0 or past the end are zero
+   */
+  getWaveformWindow(deck: number, centerSeconds: number, widthSeconds: number, bins: number): Array<number>
   /** Enable or disable microphone input */
   setMicEnabled(enabled: boolean): void
   /** Set microphone gain */
   setMicGain(gain: number): void
+  /**
+   * Set the mic pan (-1.0 = left, 0.0 = center, 1.0 = right)
+   * Balances the two channels of a stereo mic; a mono mic is panned
+   */
+  setMicPan(pan: number): void
+  /**
+   * Set how much mic audio is buffered in ms (10 to 500, default 100)
+   * Lower values cut talkover latency but underrun more easily
+   */
+  setMicLatencyMs(ms: number): void
+  /**
+   * Monitor the mic directly on the cue (default) or "main" output, e.g. for a soundcheck
+   * Bypasses talkover: the music isn't ducked and the mic needn't be enabled (mic gain and
+   * pan still apply). Mixed into the output callback straight from the mic input, so it
+   * skips the output queue; talkover keeps its own buffer and works alongside it
+   */
+  setMicMonitor(enabled: boolean, target?: string | undefined | null): void
   /** Set talkover ducking level (0.0 to 1.0 - how much to reduce music) */
   setTalkoverDucking(ducking: number): void
+  /**
+   * Queue external (line-in) audio: interleaved stereo at the engine sample rate
+   * Mixed into the master through its own EQ and fader; push continuously (at most 1 s
+   * is buffered, older audio is dropped) and gaps play as silence
+   */
+  pushExternalAudio(pcm: Float32Array): void
+  /** Set the external input fader (0.0 to 1.0, square curve like setDeckGain's default) */
+  setExternalGain(gain: number): void
+  /** Set an external input EQ band gain (dB, clamped to -26..+6; 3-band names) */
+  setExternalEqGain(band: string, gainDb: number): void
+  /**
+   * Set how fast talkover ducks the music (attack) and restores it (release), in ms
+   * (0 to 5000 each; defaults 50 and 500)
+   */
+  setTalkoverTimes(attackMs: number, releaseMs: number): void
   /** Set loop region for a deck (positions in 0.0-1.0 range) */
   setLoop(deck: number, start: number, end: number, enabled: boolean): void
   /**
@@ -53,16 +331,90 @@ export declare class AudioEngine {
    * start_seconds and end_seconds are calculated from beat grid on TypeScript side
    */
   setBeatLoop(deck: number, startSeconds: number, endSeconds: number): void
+  /**
+   * Loop `beats` beats from the playhead using the deck BPM (auto-loop buttons)
+   * With an active loop under the playhead, only its length is adjusted
+   * Emits update reason "auto_loop"
+   */
+  setAutoLoop(deck: number, beats: number): void
+  /**
+   * Mark the loop in point at the playhead (CDJ LOOP IN)
+   * Ends an active loop; the loop starts when loop_out() sets the out point
+   */
+  loopIn(deck: number): void
+  /**
+   * Mark the loop out point at the playhead and start looping (CDJ LOOP OUT)
+   * While looping this moves the out point; ignored at or before the in point
+   */
+  loopOut(deck: number): void
+  /**
+   * Jump forward (positive) or back (negative) by a number of beats along the beat grid
+   * With quantize on the playhead lands on a beat; clamped to the track, ignored without a BPM
+   */
+  jumpBeats(deck: number, beats: number): void
+  /** Jump forward (positive) or back (negative) by whole 4-beat bars (see jump_beats) */
+  jumpBars(deck: number, bars: number): void
+  /**
+   * Loop roll of `beats` beats (1/32 to 4) from the slip position; calling it again
+   * while rolling changes the size in place. The track keeps running underneath and
+   * roll_off() lands where it would have been without the roll
+   */
+  roll(deck: number, beats: number): void
+  /** End a loop roll and continue from the slip position */
+  rollOff(deck: number): void
+  /** Enable quantize (snap loop starts to the beat grid) for a deck */
+  setQuantize(deck: number, enabled: boolean): void
   /** Clear loop for a deck */
   clearLoop(deck: number): void
-  /** Start recording to a WAV file */
-  startRecording(path: string, format: string): void
+  /** Load stereo interleaved PCM into a sampler slot (0-7) */
+  loadSample(slot: number, pcmData: Float32Array): void
+  /** Trigger a sampler slot (one-shot, or restart if looped) */
+  triggerSample(slot: number): void
+  /** Stop all playing voices of a sampler slot */
+  stopSample(slot: number): void
+  /** Set sampler slot gain (0.0 to 2.0) */
+  setSampleGain(slot: number, gain: number): void
+  /** Set whether a sampler slot loops until stopped */
+  setSampleLoop(slot: number, looped: boolean): void
+  /**
+   * Assign a sampler slot to a choke group (triggering one member stops the others)
+   * Pass null for polyphonic playback
+   */
+  setSampleChokeGroup(slot: number, group?: number | undefined | null): void
+  /**
+   * Start recording to a WAV file
+   * Fails if the file can't be created; later write failures stop the recording and are
+   * reported as recordingError (update reason "recording_error")
+   * Optional metadata is written as Vorbis comments (OGG) or a LIST/INFO chunk (WAV)
+   * target: RecordingTarget.Master (default) or RecordingTarget.Cue (the PFL headphone bus)
+   * maxDurationSeconds: continue in "<name>-002.<ext>", "-003", ... each time a file reaches
+   * this length (gapless; a "recording_split" event carries each new path)
+   * dither: TPDF dither on the 16-bit WAV conversion (default true; OGG ignores it)
+   */
+  startRecording(path: string, format: string, metadata?: RecordingMetadata | undefined | null, target?: RecordingTarget | undefined | null, maxDurationSeconds?: number | undefined | null, dither?: boolean | undefined | null): void
   /** Stop recording */
   stopRecording(): void
-  /** Clean up and stop the engine */
+  /**
+   * Render the mix without an output device (interleaved, device channel layout)
+   * Advances decks, crossfades and loops exactly like live playback; for tests and bouncing.
+   * Fails while an output device is streaming, or for a duration over 10 minutes
+   * (call repeatedly for longer renders).
+   */
+  renderOffline(durationSeconds: number): Float32Array
+  /**
+   * Clean up and stop the engine
+   * Joins the processing thread; safe to call more than once
+   */
   close(): void
 }
 
+/** Cancellation handle for decodeAudioCancellable() */
+export declare class DecodeCancelHandle {
+  constructor()
+  /** Request cancellation; a running decode rejects with code "Cancelled" */
+  cancel(): void
+}
+
 /** Returns the crate version so JS can verify the native module loaded correctly. */
 export declare function addonVersion(): string
 
@@ -75,41 +427,273 @@ export interface AudioDeviceInfo {
 
 /** State update sent to JavaScript */
 export interface AudioEngineStateUpdate {
+  /** Deck A playhead in track (source) seconds; same as deck_a_source_position */
   deckAPosition?: number
+  /** Deck B playhead in track (source) seconds; same as deck_b_source_position */
   deckBPosition?: number
+  /**
+   * Deck A playhead in track (source) seconds, independent of the playback rate
+   * Use for the waveform playhead, cue points and beat grids
+   */
+  deckASourcePosition?: number
+  /** Deck B playhead in track (source) seconds, independent of the playback rate */
+  deckBSourcePosition?: number
+  /**
+   * Deck A playhead in output seconds: time actually played since the track start, counting
+   * tempo changes as they happened. A seek restarts it at the new source position divided by
+   * the playback rate; it keeps running through loops
+   */
+  deckAOutputPosition?: number
+  /** Deck B playhead in output seconds (time played since the track start) */
+  deckBOutputPosition?: number
+  /** Deck A track length in seconds */
+  deckADuration?: number
+  /** Deck B track length in seconds */
+  deckBDuration?: number
+  /** Wall-clock seconds until deck A reaches the track end at its current playback rate */
+  deckARemaining?: number
+  /** Wall-clock seconds until deck B reaches the track end at its current playback rate */
+  deckBRemaining?: number
   deckAPlaying: boolean
   deckBPlaying: boolean
   crossfaderPosition: number
+  /** Crossfader center dead zone width (0 = off) */
+  crossfaderDeadzone: number
   isCrossfading: boolean
+  /** Crossfader assignment for deck A ("a", "b" or "thru") */
+  deckACrossfaderSide: string
+  /** Crossfader assignment for deck B ("a", "b" or "thru") */
+  deckBCrossfaderSide: string
   deckAPeak: number
   deckBPeak: number
   deckAPeakHold: number
   deckBPeakHold: number
+  /** Deck A rolling RMS over about 3 s (post trim/EQ, pre-fader) */
+  deckARms: number
+  /** Deck B rolling RMS over about 3 s (post trim/EQ, pre-fader) */
+  deckBRms: number
+  /** Deck A reached 0 dBFS (post deck gain) since the last resetClipIndicators() */
+  deckAClipped: boolean
+  /** Deck B reached 0 dBFS (post deck gain) since the last resetClipIndicators() */
+  deckBClipped: boolean
+  /** Master reached 0 dBFS before the output clamp since the last resetClipIndicators() */
+  masterClipped: boolean
+  /** Cue (headphone) bus peak, left channel (0 when nothing is cued) */
+  cueLeftPeak: number
+  /** Cue (headphone) bus peak, right channel */
+  cueRightPeak: number
   masterTempo: number
   deckATrackId?: string
   deckBTrackId?: string
+  /** Failure that stopped the current recording (cleared by startRecording) */
+  recordingError?: string
+  /** File currently being recorded; follows the "recording_split" events */
+  recordingPath?: string
+  /** Output callbacks that ran out of audio since start or resetUnderrunCount() */
+  underrunCount: number
+  /** Estimated output latency: queued audio plus the device's reported latency (ms) */
+  outputLatencyMs: number
+  /** Rate the engine renders at (Hz) */
+  engineSampleRate: number
+  /**
+   * Rate the output device runs at (Hz); resampled from the engine rate when different
+   * Absent until a device is configured
+   */
+  deviceSampleRate?: number
+  /** Output device in use (absent while the engine runs without a device) */
+  deviceName?: string
+  /** Why the last configureDevice failed, e.g. no audio device (cleared once one is configured) */
+  deviceError?: string
+  /** Deck A muted on the master */
+  deckAMuted: boolean
+  /** Deck B muted on the master */
+  deckBMuted: boolean
+  /** Deck A soloed */
+  deckASoloed: boolean
+  /** Deck B soloed */
+  deckBSoloed: boolean
+  /** Track ID queued to follow deck A's current track */
+  deckANextTrackId?: string
+  /** Track ID queued to follow deck B's current track */
+  deckBNextTrackId?: string
   deckAGain: number
   deckBGain: number
+  /** Deck A fader curve ("linear", "square" or "logarithmic") */
+  deckAFaderCurve: string
+  /** Deck B fader curve ("linear", "square" or "logarithmic") */
+  deckBFaderCurve: string
+  /** Deck A pan (-1.0 left to 1.0 right) */
+  deckAPan: number
+  /** Deck B pan (-1.0 left to 1.0 right) */
+  deckBPan: number
+  /** Deck A trim in dB */
+  deckATrimDb: number
+  /** Deck B trim in dB */
+  deckBTrimDb: number
+  /** Deck A high-pass trim filter cutoff (Hz, 0 = off) */
+  deckAHpfHz: number
+  /** Deck B high-pass trim filter cutoff (Hz, 0 = off) */
+  deckBHpfHz: number
+  /** Deck A low-pass trim filter cutoff (Hz, 0 = off) */
+  deckALpfHz: number
+  /** Deck B low-pass trim filter cutoff (Hz, 0 = off) */
+  deckBLpfHz: number
+  /** Deck A pitch fader position (percent) */
+  deckAPitchAdjust: number
+  /** Deck B pitch fader position (percent) */
+  deckBPitchAdjust: number
   deckACueEnabled: boolean
   deckBCueEnabled: boolean
   /** EQ cut state for deck A */
   deckAEqCut: EqCutStateJs
   /** EQ cut state for deck B */
   deckBEqCut: EqCutStateJs
+  /** EQ mode for deck A ("3band" or "4band") */
+  deckAEqMode: string
+  /** EQ mode for deck B ("3band" or "4band") */
+  deckBEqMode: string
+  /** EQ band gains for deck A (dB, lowest band first) */
+  deckAEqGainsDb: Array<number>
+  /** EQ band gains for deck B (dB, lowest band first) */
+  deckBEqGainsDb: Array<number>
+  /**
+   * Deck A level per EQ band [low, mid, high] (RMS after the EQ, 0 when stopped)
+   * An equal split of the deck level while the EQ is flat (bands aren't measured then)
+   */
+  deckABandLevels: Array<number>
+  /** Deck B level per EQ band [low, mid, high] */
+  deckBBandLevels: Array<number>
   /** Loop state for deck A */
   deckALoop: LoopStateJs
   /** Loop state for deck B */
   deckBLoop: LoopStateJs
+  /** Temporary cue point for deck A (seconds) */
+  deckACuePoint?: number
+  /** Temporary cue point for deck B (seconds) */
+  deckBCuePoint?: number
+  /** Source headroom for deck A in dB (negative = track is hot) */
+  deckAHeadroomDb?: number
+  /** Source headroom for deck B in dB (negative = track is hot) */
+  deckBHeadroomDb?: number
   /** Microphone available (input stream created successfully) */
   micAvailable: boolean
   /** Microphone enabled */
   micEnabled: boolean
   /** Microphone peak level */
   micPeak: number
+  /** Microphone peak level per channel (equal for a mono mic) */
+  micPeakLeft: number
+  micPeakRight: number
+  /** Microphone input is stereo */
+  micStereo: boolean
+  /** Microphone pan (-1.0 = left, 0.0 = center, 1.0 = right) */
+  micPan: number
+  /** Microphone buffer depth (ms), the talkover latency it adds */
+  micLatencyMs: number
+  /** Mic monitoring on (set_mic_monitor) */
+  micMonitor: boolean
+  /** Output the mic monitor plays on ("cue" or "main") */
+  micMonitorTarget: string
+  /** External input peak level (post fader) */
+  externalPeak: number
+  /** External input fader (0.0 to 1.0) */
+  externalGain: number
+  /** Global transport paused (pause_all) */
+  paused: boolean
+  /** Output silenced by panic() until releasePanic() */
+  panicked: boolean
+  /** Whether each sampler slot is currently playing */
+  samplerPlaying: Array<boolean>
+  /** Sync lock enabled for deck A */
+  deckASynced: boolean
+  /** Sync lock enabled for deck B */
+  deckBSynced: boolean
+  /** Deck driving the master clock (None = internal clock) */
+  syncMaster?: number
+  /** Master summed to mono */
+  monoOutput: boolean
+  /** Master stereo width (0 = mono, 1 = unchanged) */
+  stereoWidth: number
+  /** Master output trim (dB, 0 = unity) */
+  masterGain: number
+  /** Master tone control gains in dB [low, mid, high] */
+  masterEqGainsDb: Array<number>
+  /** Master tone control bypassed */
+  masterEqBypassed: boolean
+  /** Master limiter enabled */
+  masterLimiterEnabled: boolean
+  /** Master limiter gain reduction (dB, 0 = not limiting) */
+  masterGainReductionDb: number
+  /** Delay added by the master limiter lookahead (ms) */
+  masterLimiterLatencyMs: number
+  /** Master stereo correlation (+1 fully correlated, -1 out of phase) */
+  masterCorrelation: number
+  /** Master clock position in beats (the timeline for scheduled actions) */
+  masterBeat: number
+  /** Scheduled actions still pending */
+  scheduledActions: number
   /** Reason for this state update: "periodic", "seek", "play", "stop", "load", etc. */
   updateReason: string
 }
 
+/** Beat detection result plus the intermediate data behind it */
+/** Stream info read from the container headers by probeAudio */
+export interface AudioProbe {
+  /** Codec short name (e.g. "mp3", "flac", "vorbis") */
+  codec: string
+  /** Source sample rate (Hz) */
+  sampleRate?: number
+  /** Source channel count */
+  channels?: number
+  /** Duration in seconds (absent if the stream doesn't declare its length) */
+  duration?: number
+  /** Average bitrate from file size and duration (kbit/s) */
+  bitrate?: number
+}
+
+/** One file's outcome, passed to the decodeAudioBatch() progress callback */
+export interface BatchDecodeProgress {
+  /** Position of the file in `paths` */
+  index: number
+  path: string
+  /** Decoded track (absent on failure) */
+  result?: DecodeResult
+  /** Decode error code (see decodeAudio; absent on success) */
+  errorCode?: string
+  /** Decode error message (absent on success) */
+  error?: string
+}
+
+export interface BeatDetectionDebugJs {
+  /** Detection result (absent if detection failed) */
+  result?: BeatDetectionResultJs
+  /** Combined onset detection function (normalized 0-1) */
+  odf: Array<number>
+  /** ODF frame rate (frames per second) */
+  odfSampleRate: number
+  /** Tempo (BPM) of each autocorrelation lag */
+  autocorrelationBpms: Array<number>
+  /** Autocorrelation of the ODF at each lag */
+  autocorrelation: Array<number>
+  /** Candidate tempos from autocorrelation peaks, strongest first */
+  candidateBpms: Array<number>
+  /** Autocorrelation strength of each candidate */
+  candidateStrengths: Array<number>
+}
+
+/** Beat detection options */
+export interface BeatDetectionOptions {
+  /** Shift the grid so a downbeat lands on the first kick (default: false) */
+  anchorToFirstKick?: boolean
+  /**
+   * ODF weights: complex, energy, mel, beat-emphasis, info-gain (default: equal).
+   * Renormalized; ignored unless exactly 5 values are given
+   */
+  odfWeights?: Array<number>
+  /** Fixed beat-emphasis reference tempo in BPM (default: scan the track's strongest periods) */
+  emphasisReferenceBpm?: number
+}
+
 export interface BeatDetectionResultJs {
   /** Detected BPM */
   bpm: number
@@ -119,23 +703,106 @@ export interface BeatDetectionResultJs {
   confidence: number
 }
 
-/** Decode an MP3 file and return PCM data with BPM and structure analysis */
-export declare function decodeAudio(mp3Path: string, targetSampleRate: number, targetChannels: number): DecodeResult
+/** Beat grid of a deck, for saving and restoring without re-analysis */
+export interface BeatGridJs {
+  /** Track BPM */
+  bpm: number
+  /** First beat position in seconds */
+  firstBeat: number
+  /** Every beat position in seconds */
+  beats: Array<number>
+}
+
+/**
+ * Decode an MP3 file and return PCM data with BPM and structure analysis
+ * A target_sample_rate or target_channels of 0 keeps the source's (passthrough, no resampling
+ * or remapping); analysis then runs on a 44.1 kHz copy of the mono mixdown
+ * `options` turns on silence trimming, DC removal, normalization, the peak waveform and
+ * sets the structure conventions (see DecodeOptions)
+ * Errors carry a `code`: FileNotFound, Unreadable, UnsupportedFormat, CorruptData, NoAudioTrack
+ * or EmptyDecode
+ */
+export declare function decodeAudio(mp3Path: string, targetSampleRate: number, targetChannels: number, options?: DecodeOptions | undefined | null): DecodeResult
+
+/**
+ * Decode many files on up to `concurrency` threads (0 = one per CPU core)
+ * `callback` runs once per file as it completes, in completion order, with its result or error;
+ * a failing file doesn't stop the others. Resolves with the number of files decoded successfully
+ * `options` as in decodeAudio, for every file
+ */
+export declare function decodeAudioBatch(paths: Array<string>, targetSampleRate: number, targetChannels: number, concurrency: number, callback: (progress: BatchDecodeProgress) => void, options?: DecodeOptions | undefined | null): Promise<number>
+
+/**
+ * Decode off the JS thread; cancel() on the handle aborts between packets
+ * Rejects with code "Cancelled" once cancelled; other failures carry decodeAudio's `code`s
+ * `options` as in decodeAudio
+ */
+export declare function decodeAudioCancellable(mp3Path: string, targetSampleRate: number, targetChannels: number, handle: DecodeCancelHandle, options?: DecodeOptions | undefined | null): Promise<DecodeResult>
+
+/**
+ * Decode and time-stretch the whole track to `target_bpm` with pitch preserved
+ * The result's `bpm` is the target tempo and `stretchRatio` the applied ratio;
+ * fails with code TempoNotDetected when the source BPM can't be found, or InvalidArgument for
+ * a target BPM that isn't a positive number. `options` apply as in decodeAudio, ahead of the
+ * stretch (the peak waveform and structure are taken from the stretched audio)
+ */
+export declare function decodeAudioWarped(mp3Path: string, targetBpm: number, targetSampleRate: number, targetChannels: number, options?: DecodeOptions | undefined | null): DecodeResult
+
+/** PCM post-processing and analysis options shared by the decode functions (all off by default) */
+export interface DecodeOptions {
+  /** Strip leading/trailing silence before analysis (offset reported as trimOffset) */
+  trimSilence?: boolean
+  /** High-pass the PCM at 5 Hz to remove any DC offset */
+  removeDc?: boolean
+  /** Apply one makeup gain (reported as normalizeGainDb) */
+  normalize?: NormalizeMode
+  /**
+   * Fill `mono` with each frame's largest-magnitude channel sample instead of the channel
+   * average, so out-of-phase stereo doesn't cancel in the display (analysis is unchanged)
+   */
+  peakWaveform?: boolean
+  /** Intro/outro length and search window for sections and hot cues */
+  structure?: StructureOptions
+}
 
 /** Decode result containing PCM data and analysis */
 export interface DecodeResult {
   /** Interleaved stereo PCM data (Float32) */
   pcm: Buffer
-  /** Mono PCM data for waveform display (Float32) */
+  /**
+   * Mono PCM data for waveform display (Float32)
+   * The channel average, or the per-frame channel peak with DecodeOptions' peakWaveform
+   */
   mono: Buffer
   /** Detected BPM (if successful) */
   bpm?: number
+  /**
+   * How sure the BPM detection is, 0-1: the autocorrelation peak's prominence combined with
+   * how well the onsets line up with the detected grid. Clear beats score about 0.7 and up;
+   * below about 0.5 the tempo is doubtful and worth verifying or tapping. Half/double tempo
+   * mistakes still line up with the grid, so they aren't caught by this score
+   */
+  bpmConfidence?: number
   /** Track structure analysis (if BPM detected) */
   structure?: TrackStructure
-  /** Output sample rate */
+  /** Output sample rate (the source rate when targetSampleRate is 0) */
   sampleRate: number
-  /** Number of channels (always 2 for stereo output) */
+  /** Number of channels (the source channel count when targetChannels is 0) */
   channels: number
+  /** Tempo ratio applied by decodeAudioWarped (target BPM / source BPM) */
+  stretchRatio?: number
+  /** Seconds of leading silence removed by DecodeOptions' trimSilence (add to times to map back to the file) */
+  trimOffset?: number
+  /** Makeup gain applied by normalization (dB) */
+  normalizeGainDb?: number
+  /** Low band (below 250 Hz) peak per waveform bin (Float32) */
+  waveformLow: Buffer
+  /** Mid band (250 Hz - 5 kHz) peak per waveform bin (Float32) */
+  waveformMid: Buffer
+  /** High band (above 5 kHz) peak per waveform bin (Float32) */
+  waveformHigh: Buffer
+  /** Mono frames per waveform bin (bin i starts at frame i * waveformBinFrames) */
+  waveformBinFrames: number
 }
 
 /**
@@ -143,9 +810,21 @@ export interface DecodeResult {
  * Based on: J. Zapata, M. Davies and E. Gómez, "Multi-feature beat tracker,"
  * IEEE/ACM Transactions on Audio, Speech and Language Processing, 22(4), 816-825, 2014
  */
-export declare function detectBeats(audio: Float32Array, sampleRate: number): BeatDetectionResultJs | null
+export declare function detectBeats(audio: Float32Array, sampleRate: number, options?: BeatDetectionOptions | undefined | null): BeatDetectionResultJs | null
+
+/**
+ * Same as detectBeats, but also returns the combined ODF, its autocorrelation
+ * and the candidate tempo peaks for plotting and diagnosing mis-detections
+ */
+export declare function detectBeatsDebug(audio: Float32Array, sampleRate: number, options?: BeatDetectionOptions | undefined | null): BeatDetectionDebugJs
 
 /** Device configuration for configureDevice() */
+export interface DeviceCapabilities {
+  name: string
+  input: StreamCapabilities
+  output: StreamCapabilities
+}
+
 export interface DeviceConfig {
   /** Device ID (device name, stable across restarts) */
   deviceId?: string
@@ -153,15 +832,81 @@ export interface DeviceConfig {
   mainChannels?: Array<number>
   /** Cue output channels [left, right], -1 for disabled */
   cueChannels?: Array<number>
+  /**
+   * Resampler used when the device rate differs from the engine rate:
+   * "cubic" (default) or "linear" (cheaper, dulls the top octave slightly)
+   */
+  resampler?: string
+  /**
+   * Output channels to open (default: the device's default channel count);
+   * must be a count the device supports
+   */
+  outputChannels?: number
+}
+
+/** Hot or memory cue read from the file's tags */
+export interface DjCue {
+  /** Hot cue slot (0-based); memory cues are numbered in time order */
+  index: number
+  /** Position in seconds */
+  position: number
+  /** Label (empty when unnamed) */
+  name: string
+  /** Display color as 0xRRGGBB (0 when the tag has none) */
+  color: number
+}
+
+/** Beat grid anchor: a downbeat position and the tempo from there to the next anchor */
+export interface DjGridAnchor {
+  /** Position in seconds */
+  position: number
+  bpm: number
+}
+
+/** Cues and beat grid recovered from DJ software tags by readDjTags */
+export interface DjTags {
+  /** Hot cues sorted by slot */
+  hotCues: Array<DjCue>
+  /** Memory cues in time order */
+  memoryCues: Array<DjCue>
+  /** Beat grid anchors in time order */
+  beatGrid: Array<DjGridAnchor>
+}
+
+/** Engine event for setEventCallback() */
+export interface EngineEvent {
+  /**
+   * "track_ended", "loop_completed", "crossfade_finished", "cue_reached" or
+   * "recording_split"
+   */
+  type: string
+  /**
+   * Deck the event belongs to (the target deck for "crossfade_finished", 0 for
+   * "recording_split")
+   */
+  deck: number
+  /** File a split recording continues in ("recording_split" only) */
+  path?: string
 }
 
 /** EQ cut state for a deck */
 export interface EqCutStateJs {
   low: boolean
+  /** 4-band mode only */
+  lowMid: boolean
+  /** 3-band mode only */
   mid: boolean
+  /** 4-band mode only */
+  highMid: boolean
   high: boolean
 }
 
+/**
+ * Full capability list of a device (default output device when no ID is given)
+ * A device without input (or output) reports empty lists for that direction
+ */
+export declare function getDeviceCapabilities(deviceId?: string | undefined | null): DeviceCapabilities
+
 export declare function listAudioDevices(): Array<AudioDeviceInfo>
 
 /** Loop state for a deck */
@@ -172,13 +917,91 @@ export interface LoopStateJs {
   start: number
   /** Loop end position (0.0-1.0) */
   end: number
+  /** Loop roll size in beats (None when the loop isn't a roll) */
+  rollBeats?: number
+  /** Where playback continues when the roll ends (0.0-1.0) */
+  slipPosition?: number
+}
+
+/** Decode-time normalization target; set one of the two */
+export interface NormalizeMode {
+  /** Normalize the sample peak to this level (dBFS) */
+  peakTo?: number
+  /**
+   * Normalize integrated loudness to this level (LUFS); takes precedence over peakTo
+   * The gain is capped so the sample peak stays at or below 0 dBFS
+   */
+  loudnessTo?: number
 }
 
+/**
+ * Check that a file is decodable and read its format and duration without decoding audio
+ * Only the container/stream headers are parsed, so this is fast enough for scanning a library
+ * Errors carry the same `code`s as decodeAudio (FileNotFound, Unreadable, UnsupportedFormat,
+ * NoAudioTrack)
+ */
+export declare function probeAudio(path: string): AudioProbe
+
+/**
+ * Read Serato and Rekordbox hot cues, memory cues and beat grid anchors from a file's
+ * ID3v2 tag. Returns empty lists when the file has no such tags or they can't be parsed
+ */
+export declare function readDjTags(path: string): DjTags
+
 export declare const enum RecordingFormat {
   Wav = 0,
   Ogg = 1
 }
 
+/** Optional tags embedded in the recorded file (unset fields are omitted) */
+export interface RecordingMetadata {
+  title?: string
+  artist?: string
+  date?: string
+  comment?: string
+}
+
+/** Bus captured by the recorder */
+export declare const enum RecordingTarget {
+  Master = 0,
+  Cue = 1
+}
+
+/** Capabilities of one direction (input or output) of a device */
+export interface StreamCapabilities {
+  /** Common sample rates inside any supported range, plus the range ends, ascending */
+  sampleRates: Array<number>
+  /** Supported channel counts, ascending */
+  channelCounts: Array<number>
+  /** Supported sample formats */
+  sampleFormats: Array<string>
+  /** The configurations themselves, for picking valid combinations */
+  configs: Array<SupportedConfigInfo>
+}
+
+/** Intro/outro conventions for structure detection; unset fields keep the defaults */
+export interface StructureOptions {
+  /** Intro/outro length used when no energy change marks the boundary (beats, default 16) */
+  sectionBeats?: number
+  /**
+   * How far from the start (and back from the end) to look for the intro end and outro start
+   * (beats, default twice sectionBeats)
+   */
+  searchBeats?: number
+}
+
+/** One supported stream configuration: a channel count and sample format over a rate range */
+export interface SupportedConfigInfo {
+  channels: number
+  /** "f32", "i16", "u16", ... */
+  sampleFormat: string
+  minSampleRate: number
+  maxSampleRate: number
+  /** Buffer size range in frames (absent when the platform can't tell before opening) */
+  minBufferSize?: number
+  maxBufferSize?: number
+}
+
 /** Track section (intro, main, or outro) */
 export interface TrackSection {
   start: number
@@ -574,7 +574,16 @@ if (!nativeBinding) {
 module.exports = nativeBinding
 module.exports.AudioEngine = nativeBinding.AudioEngine
 module.exports.addonVersion = nativeBinding.addonVersion
+module.exports.DecodeCancelHandle = nativeBinding.DecodeCancelHandle
 module.exports.decodeAudio = nativeBinding.decodeAudio
+module.exports.decodeAudioBatch = nativeBinding.decodeAudioBatch
+module.exports.decodeAudioCancellable = nativeBinding.decodeAudioCancellable
+module.exports.decodeAudioWarped = nativeBinding.decodeAudioWarped
 module.exports.detectBeats = nativeBinding.detectBeats
+module.exports.detectBeatsDebug = nativeBinding.detectBeatsDebug
+module.exports.getDeviceCapabilities = nativeBinding.getDeviceCapabilities
 module.exports.listAudioDevices = nativeBinding.listAudioDevices
+module.exports.probeAudio = nativeBinding.probeAudio
+module.exports.readDjTags = nativeBinding.readDjTags
 module.exports.RecordingFormat = nativeBinding.RecordingFormat
+module.exports.RecordingTarget = nativeBinding.RecordingTarget
@@ -7,31 +7,180 @@
 //! - Level metering with peak hold
 //! - Channel routing for main and cue outputs
 //! - Time stretching with pitch preservation (SoundTouch)
-//! - 3-band EQ with kill switches
+//! - 3-/4-band isolator EQ with kill switches (adjustable kill depth)
 //! - Microphone input with talkover (ducking)
+//!
+//! Data flow:
+//! - The processing thread locks `EngineState`, renders a chunk, and pushes it
+//!   into a lock-free SPSC ring buffer (`rtrb`) after releasing the lock
+//! - The cpal output callback owns the consumer end and only pops samples, so it
+//!   never waits on the engine mutex; an empty ring plays silence
+//! - The ring is recreated on every `configure_device()` since its contents are
+//!   interleaved for the previous device's channel count
 
 use std::collections::VecDeque;
-use std::f32::consts::PI;
+use std::f32::consts::{PI, SQRT_2};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::SampleFormat;
+use cpal::{FromSample, SampleFormat, SizedSample};
 use napi::bindgen_prelude::*;
-use napi::threadsafe_function::ThreadsafeFunctionCallMode;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 use parking_lot::Mutex;
+use rtrb::{Consumer, Producer, RingBuffer};
 use soundtouch::{Setting, SoundTouch};
 
-use crate::recorder::RecordingThread;
+use crate::recorder::{RecordingMetadata, RecordingTarget, RecordingThread};
 use thread_priority::{set_current_thread_priority, ThreadPriority};
 
-use crate::eq_processor::{EqBand, EqProcessor};
+use crate::eq_processor::{EqBand, EqCutState, EqMode, EqProcessor, MasterEq, TrimFilters};
+use crate::limiter::PeakLimiter;
+use crate::sampler::{SamplerBank, SAMPLER_SLOTS};
+use crate::simd;
 
 const DEFAULT_SAMPLE_RATE: u32 = 44_100;
 const DEFAULT_CHANNELS: u16 = 2;
-const FRAMES_PER_CHUNK: usize = 2048;
+/// Largest chunk size of any latency profile (per-deck buffers are sized for this)
+const MAX_FRAMES_PER_CHUNK: usize = 2048;
+/// Maximum rate correction applied by sync lock (±2%)
+const SYNC_MAX_CORRECTION: f32 = 0.02;
+/// Sync lock correction per beat of phase error
+const SYNC_CORRECTION_GAIN: f32 = 0.1;
+/// How long close() waits for the processing thread to exit
+const PROCESS_THREAD_JOIN_TIMEOUT: Duration = Duration::from_secs(1);
+/// Default time constant for gain/crossfader smoothing (seconds)
+const DEFAULT_SMOOTHING_TIME: f32 = 0.010;
+/// Time constant for the master correlation meter (seconds)
+const CORRELATION_SMOOTHING_TIME: f32 = 0.3;
+/// Maximum tempo nudge from a pitch-bend jog (±50% for one chunk)
+const JOG_BEND_MAX: f64 = 0.5;
+/// Frames a scratched platter is held still without jog input before playback resumes
+const SCRATCH_HOLD_FRAMES: usize = 4096;
+/// Overlap between a track's tail and the queued track's head (~6 ms at 44.1 kHz)
+const GAPLESS_CROSSFADE_FRAMES: usize = 256;
+/// Microphone buffer depth range (ms); below the minimum the input callback period underruns
+const MIC_LATENCY_RANGE_MS: (f32, f32) = (10.0, 500.0);
+/// Mic input the monitor keeps beyond the current output callback (ms); older input is dropped
+const MIC_MONITOR_SLACK_MS: f32 = 5.0;
+/// Mic input ring between the input and output callbacks (ms); input beyond it is dropped
+const MIC_MONITOR_RING_MS: f32 = 100.0;
+/// Per-frame decay of the last mic frame once the monitor input runs dry (~10 ms to -40 dB)
+const MIC_MONITOR_UNDERRUN_DECAY: f32 = 0.99;
+/// Direct-playback bridge after a jump while the cleared stretcher refills (~23 ms at 44.1 kHz)
+const JUMP_BRIDGE_FRAMES: usize = 1024;
+/// Fade-in on play and fade-out on stop (~3 ms at 44.1 kHz), so neither clicks
+const SOFT_START_FRAMES: usize = 128;
+/// Filter fade sweeps the outgoing deck's high-pass exponentially between these cutoffs (Hz)
+const FILTER_FADE_START_HZ: f32 = 20.0;
+const FILTER_FADE_END_HZ: f32 = 8000.0;
+/// Widest crossfader center dead zone (either side of center)
+const CROSSFADER_DEADZONE_MAX: f64 = 0.25;
+/// Time constant of the per-deck rolling RMS used by match_levels() (seconds)
+const RMS_WINDOW_SECONDS: f32 = 3.0;
+/// Beats per bar for bar jumps (4/4)
+const BEATS_PER_BAR: f64 = 4.0;
+/// Loop roll sizes in beats (1/32 to 4 beats)
+const ROLL_MIN_BEATS: f64 = 1.0 / 32.0;
+const ROLL_MAX_BEATS: f64 = 4.0;
+/// Range of the logarithmic fader curve below 0 dB (the bottom of travel mutes)
+const FADER_LOG_RANGE_DB: f32 = 60.0;
+/// Most bins get_waveform_window() returns (about two per pixel of a 4K-wide display)
+const MAX_WAVEFORM_BINS: usize = 8192;
+/// Largest device callback an integer-format output converts without splitting it
+const MAX_CALLBACK_FRAMES: usize = 8192;
+/// Longest render_offline() call in seconds
+const MAX_OFFLINE_SECONDS: f64 = 600.0;
+
+/// Latency profile: processing chunk size and output queue depth
+///
+/// Smaller chunks and a shallower queue reduce latency (useful for monitoring)
+/// but leave less headroom for scheduling jitter before the output underruns.
+/// - `low`: 256-frame chunks, ~10 ms queue target (monitoring; needs a quiet machine)
+/// - `balanced`: 1024-frame chunks, ~50 ms queue target
+/// - `safe`: 2048-frame chunks, ~100 ms queue target (default, robust for DJing)
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct LatencyProfile {
+  /// Frames rendered per processing chunk
+  frames_per_chunk: usize,
+  /// Queue depth target in milliseconds (the thread refills below twice this)
+  target_queue_ms: u32,
+}
+
+impl LatencyProfile {
+  const LOW: Self = Self {
+    frames_per_chunk: 256,
+    target_queue_ms: 10,
+  };
+  const BALANCED: Self = Self {
+    frames_per_chunk: 1024,
+    target_queue_ms: 50,
+  };
+  const SAFE: Self = Self {
+    frames_per_chunk: MAX_FRAMES_PER_CHUNK,
+    target_queue_ms: 100,
+  };
+
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "low" => Some(Self::LOW),
+      "balanced" => Some(Self::BALANCED),
+      "safe" => Some(Self::SAFE),
+      _ => None,
+    }
+  }
+
+  /// Queue depth target in frames
+  fn target_queue_frames(&self, sample_rate: u32) -> usize {
+    sample_rate as usize * self.target_queue_ms as usize / 1000
+  }
+
+  /// Whether the processing thread renders another chunk with `queued` samples in the
+  /// output ring (it refills below twice the target depth)
+  fn needs_chunk(&self, sample_rate: u32, output_channels: u16, queued: usize) -> bool {
+    queued < self.target_queue_frames(sample_rate) * output_channels as usize * 2
+  }
+
+  /// Processing thread sleep between chunks (80% of a chunk's duration)
+  fn interval(&self, sample_rate: u32) -> Duration {
+    Duration::from_micros(
+      ((self.frames_per_chunk as f64 / sample_rate as f64) * 1_000_000.0 * 0.8) as u64,
+    )
+  }
+}
+
+/// One-pole smoother for per-sample parameter ramping (avoids zipper noise)
+#[derive(Clone, Copy)]
+struct ParamSmoother {
+  current: f32,
+}
+
+impl ParamSmoother {
+  fn new(value: f32) -> Self {
+    Self { current: value }
+  }
+
+  /// Advance one sample toward `target` and return the smoothed value
+  #[inline]
+  fn next(&mut self, target: f32, coeff: f32) -> f32 {
+    self.current += (target - self.current) * coeff;
+    if (target - self.current).abs() < 1e-6 {
+      self.current = target;
+    }
+    self.current
+  }
+}
+
+/// Per-sample coefficient for a one-pole smoother with the given time constant
+fn smoothing_coefficient(time_seconds: f32, sample_rate: u32) -> f32 {
+  if time_seconds <= 0.0 {
+    return 1.0;
+  }
+  1.0 - (-1.0 / (time_seconds * sample_rate as f32)).exp()
+}
 
 /// Time stretcher wrapper for pitch-preserved tempo adjustment
 struct TimeStretcher {
@@ -55,21 +204,24 @@ impl TimeStretcher {
     Self {
       soundtouch,
       current_tempo: 1.0,
-      output_buffer: vec![0.0; FRAMES_PER_CHUNK * channels as usize * 2],
+      output_buffer: vec![0.0; MAX_FRAMES_PER_CHUNK * channels as usize * 2],
       reservoir: Vec::new(),
     }
   }
 
   /// Process PCM data with time stretching
-  /// Returns the number of input frames consumed
+  /// With a loop range the input is read across the loop end straight into the loop start,
+  /// however many times that takes, so loops shorter than a chunk stay continuous
+  /// Returns the next input frame to feed and the number of loop wraps
   fn process(
     &mut self,
     pcm_data: &[f32],
     position: usize,
+    loop_range: Option<(usize, usize)>,
     tempo: f32,
     frames_needed: usize,
     output: &mut [f32],
-  ) -> usize {
+  ) -> (usize, u32) {
     let channels = DEFAULT_CHANNELS as usize;
     let total_frames = pcm_data.len() / channels;
 
@@ -82,25 +234,34 @@ impl TimeStretcher {
     // Target reservoir size: just enough to satisfy this chunk plus a small buffer
     let target_reservoir = frames_needed * 2;
 
-    let mut frames_fed = 0;
+    let mut cursor = position;
+    let mut wraps = 0;
+    let limit = loop_range.map_or(total_frames, |(_, end)| end.min(total_frames));
 
     // Feed input only if reservoir is below target
     while self.reservoir.len() / channels < target_reservoir {
-      let remaining = total_frames.saturating_sub(position + frames_fed);
+      let remaining = limit.saturating_sub(cursor);
       if remaining == 0 {
         break;
       }
 
       // Feed smaller chunks for lower latency
       let chunk_size = remaining.min(1024);
-      let start_idx = (position + frames_fed) * channels;
+      let start_idx = cursor * channels;
       let end_idx = start_idx + chunk_size * channels;
 
       if end_idx <= pcm_data.len() {
         self
           .soundtouch
           .put_samples(&pcm_data[start_idx..end_idx], chunk_size);
-        frames_fed += chunk_size;
+        cursor += chunk_size;
+      }
+
+      if let Some((start, end)) = loop_range {
+        if cursor >= end {
+          cursor = start;
+          wraps += 1;
+        }
       }
 
       // Process and collect output into reservoir
@@ -128,7 +289,7 @@ impl TimeStretcher {
       }
     }
 
-    frames_fed
+    (cursor, wraps)
   }
 
   /// Collect all available output from SoundTouch into reservoir
@@ -154,10 +315,149 @@ impl TimeStretcher {
   }
 }
 
+/// Loop roll in progress: the roll loops while a slip playhead keeps running underneath
+struct LoopRoll {
+  /// Roll length in beats
+  beats: f64,
+  /// Where the track would be without the roll (fractional frames)
+  slip_position: f64,
+  /// Loop the roll replaced as (enabled, start, end), restored when the roll ends
+  saved_loop: (bool, usize, usize),
+}
+
+/// Track queued to follow the current one gaplessly
+struct QueuedTrack {
+  pcm: Vec<f32>,
+  bpm: Option<f32>,
+  track_id: Option<String>,
+  headroom_db: f32,
+}
+
+/// Transport action queued for a future engine frame
+#[derive(Clone, Copy)]
+enum ScheduledKind {
+  Play,
+  /// Normalized position (0.0 to 1.0)
+  Seek(f64),
+  Crossfade {
+    target_position: Option<f32>,
+    total_frames: usize,
+  },
+}
+
+#[derive(Clone, Copy)]
+struct ScheduledAction {
+  /// Engine frame the action lands on
+  frame: u64,
+  /// Deck the action applies to (None for the crossfader)
+  deck: Option<u32>,
+  kind: ScheduledKind,
+}
+
+/// Output callback counters and the panic flag, shared lock-free with the audio callback
+#[derive(Default)]
+struct OutputStats {
+  /// Callbacks that found the queue empty
+  underruns: AtomicU64,
+  /// Device latency reported by the last callback (microseconds)
+  device_latency_us: AtomicU64,
+  /// Panic: the callback discards queued audio and plays silence
+  silenced: AtomicBool,
+  /// Sample rate the output device runs at (0 until a device is configured)
+  device_sample_rate: AtomicU32,
+}
+
+/// Mic monitor settings for the output callback, published by the process thread each chunk
+/// Gains are f32 bits
+#[derive(Default)]
+struct MicMonitorShared {
+  /// Monitor gain (the mic gain while monitoring, 0 when off)
+  gain: AtomicU32,
+  /// Pan gains for the left and right mic channel
+  pan: [AtomicU32; 2],
+  /// Output channels [left, right] the monitor plays on (u32::MAX = none)
+  channels: [AtomicU32; 2],
+  /// Sample rate of the mic input stream (0 = no input)
+  input_sample_rate: AtomicU32,
+}
+
+/// Mixes the mic monitor into the output callback straight from the mic input ring
+/// Skips the process thread and the output queue, so the monitor lags by one callback at most
+struct MicMonitorMixer {
+  /// Interleaved stereo mic input, filled by the input callback
+  input: Consumer<f32>,
+  shared: Arc<MicMonitorShared>,
+  /// Smoothed monitor gain actually applied
+  gain_smoother: ParamSmoother,
+  /// Last mic frame played, faded out when the input runs dry
+  last: [f32; 2],
+}
+
+impl MicMonitorMixer {
+  fn new(input: Consumer<f32>, shared: Arc<MicMonitorShared>) -> Self {
+    Self {
+      input,
+      shared,
+      gain_smoother: ParamSmoother::new(0.0),
+      last: [0.0; 2],
+    }
+  }
+
+  /// Add the newest mic input to the monitor channels of `data` (`channels` per frame)
+  /// Plays nothing when the mic runs at another rate than the output (`sample_rate`)
+  fn mix(&mut self, data: &mut [f32], channels: usize, sample_rate: u32) {
+    let shared = &self.shared;
+    let same_rate = shared.input_sample_rate.load(Ordering::Relaxed) == sample_rate;
+    let gain = if same_rate {
+      f32::from_bits(shared.gain.load(Ordering::Relaxed))
+    } else {
+      0.0
+    };
+    let active = gain > 0.0 || self.gain_smoother.current > 0.0;
+
+    // Play the newest input: drop all but this callback and the slack (everything when off)
+    let frames = data.len() / channels;
+    let slack = (sample_rate as f32 * MIC_MONITOR_SLACK_MS / 1000.0) as usize;
+    let keep = if active { frames + slack } else { 0 };
+    let stale = (self.input.slots() / 2).saturating_sub(keep);
+    if let Ok(chunk) = self.input.read_chunk(stale * 2) {
+      chunk.commit_all();
+    }
+    if !active {
+      self.last = [0.0; 2];
+      return;
+    }
+
+    let pan = shared
+      .pan
+      .each_ref()
+      .map(|p| f32::from_bits(p.load(Ordering::Relaxed)));
+    let outputs = shared
+      .channels
+      .each_ref()
+      .map(|c| c.load(Ordering::Relaxed) as usize);
+    let coeff = smoothing_coefficient(DEFAULT_SMOOTHING_TIME, sample_rate);
+    for frame in data.chunks_exact_mut(channels) {
+      let gain = self.gain_smoother.next(gain, coeff);
+      if self.input.slots() >= 2 {
+        self.last = [0, 1].map(|_| self.input.pop().unwrap_or(0.0));
+      } else {
+        // Ran dry: fade out from the last frame instead of cutting off
+        self.last = self.last.map(|s| s * MIC_MONITOR_UNDERRUN_DECAY);
+      }
+      for ((mic, pan), output) in self.last.iter().zip(pan).zip(outputs) {
+        if let Some(sample) = frame.get_mut(output) {
+          *sample = (*sample + mic * gain * pan).clamp(-1.0, 1.0);
+        }
+      }
+    }
+  }
+}
+
 /// Deck state for a single deck
 struct DeckState {
-  /// PCM data (stereo interleaved f32)
-  pcm_data: Option<Vec<f32>>,
+  /// PCM data (stereo interleaved f32), shared when the same track is on both decks
+  pcm_data: Option<Arc<Vec<f32>>>,
   /// Current playback position in frames (updated during audio processing)
   position: usize,
   /// Whether the deck is currently playing
@@ -166,12 +466,34 @@ struct DeckState {
   bpm: Option<f32>,
   /// Playback rate (1.0 = normal speed)
   rate: f32,
+  /// Pitch fader range limiting the playback rate
+  pitch_range: PitchRange,
+  /// Pitch fader position in percent, on top of the tempo match
+  pitch_adjust: f32,
+  /// Channel fader position (0.0 to 1.0)
+  fader: f32,
+  /// How the fader position maps to gain
+  fader_curve: FaderCurve,
   /// Deck gain (0.0 to 1.0)
   gain: f32,
+  /// Smoothed deck gain actually applied to the mix
+  gain_smoother: ParamSmoother,
+  /// Stereo pan (-1.0 = left, 0.0 = center, 1.0 = right)
+  pan: f32,
+  /// Removed from the master (still audible on the cue bus)
+  muted: bool,
+  /// Soloed: while any deck is soloed, only soloed decks reach the master
+  soloed: bool,
+  /// Trim / input gain (linear, up to +12 dB), applied before EQ
+  trim: f32,
+  /// Ramps trim changes to avoid zipper noise
+  trim_smoother: ParamSmoother,
   /// Track ID for state updates
   track_id: Option<String>,
   /// Time stretcher for pitch-preserved tempo adjustment
   time_stretcher: TimeStretcher,
+  /// High-pass / low-pass cleanup filters, after trim and before EQ
+  trim_filters: TrimFilters,
   /// 3-band EQ processor
   eq_processor: EqProcessor,
   /// Loop enabled
@@ -180,6 +502,58 @@ struct DeckState {
   loop_start: usize,
   /// Loop end position in frames
   loop_end: usize,
+  /// Loop roll in progress (the loop fields hold the roll while it lasts)
+  roll: Option<LoopRoll>,
+  /// CDJ-style temporary cue point in frames
+  temp_cue: Option<usize>,
+  /// Headroom of the loaded PCM in dB (negative = clips at the source)
+  headroom_db: Option<f32>,
+  /// Snap loop starts to the nearest beat of the grid
+  quantize: bool,
+  /// Crossfader assignment
+  crossfader_side: CrossfaderSide,
+  /// Sync lock: follow the master clock phase
+  sync_enabled: bool,
+  /// First beat position in seconds (beat grid anchor for phase)
+  first_beat: f64,
+  /// Beat positions in seconds from an imported grid, strictly increasing
+  /// (empty = uniform grid from `bpm` and `first_beat`)
+  beat_grid: Vec<f64>,
+  /// Current sync lock rate correction (fraction of rate)
+  sync_correction: f32,
+  /// What the jog wheel does (scratch or pitch bend)
+  jog_mode: JogMode,
+  /// Jog movement (frames) accumulated since the last processed chunk
+  jog_pending: f64,
+  /// Fractional platter position while scratching (None when not scratching)
+  scratch_position: Option<f64>,
+  /// Frames left before an idle scratch releases back to normal playback
+  scratch_hold: usize,
+  /// Track to continue into when the current one ends
+  next_track: Option<QueuedTrack>,
+  /// Event types raised by the last render, drained into the engine event queue
+  events: Vec<&'static str>,
+  /// Frames left of the direct-playback bridge after a jump (0 = none)
+  jump_bridge: usize,
+  /// Fractional read position of the bridge
+  bridge_position: f64,
+  /// Unplayed tail of the previous track, faded out over the next one's head (empty = none)
+  seam_tail: Vec<f32>,
+  /// Fractional read position in the seam tail
+  seam_position: f64,
+  /// Output frames played since the track start; a jump re-derives it at the current rate
+  output_frames: f64,
+  /// Source frame under the output rendered so far; trails `position`, the stretcher's input
+  /// cursor, by what the stretcher still holds (negative while a queued track's head is buffered)
+  audible_position: f64,
+  /// Frames left of the soft-start fade-in (0 = none)
+  fade_in: usize,
+  /// Frames left of the stop fade-out; the deck stops when it runs out (0 = none)
+  fade_out: usize,
+  /// Where a fading-out deck moves once it has stopped (cue release)
+  stop_position: Option<usize>,
+  /// Whether the deck was playing at the end of the last rendered segment
+  was_playing: bool,
 }
 
 impl DeckState {
@@ -190,17 +564,585 @@ impl DeckState {
       playing: false,
       bpm: None,
       rate: 1.0,
+      pitch_range: PitchRange::Wide,
+      pitch_adjust: 0.0,
+      fader: 1.0,
+      fader_curve: FaderCurve::Square,
       gain: 1.0,
+      gain_smoother: ParamSmoother::new(1.0),
+      pan: 0.0,
+      muted: false,
+      soloed: false,
+      trim: 1.0,
+      trim_smoother: ParamSmoother::new(1.0),
       track_id: None,
       time_stretcher: TimeStretcher::new(sample_rate, DEFAULT_CHANNELS),
-      eq_processor: EqProcessor::new(FRAMES_PER_CHUNK),
+      trim_filters: TrimFilters::default(),
+      eq_processor: EqProcessor::new(MAX_FRAMES_PER_CHUNK),
       loop_enabled: false,
       loop_start: 0,
       loop_end: 0,
+      roll: None,
+      temp_cue: None,
+      headroom_db: None,
+      quantize: false,
+      crossfader_side: CrossfaderSide::A,
+      sync_enabled: false,
+      first_beat: 0.0,
+      beat_grid: Vec::new(),
+      sync_correction: 0.0,
+      jog_mode: JogMode::Scratch,
+      jog_pending: 0.0,
+      scratch_position: None,
+      scratch_hold: 0,
+      next_track: None,
+      events: Vec::new(),
+      jump_bridge: 0,
+      bridge_position: 0.0,
+      seam_tail: Vec::with_capacity(GAPLESS_CROSSFADE_FRAMES * DEFAULT_CHANNELS as usize),
+      seam_position: 0.0,
+      output_frames: 0.0,
+      audible_position: 0.0,
+      fade_in: 0,
+      fade_out: 0,
+      stop_position: None,
+      was_playing: false,
+    }
+  }
+
+  /// Beat phase of the playhead in beats since the first beat (None without BPM)
+  /// An imported grid is interpolated between its beats and extended at the BPM outside them
+  fn beat_phase(&self, sample_rate: u32) -> Option<f64> {
+    self.beat_phase_at(self.position, sample_rate)
+  }
+
+  /// Beat phase of any frame of the track (see `beat_phase`)
+  fn beat_phase_at(&self, frame: usize, sample_rate: u32) -> Option<f64> {
+    let bpm = self.bpm.filter(|&b| b > 0.0)? as f64;
+    let seconds = frame as f64 / sample_rate as f64;
+    let grid = &self.beat_grid;
+    if grid.len() < 2 {
+      return Some((seconds - self.first_beat) * bpm / 60.0);
+    }
+
+    let last = grid.len() - 1;
+    Some(match grid.partition_point(|&b| b <= seconds) {
+      0 => (seconds - grid[0]) * bpm / 60.0,
+      i if i > last => last as f64 + (seconds - grid[last]) * bpm / 60.0,
+      i => (i - 1) as f64 + (seconds - grid[i - 1]) / (grid[i] - grid[i - 1]),
+    })
+  }
+
+  /// Time in seconds of a beat index on the grid (inverse of `beat_phase`)
+  fn beat_seconds(&self, beat: f64, bpm: f64) -> f64 {
+    let grid = &self.beat_grid;
+    if grid.len() < 2 {
+      return self.first_beat + beat * 60.0 / bpm;
+    }
+
+    let last = grid.len() - 1;
+    if beat < 0.0 {
+      grid[0] + beat * 60.0 / bpm
+    } else if beat >= last as f64 {
+      grid[last] + (beat - last as f64) * 60.0 / bpm
+    } else {
+      let i = beat as usize;
+      grid[i] + (beat - i as f64) * (grid[i + 1] - grid[i])
+    }
+  }
+
+  /// Frame of the beat grid line nearest to the playhead (None without BPM)
+  fn nearest_beat_frame(&self, sample_rate: u32) -> Option<usize> {
+    let bpm = self.bpm.filter(|&b| b > 0.0)? as f64;
+    let beat = self.beat_phase(sample_rate)?.round();
+    let seconds = self.beat_seconds(beat, bpm);
+    Some((seconds * sample_rate as f64).max(0.0) as usize)
+  }
+
+  /// Frame `beats` beats away from the playhead along the grid (landing on a beat with
+  /// quantize), clamped to the track; None without a track or BPM
+  fn beat_jump_frame(&self, beats: f64, sample_rate: u32) -> Option<usize> {
+    let bpm = self.bpm.filter(|&b| b > 0.0)? as f64;
+    let total_frames = self.pcm_data.as_ref()?.len() / DEFAULT_CHANNELS as usize;
+    let mut beat = self.beat_phase(sample_rate)? + beats;
+    if self.quantize {
+      beat = beat.round();
+    }
+    let frame = (self.beat_seconds(beat, bpm) * sample_rate as f64).max(0.0) as usize;
+    Some(frame.min(total_frames.saturating_sub(1)))
+  }
+
+  /// Start a loop roll of `beats` beats, or resize the running one, from the slip position
+  /// With quantize on the roll starts on its own beat division; returns false without a BPM
+  fn roll(&mut self, beats: f64, sample_rate: u32) -> bool {
+    let Some(bpm) = self.bpm.filter(|&b| b > 0.0) else {
+      return false;
+    };
+    let Some(total_frames) = self
+      .pcm_data
+      .as_ref()
+      .map(|pcm| pcm.len() / DEFAULT_CHANNELS as usize)
+    else {
+      return false;
+    };
+
+    let roll = self.roll.get_or_insert(LoopRoll {
+      beats,
+      slip_position: self.position as f64,
+      saved_loop: (self.loop_enabled, self.loop_start, self.loop_end),
+    });
+    roll.beats = beats;
+    let slip = (roll.slip_position as usize).min(total_frames.saturating_sub(1));
+
+    let Some(mut start_beat) = self.beat_phase_at(slip, sample_rate) else {
+      return false;
+    };
+    if self.quantize {
+      start_beat = (start_beat / beats).floor() * beats;
+    }
+    let to_frame = |beat: f64| {
+      let frame = (self.beat_seconds(beat, bpm as f64) * sample_rate as f64).round();
+      (frame.max(0.0) as usize).min(total_frames)
+    };
+    let loop_start = to_frame(start_beat);
+    let loop_end = to_frame(start_beat + beats);
+
+    self.loop_start = loop_start;
+    self.loop_end = loop_end;
+    self.loop_enabled = loop_end > loop_start;
+    self.jump(loop_start);
+    true
+  }
+
+  /// End the loop roll: restore the previous loop and continue from the slip position
+  fn roll_off(&mut self) -> bool {
+    let Some(roll) = self.roll.take() else {
+      return false;
+    };
+    (self.loop_enabled, self.loop_start, self.loop_end) = roll.saved_loop;
+    let total_frames = self
+      .pcm_data
+      .as_ref()
+      .map_or(0, |pcm| pcm.len() / DEFAULT_CHANNELS as usize);
+    self.jump((roll.slip_position as usize).min(total_frames.saturating_sub(1)));
+    true
+  }
+
+  /// Start playback, taking over from a stop fade-out still in progress without a jump
+  fn start(&mut self) {
+    if self.fade_out > 0 {
+      self.fade_in = SOFT_START_FRAMES - self.fade_out;
+      self.fade_out = 0;
+      self.stop_position = None;
+    }
+    self.playing = true;
+  }
+
+  /// Playing as the state update reports it: a deck fading out after a stop already counts
+  /// as stopped
+  fn reports_playing(&self) -> bool {
+    self.playing && self.fade_out == 0
+  }
+
+  /// Stop after a short fade-out instead of cutting off mid-waveform, then move to `return_to`
+  fn soft_stop(&mut self, return_to: Option<usize>) {
+    if !self.playing {
+      if let Some(frame) = return_to {
+        self.jump(frame);
+      }
+      return;
+    }
+    if self.fade_out == 0 {
+      self.fade_out = SOFT_START_FRAMES;
+    }
+    self.stop_position = return_to;
+  }
+
+  /// Ramp a rendered segment up after a start and down ahead of a stop
+  fn apply_transport_ramp(&mut self, output: &mut [f32]) {
+    let channels = DEFAULT_CHANNELS as usize;
+    if self.playing && !self.was_playing {
+      self.fade_in = SOFT_START_FRAMES;
+    }
+
+    if self.fade_in > 0 {
+      for frame in output.chunks_exact_mut(channels) {
+        if self.fade_in == 0 {
+          break;
+        }
+        let gain = 1.0 - self.fade_in as f32 / SOFT_START_FRAMES as f32;
+        frame.iter_mut().for_each(|s| *s *= gain);
+        self.fade_in -= 1;
+      }
+    }
+
+    if self.fade_out > 0 {
+      // Silent from the end of the fade to the end of the segment
+      for frame in output.chunks_exact_mut(channels) {
+        let gain = self.fade_out as f32 / SOFT_START_FRAMES as f32;
+        frame.iter_mut().for_each(|s| *s *= gain);
+        self.fade_out = self.fade_out.saturating_sub(1);
+      }
+      if self.fade_out == 0 {
+        self.playing = false;
+        if let Some(frame) = self.stop_position.take() {
+          self.jump(frame);
+        }
+      }
+    }
+
+    self.was_playing = self.playing;
+  }
+
+  /// Start a newly loaded track at `frame`, dropping the previous track's loop, queue,
+  /// cues and any stop or scratch in progress
+  /// The new track counts as not yet playing, so starting it ramps up
+  fn reset_for_track(&mut self, frame: usize) {
+    self.jump(frame);
+    self.was_playing = false;
+    self.loop_enabled = false;
+    self.loop_start = 0;
+    self.loop_end = 0;
+    self.roll = None;
+    self.temp_cue = None;
+    self.next_track = None;
+    self.fade_out = 0;
+    self.stop_position = None;
+    self.scratch_position = None;
+  }
+
+  /// Move the playhead, restarting the stretcher
+  /// Until the stretcher has refilled, playback is bridged by reading the PCM directly
+  fn jump(&mut self, frame: usize) {
+    self.position = frame;
+    self.time_stretcher.clear();
+    self.jump_bridge = JUMP_BRIDGE_FRAMES;
+    self.bridge_position = frame as f64;
+    self.seam_tail.clear();
+    self.output_frames = frame as f64 / self.rate as f64;
+    self.audible_position = frame as f64;
+  }
+
+  /// Crossfade from direct PCM playback at `rate` into the stretcher output over the bridge
+  /// The direct read wraps inside `loop_range` like the stretcher and is silent past `end_frame`
+  fn apply_jump_bridge(
+    &mut self,
+    pcm: &[f32],
+    end_frame: usize,
+    loop_range: Option<(usize, usize)>,
+    rate: f32,
+    output: &mut [f32],
+  ) {
+    let channels = DEFAULT_CHANNELS as usize;
+    for out in output.chunks_exact_mut(channels) {
+      if self.jump_bridge == 0 {
+        break;
+      }
+      if let Some((loop_start, loop_end)) = loop_range {
+        if self.bridge_position >= loop_end as f64 {
+          let start = loop_start as f64;
+          self.bridge_position =
+            start + (self.bridge_position - start) % (loop_end - loop_start) as f64;
+        }
+      }
+      let pos = self.bridge_position;
+      let index = pos as usize;
+      let next = match loop_range {
+        Some((loop_start, loop_end)) if index + 1 >= loop_end => loop_start,
+        _ => index + 1,
+      };
+      let frac = (pos - index as f64) as f32;
+      let fade = 1.0 - self.jump_bridge as f32 / JUMP_BRIDGE_FRAMES as f32;
+      let frame = |i: usize, ch: usize| {
+        if i < end_frame {
+          pcm[i * channels + ch]
+        } else {
+          0.0
+        }
+      };
+      for (ch, sample) in out.iter_mut().enumerate() {
+        let a = frame(index, ch);
+        let b = frame(next, ch);
+        let direct = a + (b - a) * frac;
+        *sample = direct * (1.0 - fade) + *sample * fade;
+      }
+      self.bridge_position += rate as f64;
+      self.jump_bridge -= 1;
+    }
+  }
+
+  /// Fade the previous track's tail out over the head of the new one at `rate`
+  fn apply_seam(&mut self, rate: f32, output: &mut [f32]) {
+    let channels = DEFAULT_CHANNELS as usize;
+    let tail_frames = self.seam_tail.len() / channels;
+    let end = tail_frames as f64;
+    for out in output.chunks_exact_mut(channels) {
+      let pos = self.seam_position;
+      if pos >= end {
+        break;
+      }
+      let index = pos as usize;
+      let next = (index + 1).min(tail_frames - 1);
+      let frac = (pos - index as f64) as f32;
+      let fade_in = (pos / end) as f32;
+      for (ch, sample) in out.iter_mut().enumerate() {
+        let a = self.seam_tail[index * channels + ch];
+        let b = self.seam_tail[next * channels + ch];
+        let tail = a + (b - a) * frac;
+        *sample = tail * (1.0 - fade_in) + *sample * fade_in;
+      }
+      self.seam_position += rate as f64;
+    }
+    if self.seam_position >= end {
+      self.seam_tail.clear();
+    }
+  }
+
+  /// Move the audible position on by `frames` output frames at `rate`, wrapping inside the loop
+  fn advance_audible_position(
+    &mut self,
+    frames: usize,
+    rate: f32,
+    loop_range: Option<(usize, usize)>,
+  ) {
+    self.audible_position += frames as f64 * rate as f64;
+    if let Some((loop_start, loop_end)) = loop_range {
+      if self.audible_position >= loop_end as f64 {
+        let start = loop_start as f64;
+        self.audible_position =
+          start + (self.audible_position - start) % (loop_end - loop_start) as f64;
+      }
+    }
+  }
+
+  /// Playhead frame for a manual loop point, snapped to the beat grid with quantize on
+  fn loop_punch_frame(&self, sample_rate: u32) -> usize {
+    if self.quantize {
+      self
+        .nearest_beat_frame(sample_rate)
+        .unwrap_or(self.position)
+    } else {
+      self.position
+    }
+  }
+
+  /// Enabled loop as (start, end) frames clipped to `end_frame` (None when off or empty)
+  fn active_loop(&self, end_frame: usize) -> Option<(usize, usize)> {
+    let loop_end = self.loop_end.min(end_frame);
+    (self.loop_enabled && loop_end > self.loop_start).then_some((self.loop_start, loop_end))
+  }
+
+  /// Recompute the playback rate from the master tempo and pitch fader
+  fn update_rate(&mut self, master_tempo: f32) {
+    let (min, max) = self.pitch_range.limits();
+    let tempo_rate = calculate_playback_rate(self.bpm, master_tempo);
+    self.rate = (tempo_rate * (1.0 + self.pitch_adjust / 100.0)).clamp(min, max);
+  }
+
+  /// Render one chunk of this deck (time stretcher or scratch, then trim and EQ)
+  /// `coeff` is the per-sample smoothing coefficient for the trim ramp
+  /// Returns the previous track's PCM when playback advanced into the queued track
+  fn render(&mut self, frames: usize, output: &mut [f32], coeff: f32) -> Option<Arc<Vec<f32>>> {
+    // Own handle, so the deck can jump while reading (the deck keeps its reference)
+    let Some(pcm) = self.pcm_data.clone() else {
+      return None;
+    };
+    let channels = DEFAULT_CHANNELS as usize;
+    let total_frames = pcm.len() / channels;
+    // With a track queued, stop feeding short of the end; the tail is crossfaded into its head
+    let end_frame = if self.next_track.is_some() {
+      total_frames.saturating_sub(GAPLESS_CROSSFADE_FRAMES)
+    } else {
+      total_frames
+    };
+    let mut previous = None;
+
+    if self.jog_mode == JogMode::Scratch && self.jog_pending != 0.0 {
+      self.render_scratch(frames, output);
+    } else if self.scratch_position.is_some() && self.scratch_hold > 0 {
+      // Platter held still between jog events: stay silent instead of
+      // snapping back to normal playback
+      self.scratch_hold = self.scratch_hold.saturating_sub(frames);
+      return None;
+    } else if self.playing {
+      self.scratch_position = None;
+      let mut rate = self.rate * (1.0 + self.sync_correction);
+
+      // Pitch bend: nudge the tempo so the pending jog delta is consumed this chunk
+      if self.jog_pending != 0.0 {
+        let nominal = rate as f64 * frames as f64;
+        let bend = (self.jog_pending / nominal).clamp(-JOG_BEND_MAX, JOG_BEND_MAX);
+        rate *= (1.0 + bend) as f32;
+        self.jog_pending = 0.0;
+      }
+
+      // The slip playhead runs on at the playback rate while a roll loops
+      if let Some(roll) = &mut self.roll {
+        roll.slip_position =
+          (roll.slip_position + frames as f64 * rate as f64).min(end_frame as f64);
+      }
+
+      // A loop set behind the playhead jumps back to its start
+      let loop_range = self.active_loop(end_frame);
+      if let Some((loop_start, loop_end)) = loop_range {
+        if self.position >= loop_end {
+          self.jump(loop_start);
+        }
+      }
+
+      // Use time stretcher for tempo adjustment with pitch preservation
+      let (position, wraps) = self.time_stretcher.process(
+        &pcm[..end_frame * channels],
+        self.position,
+        loop_range,
+        rate,
+        frames,
+        output,
+      );
+
+      if self.jump_bridge > 0 {
+        self.apply_jump_bridge(&pcm, end_frame, loop_range, rate, output);
+      }
+      if !self.seam_tail.is_empty() {
+        self.apply_seam(rate, output);
+      }
+
+      let last_position = self.position;
+      self.position = position;
+      self.output_frames += frames as f64;
+      self.advance_audible_position(frames, rate, loop_range);
+
+      // Playing through the temporary cue point
+      if let Some(cue) = self.temp_cue {
+        if wraps == 0 && last_position < cue && self.position >= cue {
+          self.events.push("cue_reached");
+        }
+      }
+
+      for _ in 0..wraps {
+        self.events.push("loop_completed");
+      }
+
+      // Check for track end (a loop never reaches it)
+      if loop_range.is_none() && self.position >= end_frame {
+        if let Some(next) = self.next_track.take() {
+          // The stretcher keeps running so the old tail flows straight into the new head
+          previous = Some(self.advance_to(next, end_frame));
+        } else {
+          self.playing = false;
+          self.position = 0;
+          self.output_frames = 0.0;
+          self.audible_position = 0.0;
+          self.time_stretcher.clear();
+          self.events.push("track_ended");
+        }
+      }
+    } else {
+      self.scratch_position = None;
+      return None;
+    }
+
+    // Apply trim at the head of the chain, before EQ (ramped like the faders)
+    if self.trim != 1.0 || self.trim_smoother.current != 1.0 {
+      for frame in output.chunks_exact_mut(channels) {
+        let trim = self.trim_smoother.next(self.trim, coeff);
+        frame.iter_mut().for_each(|s| *s *= trim);
+      }
+    }
+
+    // Cleanup high-pass / low-pass ahead of the EQ
+    self.trim_filters.process(output, frames);
+
+    // Apply EQ processing
+    self.eq_processor.process(output, frames);
+
+    previous
+  }
+
+  /// Swap in the queued track, keeping the unplayed tail (from `seam`) to fade out over its head
+  /// The new PCM is left untouched; the seam is blended into the rendered output
+  /// Returns the previous PCM; the caller recomputes the rate for the new BPM
+  fn advance_to(&mut self, next: QueuedTrack, seam: usize) -> Arc<Vec<f32>> {
+    let channels = DEFAULT_CHANNELS as usize;
+    let previous = self.pcm_data.take().unwrap_or_default();
+    let tail = &previous[(seam * channels).min(previous.len())..];
+    let overlap = (tail.len() / channels).min(GAPLESS_CROSSFADE_FRAMES);
+    self.seam_tail.clear();
+    self
+      .seam_tail
+      .extend_from_slice(&tail[..overlap * channels]);
+    self.seam_position = 0.0;
+
+    self.pcm_data = Some(Arc::new(next.pcm));
+    self.position = 0;
+    self.output_frames = 0.0;
+    // The old tail still buffered in the stretcher plays before the new head
+    self.audible_position -= seam as f64;
+    self.bpm = next.bpm;
+    self.beat_grid.clear();
+    self.first_beat = 0.0;
+    self.track_id = next.track_id;
+    self.headroom_db = Some(next.headroom_db);
+    self.temp_cue = None;
+    self.jump_bridge = 0;
+    previous
+  }
+
+  /// Play the pending jog movement straight from the PCM at the implied speed
+  ///
+  /// Bypasses the time stretcher (a scratch changes pitch with speed, like vinyl)
+  /// and reads backwards when the movement is negative. The fractional platter
+  /// position carries across chunks so consecutive jog events join seamlessly.
+  fn render_scratch(&mut self, frames: usize, output: &mut [f32]) {
+    let Some(ref pcm) = self.pcm_data else {
+      return;
+    };
+    let channels = DEFAULT_CHANNELS as usize;
+    let total_frames = pcm.len() / channels;
+    if total_frames == 0 {
+      return;
+    }
+    let last = (total_frames - 1) as f64;
+
+    // Keep the fractional position unless the deck was seeked in between
+    let start = self
+      .scratch_position
+      .filter(|&p| p as usize == self.position)
+      .unwrap_or(self.position as f64);
+    let step = self.jog_pending / frames as f64;
+
+    // Linear interpolation between neighbouring frames
+    for (frame, out) in output.chunks_exact_mut(channels).take(frames).enumerate() {
+      let pos = (start + step * frame as f64).clamp(0.0, last);
+      let index = pos as usize;
+      let next = (index + 1).min(total_frames - 1);
+      let frac = (pos - index as f64) as f32;
+      for (ch, sample) in out.iter_mut().enumerate() {
+        let a = pcm[index * channels + ch];
+        let b = pcm[next * channels + ch];
+        *sample = a + (b - a) * frac;
+      }
     }
+
+    let end = (start + self.jog_pending).clamp(0.0, last);
+    self.scratch_position = Some(end);
+    self.scratch_hold = SCRATCH_HOLD_FRAMES;
+    self.jog_pending = 0.0;
+    // Resume from the new position when the platter is released
+    self.jump(end as usize);
   }
 }
 
+/// Filter fade in progress: the outgoing deck's high-pass sweeps up alongside the crossfade
+struct FilterFade {
+  /// Outgoing deck
+  deck: u32,
+  total_frames: usize,
+  remaining_frames: usize,
+  /// High-pass cutoff to restore once the deck has stopped
+  restore_hz: f32,
+}
+
 /// Crossfade state
 struct CrossfadeState {
   /// Current crossfader position (0.0 = full A, 1.0 = full B)
@@ -209,6 +1151,8 @@ struct CrossfadeState {
   active: bool,
   /// Auto crossfade direction
   direction: Option<CrossfadeDirection>,
+  /// Frames to hold before the ramp starts (scheduled crossfades landing mid-chunk)
+  delay_frames: usize,
   /// Remaining frames in auto crossfade
   remaining_frames: usize,
   /// Total frames for auto crossfade
@@ -217,6 +1161,13 @@ struct CrossfadeState {
   start_position: f32,
   /// Target position for auto crossfade
   target_position: f32,
+  /// Stop the outgoing deck when the auto crossfade completes
+  stop_on_complete: bool,
+  /// Positions within this distance of center play as exactly center, the rest of the
+  /// travel is stretched around it (0 = off)
+  deadzone: f32,
+  /// Smoothed crossfader position actually applied to the mix
+  smoother: ParamSmoother,
 }
 
 impl Default for CrossfadeState {
@@ -225,46 +1176,274 @@ impl Default for CrossfadeState {
       position: 0.0,
       active: false,
       direction: None,
+      delay_frames: 0,
       remaining_frames: 0,
       total_frames: 0,
       start_position: 0.0,
+      stop_on_complete: true,
+      deadzone: 0.0,
       target_position: 0.0,
+      smoother: ParamSmoother::new(0.0),
     }
   }
 }
 
+/// Crossfader side a deck is assigned to
 #[derive(Clone, Copy, PartialEq)]
-enum CrossfadeDirection {
-  AtoB,
-  BtoA,
+enum CrossfaderSide {
+  A,
+  B,
+  /// Bypasses the crossfader (full gain at any position)
+  Thru,
 }
 
-/// Level meter state
-struct LevelMeterState {
-  deck_a_peak: f32,
-  deck_b_peak: f32,
-  deck_a_peak_hold: f32,
-  deck_b_peak_hold: f32,
-  deck_a_peak_hold_time: Instant,
-  deck_b_peak_hold_time: Instant,
+impl CrossfaderSide {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "a" => Some(Self::A),
+      "b" => Some(Self::B),
+      "thru" => Some(Self::Thru),
+      _ => None,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::A => "a",
+      Self::B => "b",
+      Self::Thru => "thru",
+    }
+  }
+
+  /// Pick this side's gain from the crossfader curve
+  fn gain(self, gain_a: f32, gain_b: f32) -> f32 {
+    match self {
+      Self::A => gain_a,
+      Self::B => gain_b,
+      Self::Thru => 1.0,
+    }
+  }
 }
 
-impl Default for LevelMeterState {
-  fn default() -> Self {
-    Self {
-      deck_a_peak: 0.0,
-      deck_b_peak: 0.0,
-      deck_a_peak_hold: 0.0,
-      deck_b_peak_hold: 0.0,
-      deck_a_peak_hold_time: Instant::now(),
-      deck_b_peak_hold_time: Instant::now(),
+/// Pitch fader range
+#[derive(Clone, Copy, PartialEq)]
+enum PitchRange {
+  Six,
+  Ten,
+  Sixteen,
+  /// Half to double speed
+  Wide,
+}
+
+impl PitchRange {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "6" => Some(Self::Six),
+      "10" => Some(Self::Ten),
+      "16" => Some(Self::Sixteen),
+      "wide" => Some(Self::Wide),
+      _ => None,
+    }
+  }
+
+  /// Playback rate limits (min, max)
+  fn limits(self) -> (f32, f32) {
+    match self {
+      Self::Six => (0.94, 1.06),
+      Self::Ten => (0.90, 1.10),
+      Self::Sixteen => (0.84, 1.16),
+      Self::Wide => (0.5, 2.0),
     }
   }
 }
 
-/// Audio channel configuration
-struct ChannelConfig {
-  /// Output channel count
+/// Channel fader taper (fader position 0-1 to linear gain)
+#[derive(Clone, Copy, PartialEq)]
+enum FaderCurve {
+  /// gain = x: -6 dB at half travel, -20 dB at 10%
+  Linear,
+  /// gain = x²: -12 dB at half travel, -40 dB at 10%
+  Square,
+  /// Straight line in dB from -60 dB (bottom) to 0 dB: -30 dB at half travel
+  Logarithmic,
+}
+
+impl FaderCurve {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "linear" => Some(Self::Linear),
+      "square" => Some(Self::Square),
+      "logarithmic" => Some(Self::Logarithmic),
+      _ => None,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::Linear => "linear",
+      Self::Square => "square",
+      Self::Logarithmic => "logarithmic",
+    }
+  }
+
+  /// Linear gain for a fader position; the bottom of travel is always silent
+  fn gain(self, fader: f32) -> f32 {
+    if fader <= 0.0 {
+      return 0.0;
+    }
+    match self {
+      Self::Linear => fader,
+      Self::Square => fader * fader,
+      Self::Logarithmic => 10.0f32.powf((fader - 1.0) * FADER_LOG_RANGE_DB / 20.0),
+    }
+  }
+}
+
+/// What a jog wheel movement does
+#[derive(Clone, Copy, PartialEq)]
+enum JogMode {
+  /// Vinyl-style scratch: the platter position follows the jog (works while paused)
+  Scratch,
+  /// Temporary pitch bend for beatmatching (only while playing)
+  Bend,
+}
+
+impl JogMode {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "scratch" => Some(Self::Scratch),
+      "bend" => Some(Self::Bend),
+      _ => None,
+    }
+  }
+}
+
+/// Output the mic monitor plays on
+#[derive(Clone, Copy, PartialEq)]
+enum MicMonitorTarget {
+  /// Headphones (the cue channels)
+  Cue,
+  /// Main outputs, after the master chain
+  Main,
+}
+
+impl MicMonitorTarget {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "cue" => Some(Self::Cue),
+      "main" => Some(Self::Main),
+      _ => None,
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::Cue => "cue",
+      Self::Main => "main",
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CrossfadeDirection {
+  AtoB,
+  BtoA,
+}
+
+/// Wall-clock time source for the timing that isn't counted in frames
+/// (peak hold decay, state emit pacing); tests drive a `ManualClock` instead of sleeping
+/// Crossfades and scheduled actions count rendered frames, which a playing device pulls at
+/// the pace of this clock; tests advance a `ManualClock` and render the elapsed frames
+trait Clock: Send + Sync {
+  fn now(&self) -> Instant;
+}
+
+/// Production clock: the monotonic system clock
+struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+}
+
+/// Test clock that only moves when advanced
+#[cfg(test)]
+struct ManualClock {
+  start: Instant,
+  elapsed: Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+  fn new() -> Self {
+    Self {
+      start: Instant::now(),
+      elapsed: Mutex::new(Duration::ZERO),
+    }
+  }
+
+  fn advance(&self, by: Duration) {
+    *self.elapsed.lock() += by;
+  }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+  fn now(&self) -> Instant {
+    self.start + *self.elapsed.lock()
+  }
+}
+
+/// Level meter state
+struct LevelMeterState {
+  deck_a_peak: f32,
+  deck_b_peak: f32,
+  deck_a_peak_hold: f32,
+  deck_b_peak_hold: f32,
+  deck_a_peak_hold_time: Instant,
+  deck_b_peak_hold_time: Instant,
+  /// Rolling mean square per deck (post trim/EQ, pre-fader), held while a deck is silent
+  deck_a_mean_square: f32,
+  deck_b_mean_square: f32,
+  /// Cue (headphone) bus peaks after cue gain and limiter
+  cue_left_peak: f32,
+  cue_right_peak: f32,
+  /// Clip indicators: latched when a chunk reaches 0 dBFS, until reset
+  deck_a_clipped: bool,
+  deck_b_clipped: bool,
+  master_clipped: bool,
+  /// How long a peak hold stays put before decaying
+  hold_duration: Duration,
+  /// Peak hold fall rate after the hold time (dB per second)
+  decay_db_per_sec: f32,
+}
+
+impl LevelMeterState {
+  fn new(now: Instant) -> Self {
+    Self {
+      deck_a_peak: 0.0,
+      deck_b_peak: 0.0,
+      deck_a_peak_hold: 0.0,
+      deck_b_peak_hold: 0.0,
+      deck_a_peak_hold_time: now,
+      deck_b_peak_hold_time: now,
+      deck_a_mean_square: 0.0,
+      deck_b_mean_square: 0.0,
+      cue_left_peak: 0.0,
+      cue_right_peak: 0.0,
+      deck_a_clipped: false,
+      deck_b_clipped: false,
+      master_clipped: false,
+      hold_duration: Duration::from_millis(1500),
+      decay_db_per_sec: 6.0,
+    }
+  }
+}
+
+/// Audio channel configuration
+struct ChannelConfig {
+  /// Output channel count
   output_channels: u16,
   /// Main output channels [left, right]
   main_channels: [Option<u16>; 2],
@@ -288,6 +1467,25 @@ impl Default for ChannelConfig {
   }
 }
 
+/// Headphone (cue) bus level control, independent of the master
+struct CueBusState {
+  /// Cue output gain (0.0 to 2.0)
+  gain: f32,
+  /// Limit the summed cue signal before routing to the cue channels
+  limiter_enabled: bool,
+  limiter: PeakLimiter,
+}
+
+impl CueBusState {
+  fn new(sample_rate: u32) -> Self {
+    Self {
+      gain: 1.0,
+      limiter_enabled: false,
+      limiter: PeakLimiter::new(-1.0, sample_rate),
+    }
+  }
+}
+
 /// Microphone input state
 struct MicrophoneState {
   /// Whether microphone is enabled
@@ -298,8 +1496,28 @@ struct MicrophoneState {
   talkover_ducking: f32,
   /// Input buffer from microphone (ring buffer)
   input_buffer: VecDeque<f32>,
+  /// Most mic audio kept in the input buffer (ms); older input is dropped
+  latency_ms: f32,
   /// Current microphone peak level
   peak: f32,
+  /// Per-channel peak levels (equal for a mono mic)
+  peak_left: f32,
+  peak_right: f32,
+  /// Input device delivers stereo (otherwise the first channel is duplicated)
+  stereo: bool,
+  /// Mic pan / balance (-1.0 = left, 0.0 = center, 1.0 = right)
+  pan: f32,
+  /// Smoothed mic gain actually applied to the mix
+  gain_smoother: ParamSmoother,
+  /// Smoothed music attenuation (talkover ducking) actually applied to the mix
+  ducking_smoother: ParamSmoother,
+  /// Time constant for ducking the music when talkover starts (seconds)
+  talkover_attack: f32,
+  /// Time constant for the music returning to unity when talkover ends (seconds)
+  talkover_release: f32,
+  /// Mic monitoring: the mic straight to an output, outside talkover
+  monitor: bool,
+  monitor_target: MicMonitorTarget,
 }
 
 impl Default for MicrophoneState {
@@ -309,7 +1527,47 @@ impl Default for MicrophoneState {
       gain: 1.0,
       talkover_ducking: 0.5, // Reduce music to 50% when talkover active
       input_buffer: VecDeque::new(),
+      latency_ms: 100.0,
+      peak: 0.0,
+      peak_left: 0.0,
+      peak_right: 0.0,
+      stereo: false,
+      pan: 0.0,
+      gain_smoother: ParamSmoother::new(0.0),
+      ducking_smoother: ParamSmoother::new(1.0),
+      talkover_attack: 0.05,
+      talkover_release: 0.5,
+      monitor: false,
+      monitor_target: MicMonitorTarget::Cue,
+    }
+  }
+}
+
+/// External (line-in) source, fed from JS with push_external_audio()
+/// Runs through its own EQ and fader into the master like a deck
+struct ExternalInputState {
+  /// Pushed interleaved stereo waiting to be played
+  buffer: VecDeque<f32>,
+  /// Fader level (0.0 to 1.0)
+  gain: f32,
+  /// Smoothed fader gain actually applied to the mix
+  gain_smoother: ParamSmoother,
+  eq_processor: EqProcessor,
+  /// Peak of the last chunk (post fader)
+  peak: f32,
+  /// Chunk taken from `buffer`, sized for the largest chunk and reused
+  scratch: Vec<f32>,
+}
+
+impl Default for ExternalInputState {
+  fn default() -> Self {
+    Self {
+      buffer: VecDeque::new(),
+      gain: 1.0,
+      gain_smoother: ParamSmoother::new(1.0),
+      eq_processor: EqProcessor::new(MAX_FRAMES_PER_CHUNK),
       peak: 0.0,
+      scratch: vec![0.0; MAX_FRAMES_PER_CHUNK * DEFAULT_CHANNELS as usize],
     }
   }
 }
@@ -321,33 +1579,457 @@ struct EngineState {
   crossfade: CrossfadeState,
   levels: LevelMeterState,
   channel_config: ChannelConfig,
+  cue_bus: CueBusState,
   microphone: MicrophoneState,
+  external_input: ExternalInputState,
+  /// One-shot sample players mixed into the master
+  sampler: SamplerBank,
+  /// Master clock beat phase, advanced by master tempo (sync lock reference)
+  clock_phase: f64,
+  /// Frames processed since the engine started (excluding pauses)
+  frame_clock: u64,
+  /// Actions waiting for their frame, applied sample-accurately by process_audio_chunk
+  scheduled: Vec<ScheduledAction>,
+  /// Filter + crossfade transition in progress
+  filter_fade: Option<FilterFade>,
+  /// Deck whose phase drives the master clock (None = internal clock)
+  sync_master: Option<u32>,
   master_tempo: f32,
+  /// Global transport pause: output silence and freeze all deck positions
+  paused: bool,
+  /// Panic: output silence until release_panic()
+  panicked: bool,
+  /// Time constant for gain/crossfader smoothing (seconds)
+  smoothing_time: f32,
+  /// Chunk size and queue depth used by the processing thread
+  latency_profile: LatencyProfile,
+  /// Soft-clip (tanh saturation) on the master before the final hard clamp
+  soft_clip_enabled: bool,
+  /// Soft-clip drive (1.0 = gentle, higher = more saturation)
+  soft_clip_drive: f32,
+  /// Sum the master to mono before channel mapping
+  mono_output: bool,
+  /// Master stereo width (0 = mono, 1 = unchanged, up to 2 = wider)
+  stereo_width: f32,
+  /// Master output trim in dB (cue bus unaffected)
+  master_gain_db: f32,
+  /// Ramps master trim changes to avoid zipper noise
+  master_gain_smoother: ParamSmoother,
+  /// Master tone control (room correction), after the master trim
+  master_eq: MasterEq,
+  /// Peak limiter at the end of the master chain
+  master_limiter_enabled: bool,
+  /// Master limiter (input gain drives it, the ceiling leaves the headroom)
+  master_limiter: PeakLimiter,
+  /// Deepest master limiter gain reduction in the last chunk (dB, positive)
+  master_gain_reduction_db: f32,
+  /// Bus fed to the recorder
+  recording_target: RecordingTarget,
+  /// Smoothed master stereo correlation (+1 = mono, 0 = uncorrelated, -1 = out of phase)
+  master_correlation: f32,
   running: bool,
   /// Set to true during device reconfiguration to pause audio processing
   configuring: bool,
   /// Whether microphone input is available
   mic_available: bool,
-  output_queue: VecDeque<f32>,
+  /// Output device in use (None until configure_device succeeds)
+  device_name: Option<String>,
+  /// Why the last configure_device failed (cleared once a device is configured)
+  device_error: Option<String>,
   /// Pending state update reason (None = periodic, Some = specific event)
   update_reason: Option<String>,
+  /// PCM of tracks replaced during processing, freed by the process thread outside the lock
+  retired_pcm: Vec<Arc<Vec<f32>>>,
+  /// Counters written by the output callback (which never takes the state lock)
+  output_stats: Arc<OutputStats>,
+  /// Mic monitor settings read by the output callback
+  mic_monitor: Arc<MicMonitorShared>,
+  /// Report deck positions as currently audible (minus the output latency)
+  latency_compensation: bool,
+  /// Failure that stopped the current recording
+  recording_error: Option<String>,
+  /// File currently being recorded (changes when a recording splits)
+  recording_path: Option<String>,
+  /// Events raised during processing, delivered by the process thread outside the lock
+  events: Vec<EngineEvent>,
+  /// Time source for peak hold and state emits
+  clock: Arc<dyn Clock>,
+  /// Per-sample deck gains for the mix, sized for the largest chunk and reused
+  mix_gains_a: Vec<f32>,
+  mix_gains_b: Vec<f32>,
 }
 
 impl EngineState {
   fn new(sample_rate: u32) -> Self {
+    Self::with_clock(sample_rate, Arc::new(SystemClock))
+  }
+
+  fn with_clock(sample_rate: u32, clock: Arc<dyn Clock>) -> Self {
     Self {
       deck_a: DeckState::new(sample_rate),
-      deck_b: DeckState::new(sample_rate),
+      deck_b: DeckState {
+        crossfader_side: CrossfaderSide::B,
+        ..DeckState::new(sample_rate)
+      },
       crossfade: CrossfadeState::default(),
-      levels: LevelMeterState::default(),
+      levels: LevelMeterState::new(clock.now()),
       channel_config: ChannelConfig::default(),
+      cue_bus: CueBusState::new(sample_rate),
       microphone: MicrophoneState::default(),
+      external_input: ExternalInputState::default(),
+      sampler: SamplerBank::new(),
+      clock_phase: 0.0,
+      frame_clock: 0,
+      scheduled: Vec::new(),
+      filter_fade: None,
+      sync_master: None,
       master_tempo: 130.0,
+      paused: false,
+      panicked: false,
+      smoothing_time: DEFAULT_SMOOTHING_TIME,
+      latency_profile: LatencyProfile::SAFE,
+      soft_clip_enabled: false,
+      soft_clip_drive: 1.0,
+      mono_output: false,
+      stereo_width: 1.0,
+      master_gain_db: 0.0,
+      master_gain_smoother: ParamSmoother::new(1.0),
+      master_eq: MasterEq::default(),
+      master_limiter_enabled: false,
+      master_limiter: PeakLimiter::new(-1.0, sample_rate),
+      master_gain_reduction_db: 0.0,
+      master_correlation: 1.0,
+      recording_target: RecordingTarget::Master,
       running: true,
       configuring: false,
       mic_available: false,
-      output_queue: VecDeque::new(),
+      device_name: None,
+      device_error: None,
       update_reason: None,
+      retired_pcm: Vec::new(),
+      output_stats: Arc::new(OutputStats::default()),
+      mic_monitor: Arc::new(MicMonitorShared::default()),
+      latency_compensation: false,
+      recording_error: None,
+      recording_path: None,
+      events: Vec::new(),
+      clock,
+      mix_gains_a: vec![0.0; MAX_FRAMES_PER_CHUNK * DEFAULT_CHANNELS as usize],
+      mix_gains_b: vec![0.0; MAX_FRAMES_PER_CHUNK * DEFAULT_CHANNELS as usize],
+    }
+  }
+
+  /// Deck state by deck number (1 = A, anything else = B)
+  fn deck(&self, deck: u32) -> &DeckState {
+    if deck == 1 {
+      &self.deck_a
+    } else {
+      &self.deck_b
+    }
+  }
+
+  /// Mutable deck state by deck number (1 = A, anything else = B)
+  fn deck_mut(&mut self, deck: u32) -> &mut DeckState {
+    if deck == 1 {
+      &mut self.deck_a
+    } else {
+      &mut self.deck_b
+    }
+  }
+
+  /// Finish a gapless advance: retime the deck for the new BPM and retire the old PCM
+  fn track_advanced(&mut self, deck: u32, previous: Arc<Vec<f32>>) {
+    let master_tempo = self.master_tempo;
+    self.deck_mut(deck).update_rate(master_tempo);
+    self.retired_pcm.push(previous);
+    self.update_reason = Some("advanced".to_string());
+  }
+
+  /// Start an auto crossfade toward `target_position` (default: away from the playing deck)
+  /// The ramp starts after `delay_frames` frames of the next chunk
+  fn begin_crossfade(
+    &mut self,
+    target_position: Option<f32>,
+    total_frames: usize,
+    delay_frames: usize,
+  ) {
+    let current = self.crossfade.position;
+    let target = target_position.unwrap_or(if self.deck_a.playing { 1.0 } else { 0.0 });
+
+    let direction = if target > current {
+      CrossfadeDirection::AtoB
+    } else {
+      CrossfadeDirection::BtoA
+    };
+
+    self.crossfade.active = true;
+    self.crossfade.direction = Some(direction);
+    self.crossfade.delay_frames = delay_frames;
+    self.crossfade.remaining_frames = total_frames;
+    self.crossfade.total_frames = total_frames;
+    self.crossfade.start_position = current;
+    self.crossfade.target_position = target;
+    self.crossfade.stop_on_complete = true;
+  }
+
+  /// Sweep the filter fade's high-pass for this chunk; stops the outgoing deck when done
+  fn update_filter_fade(&mut self, frames: usize) {
+    let Some(ref mut fade) = self.filter_fade else {
+      return;
+    };
+    fade.remaining_frames = fade.remaining_frames.saturating_sub(frames);
+    let (deck, restore_hz) = (fade.deck, fade.restore_hz);
+
+    if fade.remaining_frames == 0 {
+      self.filter_fade = None;
+      let deck_state = self.deck_mut(deck);
+      deck_state.playing = false;
+      deck_state.trim_filters.set_highpass_hz(restore_hz);
+      self.update_reason = Some("stop".to_string());
+    } else {
+      let progress = 1.0 - fade.remaining_frames as f32 / fade.total_frames as f32;
+      let hz = FILTER_FADE_START_HZ * (FILTER_FADE_END_HZ / FILTER_FADE_START_HZ).powf(progress);
+      self.deck_mut(deck).trim_filters.set_highpass_hz(hz);
+    }
+  }
+
+  /// Abandon a filter fade, restoring the outgoing deck's high-pass
+  fn cancel_filter_fade(&mut self) {
+    if let Some(fade) = self.filter_fade.take() {
+      let deck_state = self.deck_mut(fade.deck);
+      deck_state.trim_filters.set_highpass_hz(fade.restore_hz);
+    }
+  }
+
+  /// Loop `beats` beats from a deck's playhead (see AudioEngine::set_auto_loop)
+  /// Emits update reason "auto_loop" when the loop changes
+  fn set_auto_loop(&mut self, deck: u32, beats: f64, sample_rate: u32) -> Result<()> {
+    let deck_state = self.deck_mut(deck);
+
+    let Some(total_frames) = deck_state
+      .pcm_data
+      .as_ref()
+      .map(|pcm| pcm.len() / DEFAULT_CHANNELS as usize)
+    else {
+      return Ok(());
+    };
+    let bpm = deck_state
+      .bpm
+      .filter(|&b| b > 0.0)
+      .ok_or_else(|| Error::from_reason("Auto loop requires a track BPM"))?;
+
+    let length = (beats * 60.0 / bpm as f64 * sample_rate as f64) as usize;
+    let position = deck_state.position;
+    let loop_start = if deck_state.loop_enabled
+      && (deck_state.loop_start..deck_state.loop_end).contains(&position)
+    {
+      deck_state.loop_start
+    } else if deck_state.quantize {
+      deck_state
+        .nearest_beat_frame(sample_rate)
+        .unwrap_or(position)
+    } else {
+      position
+    };
+    let loop_end = (loop_start + length).min(total_frames);
+
+    if loop_end > loop_start {
+      deck_state.loop_start = loop_start;
+      deck_state.loop_end = loop_end;
+      deck_state.loop_enabled = true;
+
+      if position >= loop_end || position < loop_start {
+        deck_state.jump(loop_start);
+      }
+      self.update_reason = Some("auto_loop".to_string());
+    }
+
+    Ok(())
+  }
+
+  /// Put `from_deck`'s track on the other deck in phase (see AudioEngine::instant_double)
+  /// Returns the target's previous PCM, to be freed outside the lock
+  fn instant_double(&mut self, from_deck: u32) -> Result<Option<Arc<Vec<f32>>>> {
+    let (source, target) = if from_deck == 1 {
+      (&self.deck_a, &mut self.deck_b)
+    } else {
+      (&self.deck_b, &mut self.deck_a)
+    };
+    let Some(pcm) = source.pcm_data.clone() else {
+      return Err(Error::from_reason(format!(
+        "Deck {} has no track loaded",
+        from_deck
+      )));
+    };
+
+    let previous = target.pcm_data.replace(pcm);
+    // Start from what is heard on the source, not its stretcher's input cursor, which runs
+    // ahead by the reservoir and SoundTouch latency
+    target.reset_for_track(source.audible_position.max(0.0).round() as usize);
+    target.output_frames = source.output_frames;
+    target.bpm = source.bpm;
+    target.rate = source.rate;
+    target.sync_correction = source.sync_correction;
+    target.pitch_range = source.pitch_range;
+    target.pitch_adjust = source.pitch_adjust;
+    target.first_beat = source.first_beat;
+    target.beat_grid = source.beat_grid.clone();
+    target.track_id = source.track_id.clone();
+    target.headroom_db = source.headroom_db;
+    target.playing = false;
+    target.start();
+    self.update_reason = Some("load".to_string());
+    Ok(previous)
+  }
+
+  /// Hand the mic monitor's gain, pan and output channels to the output callback
+  /// Silent while paused or panicked, like the rest of the output
+  fn publish_mic_monitor(&self) {
+    let mic = &self.microphone;
+    let on = mic.monitor && !self.paused && !self.panicked;
+    let shared = &self.mic_monitor;
+    let gain = if on { mic.gain } else { 0.0 };
+    shared.gain.store(gain.to_bits(), Ordering::Relaxed);
+    let (pan_left, pan_right) = pan_gains(mic.pan);
+    for (slot, pan) in shared.pan.iter().zip([pan_left, pan_right]) {
+      slot.store(pan.to_bits(), Ordering::Relaxed);
+    }
+    let channels = match mic.monitor_target {
+      MicMonitorTarget::Cue => self.channel_config.cue_channels,
+      MicMonitorTarget::Main => self.channel_config.main_channels,
+    };
+    for (slot, channel) in shared.channels.iter().zip(channels) {
+      slot.store(channel.map_or(u32::MAX, u32::from), Ordering::Relaxed);
+    }
+  }
+
+  /// Update the peak hold meters at the engine clock's current time
+  fn update_peak_hold(&mut self) {
+    let now = self.clock.now();
+    update_peak_hold(&mut self.levels, now);
+  }
+
+  /// Queue an action for the master clock beat `at_beat`
+  /// The beat is converted to an engine frame at the current master tempo; past beats fire
+  /// on the next chunk
+  fn schedule(
+    &mut self,
+    at_beat: f64,
+    deck: Option<u32>,
+    kind: ScheduledKind,
+    sample_rate: u32,
+  ) -> Result<()> {
+    if !at_beat.is_finite() {
+      return Err(Error::from_reason("Scheduled beat must be finite"));
+    }
+
+    let seconds = (at_beat - self.clock_phase) * 60.0 / self.master_tempo as f64;
+    let offset = (seconds * sample_rate as f64).max(0.0).round() as u64;
+    let frame = self.frame_clock.saturating_add(offset);
+    self.scheduled.push(ScheduledAction { frame, deck, kind });
+    self.scheduled.sort_by_key(|action| action.frame);
+    Ok(())
+  }
+
+  /// Remove the actions landing in the next `frames` frames, with their offset into the chunk
+  fn take_due_actions(&mut self, frames: usize) -> Vec<(usize, ScheduledAction)> {
+    let chunk_end = self.frame_clock + frames as u64;
+    let due = self
+      .scheduled
+      .iter()
+      .take_while(|action| action.frame < chunk_end)
+      .count();
+    let frame_clock = self.frame_clock;
+    self
+      .scheduled
+      .drain(..due)
+      .map(|action| (action.frame.saturating_sub(frame_clock) as usize, action))
+      .collect()
+  }
+
+  /// Apply a due deck action (play or seek) at the current render point
+  fn apply_scheduled(&mut self, deck: u32, kind: ScheduledKind) {
+    let deck_state = self.deck_mut(deck);
+    let Some(ref pcm) = deck_state.pcm_data else {
+      return;
+    };
+    match kind {
+      ScheduledKind::Play => deck_state.start(),
+      ScheduledKind::Seek(position) => {
+        let total_frames = pcm.len() / DEFAULT_CHANNELS as usize;
+        deck_state.jump((total_frames as f64 * position) as usize);
+      }
+      ScheduledKind::Crossfade { .. } => return,
+    }
+    self.update_reason = Some("scheduled".to_string());
+  }
+
+  /// Render one deck chunk, splitting it at the frames where scheduled actions land
+  fn render_deck(
+    &mut self,
+    deck: u32,
+    frames: usize,
+    output: &mut [f32],
+    due: &[(usize, ScheduledAction)],
+    coeff: f32,
+  ) {
+    let mut start = 0;
+    for &(offset, action) in due.iter().filter(|(_, action)| action.deck == Some(deck)) {
+      self.render_deck_segment(deck, output, start, offset, coeff);
+      self.apply_scheduled(deck, action.kind);
+      start = offset;
+    }
+    self.render_deck_segment(deck, output, start, frames, coeff);
+  }
+
+  /// Render frames `start..end` of a deck chunk into the matching part of `output`
+  fn render_deck_segment(
+    &mut self,
+    deck: u32,
+    output: &mut [f32],
+    start: usize,
+    end: usize,
+    coeff: f32,
+  ) {
+    if end <= start {
+      return;
+    }
+    let channels = DEFAULT_CHANNELS as usize;
+    let segment = &mut output[start * channels..end * channels];
+    let deck_state = self.deck_mut(deck);
+    let previous = deck_state.render(end - start, segment, coeff);
+    deck_state.apply_transport_ramp(segment);
+    if let Some(previous) = previous {
+      self.track_advanced(deck, previous);
+    }
+  }
+
+  /// Queue an event for the event callback
+  fn emit_event(&mut self, kind: &str, deck: u32) {
+    self.events.push(EngineEvent {
+      kind: kind.to_string(),
+      deck,
+      path: None,
+    });
+  }
+
+  /// Queue a "recording_split" event carrying the file the recording continues in
+  fn emit_recording_split(&mut self, path: String) {
+    self.recording_path = Some(path.clone());
+    self.events.push(EngineEvent {
+      kind: "recording_split".to_string(),
+      deck: 0,
+      path: Some(path),
+    });
+  }
+
+  /// Move the events raised by each deck's last render into the engine queue
+  fn collect_deck_events(&mut self) {
+    for deck in [1, 2] {
+      let kinds = std::mem::take(&mut self.deck_mut(deck).events);
+      for kind in kinds {
+        self.emit_event(kind, deck);
+      }
     }
   }
 }
@@ -357,10 +2039,27 @@ impl EngineState {
 #[derive(Clone, Copy, Default)]
 pub struct EqCutStateJs {
   pub low: bool,
+  /// 4-band mode only
+  pub low_mid: bool,
+  /// 3-band mode only
   pub mid: bool,
+  /// 4-band mode only
+  pub high_mid: bool,
   pub high: bool,
 }
 
+impl From<EqCutState> for EqCutStateJs {
+  fn from(cut: EqCutState) -> Self {
+    Self {
+      low: cut.low,
+      low_mid: cut.low_mid,
+      mid: cut.mid,
+      high_mid: cut.high_mid,
+      high: cut.high,
+    }
+  }
+}
+
 /// Loop state for a deck
 #[napi(object)]
 #[derive(Clone, Copy, Default)]
@@ -371,42 +2070,224 @@ pub struct LoopStateJs {
   pub start: f64,
   /// Loop end position (0.0-1.0)
   pub end: f64,
+  /// Loop roll size in beats (None when the loop isn't a roll)
+  pub roll_beats: Option<f64>,
+  /// Where playback continues when the roll ends (0.0-1.0)
+  pub slip_position: Option<f64>,
+}
+
+/// Beat grid of a deck, for saving and restoring without re-analysis
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct BeatGridJs {
+  /// Track BPM
+  pub bpm: f64,
+  /// First beat position in seconds
+  pub first_beat: f64,
+  /// Every beat position in seconds
+  pub beats: Vec<f64>,
 }
 
 /// State update sent to JavaScript
 #[napi(object)]
 pub struct AudioEngineStateUpdate {
+  /// Deck A playhead in track (source) seconds; same as deck_a_source_position
   pub deck_a_position: Option<f64>,
+  /// Deck B playhead in track (source) seconds; same as deck_b_source_position
   pub deck_b_position: Option<f64>,
+  /// Deck A playhead in track (source) seconds, independent of the playback rate
+  /// Use for the waveform playhead, cue points and beat grids
+  pub deck_a_source_position: Option<f64>,
+  /// Deck B playhead in track (source) seconds, independent of the playback rate
+  pub deck_b_source_position: Option<f64>,
+  /// Deck A playhead in output seconds: time actually played since the track start, counting
+  /// tempo changes as they happened. A seek restarts it at the new source position divided by
+  /// the playback rate; it keeps running through loops
+  pub deck_a_output_position: Option<f64>,
+  /// Deck B playhead in output seconds (time played since the track start)
+  pub deck_b_output_position: Option<f64>,
+  /// Deck A track length in seconds
+  pub deck_a_duration: Option<f64>,
+  /// Deck B track length in seconds
+  pub deck_b_duration: Option<f64>,
+  /// Wall-clock seconds until deck A reaches the track end at its current playback rate
+  pub deck_a_remaining: Option<f64>,
+  /// Wall-clock seconds until deck B reaches the track end at its current playback rate
+  pub deck_b_remaining: Option<f64>,
   pub deck_a_playing: bool,
   pub deck_b_playing: bool,
   pub crossfader_position: f64,
+  /// Crossfader center dead zone width (0 = off)
+  pub crossfader_deadzone: f64,
   pub is_crossfading: bool,
+  /// Crossfader assignment for deck A ("a", "b" or "thru")
+  pub deck_a_crossfader_side: String,
+  /// Crossfader assignment for deck B ("a", "b" or "thru")
+  pub deck_b_crossfader_side: String,
   pub deck_a_peak: f64,
   pub deck_b_peak: f64,
   pub deck_a_peak_hold: f64,
   pub deck_b_peak_hold: f64,
+  /// Deck A rolling RMS over about 3 s (post trim/EQ, pre-fader)
+  pub deck_a_rms: f64,
+  /// Deck B rolling RMS over about 3 s (post trim/EQ, pre-fader)
+  pub deck_b_rms: f64,
+  /// Deck A reached 0 dBFS (post deck gain) since the last resetClipIndicators()
+  pub deck_a_clipped: bool,
+  /// Deck B reached 0 dBFS (post deck gain) since the last resetClipIndicators()
+  pub deck_b_clipped: bool,
+  /// Master reached 0 dBFS before the output clamp since the last resetClipIndicators()
+  pub master_clipped: bool,
+  /// Cue (headphone) bus peak, left channel (0 when nothing is cued)
+  pub cue_left_peak: f64,
+  /// Cue (headphone) bus peak, right channel
+  pub cue_right_peak: f64,
   pub master_tempo: f64,
   pub deck_a_track_id: Option<String>,
   pub deck_b_track_id: Option<String>,
+  /// Failure that stopped the current recording (cleared by startRecording)
+  pub recording_error: Option<String>,
+  /// File currently being recorded; follows the "recording_split" events
+  pub recording_path: Option<String>,
+  /// Output callbacks that ran out of audio since start or resetUnderrunCount()
+  pub underrun_count: f64,
+  /// Estimated output latency: queued audio plus the device's reported latency (ms)
+  pub output_latency_ms: f64,
+  /// Rate the engine renders at (Hz)
+  pub engine_sample_rate: u32,
+  /// Rate the output device runs at (Hz); resampled from the engine rate when different
+  /// Absent until a device is configured
+  pub device_sample_rate: Option<u32>,
+  /// Output device in use (absent while the engine runs without a device)
+  pub device_name: Option<String>,
+  /// Why the last configureDevice failed, e.g. no audio device (cleared once one is configured)
+  pub device_error: Option<String>,
+  /// Deck A muted on the master
+  pub deck_a_muted: bool,
+  /// Deck B muted on the master
+  pub deck_b_muted: bool,
+  /// Deck A soloed
+  pub deck_a_soloed: bool,
+  /// Deck B soloed
+  pub deck_b_soloed: bool,
+  /// Track ID queued to follow deck A's current track
+  pub deck_a_next_track_id: Option<String>,
+  /// Track ID queued to follow deck B's current track
+  pub deck_b_next_track_id: Option<String>,
   pub deck_a_gain: f64,
   pub deck_b_gain: f64,
+  /// Deck A fader curve ("linear", "square" or "logarithmic")
+  pub deck_a_fader_curve: String,
+  /// Deck B fader curve ("linear", "square" or "logarithmic")
+  pub deck_b_fader_curve: String,
+  /// Deck A pan (-1.0 left to 1.0 right)
+  pub deck_a_pan: f64,
+  /// Deck B pan (-1.0 left to 1.0 right)
+  pub deck_b_pan: f64,
+  /// Deck A trim in dB
+  pub deck_a_trim_db: f64,
+  /// Deck B trim in dB
+  pub deck_b_trim_db: f64,
+  /// Deck A high-pass trim filter cutoff (Hz, 0 = off)
+  pub deck_a_hpf_hz: f64,
+  /// Deck B high-pass trim filter cutoff (Hz, 0 = off)
+  pub deck_b_hpf_hz: f64,
+  /// Deck A low-pass trim filter cutoff (Hz, 0 = off)
+  pub deck_a_lpf_hz: f64,
+  /// Deck B low-pass trim filter cutoff (Hz, 0 = off)
+  pub deck_b_lpf_hz: f64,
+  /// Deck A pitch fader position (percent)
+  pub deck_a_pitch_adjust: f64,
+  /// Deck B pitch fader position (percent)
+  pub deck_b_pitch_adjust: f64,
   pub deck_a_cue_enabled: bool,
   pub deck_b_cue_enabled: bool,
   /// EQ cut state for deck A
   pub deck_a_eq_cut: EqCutStateJs,
   /// EQ cut state for deck B
   pub deck_b_eq_cut: EqCutStateJs,
+  /// EQ mode for deck A ("3band" or "4band")
+  pub deck_a_eq_mode: String,
+  /// EQ mode for deck B ("3band" or "4band")
+  pub deck_b_eq_mode: String,
+  /// EQ band gains for deck A (dB, lowest band first)
+  pub deck_a_eq_gains_db: Vec<f64>,
+  /// EQ band gains for deck B (dB, lowest band first)
+  pub deck_b_eq_gains_db: Vec<f64>,
+  /// Deck A level per EQ band [low, mid, high] (RMS after the EQ, 0 when stopped)
+  /// An equal split of the deck level while the EQ is flat (bands aren't measured then)
+  pub deck_a_band_levels: Vec<f64>,
+  /// Deck B level per EQ band [low, mid, high]
+  pub deck_b_band_levels: Vec<f64>,
   /// Loop state for deck A
   pub deck_a_loop: LoopStateJs,
   /// Loop state for deck B
   pub deck_b_loop: LoopStateJs,
+  /// Temporary cue point for deck A (seconds)
+  pub deck_a_cue_point: Option<f64>,
+  /// Temporary cue point for deck B (seconds)
+  pub deck_b_cue_point: Option<f64>,
+  /// Source headroom for deck A in dB (negative = track is hot)
+  pub deck_a_headroom_db: Option<f64>,
+  /// Source headroom for deck B in dB (negative = track is hot)
+  pub deck_b_headroom_db: Option<f64>,
   /// Microphone available (input stream created successfully)
   pub mic_available: bool,
   /// Microphone enabled
   pub mic_enabled: bool,
   /// Microphone peak level
   pub mic_peak: f64,
+  /// Microphone peak level per channel (equal for a mono mic)
+  pub mic_peak_left: f64,
+  pub mic_peak_right: f64,
+  /// Microphone input is stereo
+  pub mic_stereo: bool,
+  /// Microphone pan (-1.0 = left, 0.0 = center, 1.0 = right)
+  pub mic_pan: f64,
+  /// Microphone buffer depth (ms), the talkover latency it adds
+  pub mic_latency_ms: f64,
+  /// Mic monitoring on (set_mic_monitor)
+  pub mic_monitor: bool,
+  /// Output the mic monitor plays on ("cue" or "main")
+  pub mic_monitor_target: String,
+  /// External input peak level (post fader)
+  pub external_peak: f64,
+  /// External input fader (0.0 to 1.0)
+  pub external_gain: f64,
+  /// Global transport paused (pause_all)
+  pub paused: bool,
+  /// Output silenced by panic() until releasePanic()
+  pub panicked: bool,
+  /// Whether each sampler slot is currently playing
+  pub sampler_playing: Vec<bool>,
+  /// Sync lock enabled for deck A
+  pub deck_a_synced: bool,
+  /// Sync lock enabled for deck B
+  pub deck_b_synced: bool,
+  /// Deck driving the master clock (None = internal clock)
+  pub sync_master: Option<u32>,
+  /// Master summed to mono
+  pub mono_output: bool,
+  /// Master stereo width (0 = mono, 1 = unchanged)
+  pub stereo_width: f64,
+  /// Master output trim (dB, 0 = unity)
+  pub master_gain: f64,
+  /// Master tone control gains in dB [low, mid, high]
+  pub master_eq_gains_db: Vec<f64>,
+  /// Master tone control bypassed
+  pub master_eq_bypassed: bool,
+  /// Master limiter enabled
+  pub master_limiter_enabled: bool,
+  /// Master limiter gain reduction (dB, 0 = not limiting)
+  pub master_gain_reduction_db: f64,
+  /// Delay added by the master limiter lookahead (ms)
+  pub master_limiter_latency_ms: f64,
+  /// Master stereo correlation (+1 fully correlated, -1 out of phase)
+  pub master_correlation: f64,
+  /// Master clock position in beats (the timeline for scheduled actions)
+  pub master_beat: f64,
+  /// Scheduled actions still pending
+  pub scheduled_actions: u32,
   /// Reason for this state update: "periodic", "seek", "play", "stop", "load", etc.
   pub update_reason: String,
 }
@@ -420,15 +2301,43 @@ pub struct DeviceConfig {
   pub main_channels: Option<Vec<i32>>,
   /// Cue output channels [left, right], -1 for disabled
   pub cue_channels: Option<Vec<i32>>,
+  /// Resampler used when the device rate differs from the engine rate:
+  /// "cubic" (default) or "linear" (cheaper, dulls the top octave slightly)
+  pub resampler: Option<String>,
+  /// Output channels to open (default: the device's default channel count);
+  /// must be a count the device supports
+  pub output_channels: Option<u32>,
+}
+
+/// Engine event for setEventCallback()
+#[napi(object)]
+#[derive(Clone)]
+pub struct EngineEvent {
+  /// "track_ended", "loop_completed", "crossfade_finished", "cue_reached" or
+  /// "recording_split"
+  #[napi(js_name = "type")]
+  pub kind: String,
+  /// Deck the event belongs to (the target deck for "crossfade_finished", 0 for
+  /// "recording_split")
+  pub deck: u32,
+  /// File a split recording continues in ("recording_split" only)
+  pub path: Option<String>,
 }
 
+type EventCallback = ThreadsafeFunction<EngineEvent, (), EngineEvent, Status, false>;
+
 #[napi]
 pub struct AudioEngine {
   state: Arc<Mutex<EngineState>>,
   stream: Arc<Mutex<Option<cpal::Stream>>>,
   input_stream: Arc<Mutex<Option<cpal::Stream>>>,
-  _process_thread: Option<JoinHandle<()>>,
+  /// Producer end of the output ring buffer (None until a device is configured)
+  output_producer: Arc<Mutex<Option<Producer<f32>>>>,
+  /// Processing thread handle (taken and joined by close())
+  process_thread: Mutex<Option<JoinHandle<()>>>,
   recording_thread: Arc<Mutex<Option<RecordingThread>>>,
+  /// Optional callback for discrete engine events (set by setEventCallback())
+  event_callback: Arc<Mutex<Option<EventCallback>>>,
   sample_rate: u32,
 }
 
@@ -453,8 +2362,13 @@ impl AudioEngine {
 
     let recording_thread: Arc<Mutex<Option<RecordingThread>>> = Arc::new(Mutex::new(Some(RecordingThread::new())));
 
+    let output_producer: Arc<Mutex<Option<Producer<f32>>>> = Arc::new(Mutex::new(None));
+
     let state_for_process = Arc::clone(&state);
     let recording_thread_for_process = Arc::clone(&recording_thread);
+    let output_producer_for_process = Arc::clone(&output_producer);
+    let event_callback: Arc<Mutex<Option<EventCallback>>> = Arc::new(Mutex::new(None));
+    let event_callback_for_process = Arc::clone(&event_callback);
 
     // Create threadsafe function for state updates
     let tsfn = state_callback
@@ -471,64 +2385,116 @@ impl AudioEngine {
         Err(e) => eprintln!("[AudioEngine] Warning: Could not set thread priority: {e:?}"),
       }
 
-      let target_queue_samples = (sample_rate_for_process as usize / 10) * output_channels as usize;
-      let interval = Duration::from_micros(
-        ((FRAMES_PER_CHUNK as f64 / sample_rate_for_process as f64) * 1_000_000.0 * 0.8) as u64,
-      );
-      let mut last_state_emit = Instant::now();
+      let clock = Arc::clone(&state_for_process.lock().clock);
+      let mut last_state_emit = clock.now();
       let state_emit_interval = Duration::from_millis(33); // 30 FPS
 
       loop {
-        let should_exit = {
+        let (should_exit, profile, current_output_channels) = {
           let state = state_for_process.lock();
-          !state.running
+          (
+            !state.running,
+            state.latency_profile,
+            state.channel_config.output_channels,
+          )
         };
 
         if should_exit {
           break;
         }
 
-        // Check queue size and get current output_channels
-        let (queue_size, current_output_channels) = {
-          let state = state_for_process.lock();
-          (
-            state.output_queue.len(),
-            state.channel_config.output_channels,
-          )
-        };
+        // Check ring buffer fill level (no output device yet means nothing to feed)
+        let queue_size = output_producer_for_process
+          .lock()
+          .as_ref()
+          .map(|producer| producer.buffer().capacity() - producer.slots());
 
-        if queue_size < target_queue_samples * 2 {
+        if queue_size.is_some_and(|size| {
+          profile.needs_chunk(sample_rate_for_process, current_output_channels, size)
+        }) {
           // Process audio chunk
-          let chunk = {
+          let (chunk, channels, recording, state_update, retired_pcm, events) = {
             let mut state = state_for_process.lock();
-            let (chunk, _) =
-              process_audio_chunk(&mut state, sample_rate_for_process, current_output_channels);
-            chunk
+            let channels = state.channel_config.output_channels;
+            let (chunk, recording, state_update) =
+              process_audio_chunk(&mut state, sample_rate_for_process, channels);
+            let retired_pcm = std::mem::take(&mut state.retired_pcm);
+            let events = std::mem::take(&mut state.events);
+            (
+              chunk,
+              channels,
+              recording,
+              state_update,
+              retired_pcm,
+              events,
+            )
           };
 
-          // Add to queue
-          {
-            let mut state = state_for_process.lock();
-            state.output_queue.extend(chunk.clone());
+          // Free replaced tracks outside the lock
+          drop(retired_pcm);
+
+          // Fire each engine event once
+          if !events.is_empty() {
+            if let Some(ref callback) = *event_callback_for_process.lock() {
+              for event in events {
+                callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+              }
+            }
+          }
+
+          // Deliver event updates (e.g. "advanced") right away instead of dropping them
+          if state_update.update_reason != "periodic" {
+            tsfn.call(state_update, ThreadsafeFunctionCallMode::NonBlocking);
+          }
+
+          // Push to the output ring buffer (engine lock is not held here)
+          if let Some(ref mut producer) = *output_producer_for_process.lock() {
+            push_output(producer, &chunk, channels);
+          }
+
+          // Send the recorded bus (stereo master or cue) to the recording thread
+          let (recording_error, recording_split) = recording_thread_for_process
+            .lock()
+            .as_mut()
+            .map(|rt| {
+              rt.send_audio_data(&recording);
+              (rt.error(), rt.take_split())
+            })
+            .unwrap_or_default();
+
+          // Report the file a split recording continues in
+          if let Some(path) = recording_split {
+            state_for_process.lock().emit_recording_split(path);
           }
 
-          // Send to recording thread
-          if let Some(ref mut rt) = *recording_thread_for_process.lock() {
-            rt.send_audio_data(&chunk);
+          // Surface a failed write once as an event; it stays in the state afterwards
+          if let Some(error) = recording_error {
+            let mut state = state_for_process.lock();
+            if state.recording_error.as_ref() != Some(&error) {
+              state.recording_error = Some(error);
+              state.update_reason = Some("recording_error".to_string());
+            }
           }
         }
 
         // Emit state update at 30 FPS (always, regardless of queue size)
-        if last_state_emit.elapsed() >= state_emit_interval {
+        if clock.now().duration_since(last_state_emit) >= state_emit_interval {
           let state_update = {
-            let state = state_for_process.lock();
-            create_state_update(&state, sample_rate_for_process)
+            let mut state = state_for_process.lock();
+            // Never call back into JS once close() has started
+            if !state.running {
+              break;
+            }
+            let state_update = create_state_update(&state, sample_rate_for_process);
+            // The event (if any) has been delivered
+            state.update_reason = None;
+            state_update
           };
           tsfn.call(state_update, ThreadsafeFunctionCallMode::NonBlocking);
-          last_state_emit = Instant::now();
+          last_state_emit = clock.now();
         }
 
-        thread::sleep(interval);
+        thread::sleep(profile.interval(sample_rate_for_process));
       }
     });
 
@@ -536,123 +2502,73 @@ impl AudioEngine {
       state,
       stream: Arc::new(Mutex::new(None)),
       input_stream: Arc::new(Mutex::new(None)),
-      _process_thread: Some(process_thread),
+      output_producer,
+      process_thread: Mutex::new(Some(process_thread)),
       // Use the SAME recording_thread that the process thread uses
       recording_thread,
+      event_callback,
       sample_rate,
     })
   }
 
+  /// Set (or clear with null) the callback for discrete engine events
+  /// Each track end, loop wrap, finished auto crossfade and temp cue pass fires once
+  #[napi]
+  pub fn set_event_callback(
+    &self,
+    #[napi(ts_arg_type = "((event: EngineEvent) => void) | undefined | null")] callback: Option<
+      Function<EngineEvent, ()>,
+    >,
+  ) -> Result<()> {
+    let callback = match callback {
+      Some(callback) => Some(
+        callback
+          .build_threadsafe_function()
+          .callee_handled::<false>()
+          .build()?,
+      ),
+      None => None,
+    };
+    *self.event_callback.lock() = callback;
+    Ok(())
+  }
+
   /// Configure audio device and start output stream
   /// Can be called multiple times to switch devices without losing engine state
+  /// The engine runs without a device until this succeeds; a failure (e.g. no sound card yet)
+  /// is returned and reported as deviceError (update reason "device_error"), and calling
+  /// again later attaches a device once one is available
   #[napi]
   pub fn configure_device(&mut self, config: DeviceConfig) -> Result<()> {
-    // Get device once and reuse for both output and input
-    let device = get_device(config.device_id.as_deref())?;
-    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+    let result = self.attach_device(config);
 
-    // Get device's max output channels (use all available)
-    let output_channels = device
-      .default_output_config()
-      .map_err(|e| Error::from_reason(format!("Device '{}' error: {}", device_name, e)))?
-      .channels();
+    // A failure after the old stream was dropped leaves the engine without a device
+    let detached = result.is_err() && self.stream.lock().is_none();
+    if detached {
+      *self.output_producer.lock() = None;
+    }
 
-    // Stop old stream explicitly before dropping
-    {
-      let mut stream_guard = self.stream.lock();
-      if let Some(ref stream) = *stream_guard {
-        // Explicitly pause the stream before dropping
-        if let Err(e) = stream.pause() {
-          eprintln!("[AudioEngine] Warning: Failed to pause old stream: {e}");
+    let mut state = self.state.lock();
+    match &result {
+      Ok(device_name) => {
+        state.device_name = Some(device_name.clone());
+        state.device_error = None;
+      }
+      Err(e) => {
+        if detached {
+          state.device_name = None;
+          let device_sample_rate = &state.output_stats.device_sample_rate;
+          device_sample_rate.store(0, Ordering::Relaxed);
         }
+        state.device_error = Some(e.reason.clone());
+        state.update_reason = Some("device_error".to_string());
       }
-      // Drop the old stream
-      *stream_guard = None;
     }
-
-    // Update channel config in state and clear queue
-    {
-      let mut state = self.state.lock();
-      state.channel_config.output_channels = output_channels;
-
-      // Log input config
-      eprintln!(
-        "[AudioEngine] configureDevice input: main={:?}, cue={:?}",
-        config.main_channels, config.cue_channels
-      );
-
-      // Helper to clamp channel to valid range, or None if out of bounds
-      let clamp_channel = |c: i32| -> Option<u16> {
-        if c >= 0 && (c as u16) < output_channels {
-          Some(c as u16)
-        } else {
-          None
-        }
-      };
-
-      // Apply main/cue channel mapping (clamp to device's channel count)
-      if let Some(ref main) = config.main_channels {
-        state.channel_config.main_channels = [
-          main.first().copied().and_then(&clamp_channel),
-          main.get(1).copied().and_then(&clamp_channel),
-        ];
-      } else {
-        // No config provided: default to channels 0 and 1
-        state.channel_config.main_channels =
-          [Some(0), Some(1.min(output_channels.saturating_sub(1)))];
-      }
-
-      if let Some(ref cue) = config.cue_channels {
-        state.channel_config.cue_channels = [
-          cue.first().copied().and_then(&clamp_channel),
-          cue.get(1).copied().and_then(&clamp_channel),
-        ];
-      }
-
-      // Clear output queue (old data has wrong channel count)
-      state.output_queue.clear();
-    }
-
-    // Build and start new output stream
-    let new_stream = build_output_stream(&device, output_channels, Arc::clone(&self.state))?;
-
-    // Set new output stream
-    {
-      let mut stream_guard = self.stream.lock();
-      *stream_guard = Some(new_stream);
-    }
-
-    // Try to build input stream for microphone (using same device)
-    let new_input_stream = build_input_stream(&device, Arc::clone(&self.state));
-
-    // Check if mic is available
-    let has_mic = new_input_stream.is_some();
-
-    // Set new input stream
-    {
-      let mut input_guard = self.input_stream.lock();
-      *input_guard = new_input_stream;
-    }
-
-    // Resume process thread and log detailed config
-    {
-      let mut state = self.state.lock();
-      state.configuring = false;
-      state.mic_available = has_mic;
-      eprintln!(
-        "[AudioEngine] Device configured: channels={}, sample_rate={}, main={:?}, cue={:?}, mic={}",
-        output_channels,
-        self.sample_rate,
-        state.channel_config.main_channels,
-        state.channel_config.cue_channels,
-        if has_mic { "available" } else { "N/A" }
-      );
-    }
-
-    Ok(())
+    result.map(|_| ())
   }
 
   /// Load PCM data onto a deck
+  /// If the other deck holds the same track (same trackId and length) its buffer is shared
   #[napi]
   pub fn load_track(
     &self,
@@ -661,24 +2577,67 @@ impl AudioEngine {
     bpm: Option<f64>,
     track_id: Option<String>,
   ) -> Result<()> {
-    let mut state = self.state.lock();
-    let master_tempo = state.master_tempo;
-    let deck_state = if deck == 1 {
-      &mut state.deck_a
-    } else {
-      &mut state.deck_b
+    load_deck_track(
+      &self.state,
+      deck,
+      &pcm_data,
+      bpm.map(|b| b as f32),
+      track_id,
+    );
+    Ok(())
+  }
+
+  /// Queue PCM data to follow the deck's current track without a gap
+  /// When the current track ends the deck continues into it (update reason "advanced");
+  /// queueing again replaces the pending track
+  #[napi]
+  pub fn queue_next(
+    &self,
+    deck: u32,
+    pcm_data: Float32Array,
+    bpm: Option<f64>,
+    track_id: Option<String>,
+  ) -> Result<()> {
+    // Copy the PCM outside the lock, as in load_track()
+    let pcm = pcm_data.to_vec();
+    let next = QueuedTrack {
+      headroom_db: headroom_db(simd::peak_abs(&pcm)),
+      pcm,
+      bpm: bpm.map(|b| b as f32),
+      track_id,
     };
 
-    deck_state.pcm_data = Some(pcm_data.to_vec());
-    deck_state.position = 0;
-    deck_state.playing = false;
-    deck_state.bpm = bpm.map(|b| b as f32);
-    deck_state.rate = calculate_playback_rate(bpm.map(|b| b as f32), master_tempo);
-    deck_state.track_id = track_id;
-    deck_state.time_stretcher.clear();
+    let previous = self.state.lock().deck_mut(deck).next_track.replace(next);
+    drop(previous);
 
-    state.update_reason = Some("load".to_string());
+    Ok(())
+  }
 
+  /// Drop the track queued on a deck
+  #[napi]
+  pub fn clear_next(&self, deck: u32) -> Result<()> {
+    let previous = self.state.lock().deck_mut(deck).next_track.take();
+    drop(previous);
+    Ok(())
+  }
+
+  /// Instant doubles: put the track from one deck on the other, at the same position,
+  /// BPM, tempo and beat grid, and play it in phase with what the source deck is playing
+  /// (soft-started like play()). The PCM is shared, not copied
+  /// Like a load, the target's loop, queued track and cues are dropped
+  /// Emits update reason "load"
+  #[napi]
+  pub fn instant_double(&self, from_deck: u32, to_deck: u32) -> Result<()> {
+    if (from_deck == 1) == (to_deck == 1) {
+      return Err(Error::from_reason(
+        "Instant double needs two different decks",
+      ));
+    }
+
+    let previous_pcm = self.state.lock().instant_double(from_deck)?;
+
+    // Free the replaced track outside the lock, as in load_track()
+    drop(previous_pcm);
     Ok(())
   }
 
@@ -686,34 +2645,118 @@ impl AudioEngine {
   #[napi]
   pub fn play(&self, deck: u32) -> Result<()> {
     let mut state = self.state.lock();
-    if deck == 1 {
-      if state.deck_a.pcm_data.is_some() {
-        state.deck_a.playing = true;
-      }
-    } else if state.deck_b.pcm_data.is_some() {
-      state.deck_b.playing = true;
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_some() {
+      deck_state.start();
     }
     state.update_reason = Some("play".to_string());
     Ok(())
   }
 
-  /// Stop playback on a deck
+  /// Stop playback on a deck (after a fade-out of a few milliseconds)
   #[napi]
   pub fn stop(&self, deck: u32) -> Result<()> {
     let mut state = self.state.lock();
-    if deck == 1 {
-      state.deck_a.playing = false;
-    } else {
-      state.deck_b.playing = false;
-    }
+    state.deck_mut(deck).soft_stop(None);
     // Reset crossfade state
     state.crossfade.active = false;
     state.crossfade.direction = None;
     state.crossfade.remaining_frames = 0;
+    state.cancel_filter_fade();
     state.update_reason = Some("stop".to_string());
     Ok(())
   }
 
+  /// Silence all output and freeze every deck in place
+  /// Unlike stop(), playing flags and positions are preserved for resume_all()
+  #[napi]
+  pub fn pause_all(&self) -> Result<()> {
+    let mut state = self.state.lock();
+    state.paused = true;
+    state.update_reason = Some("pause".to_string());
+    Ok(())
+  }
+
+  /// Resume output after pause_all() exactly where it left off
+  #[napi]
+  pub fn resume_all(&self) -> Result<()> {
+    let mut state = self.state.lock();
+    state.paused = false;
+    state.update_reason = Some("resume".to_string());
+    Ok(())
+  }
+
+  /// Emergency silence (e.g. feedback): the output drops all queued audio at once and plays
+  /// silence until release_panic(); both decks, the samplers, the mic and any automation stop
+  /// Unlike pause_all() nothing resumes afterwards, and the output stream keeps running
+  #[napi]
+  pub fn panic(&self) -> Result<()> {
+    let mut state = self.state.lock();
+    state.panicked = true;
+    state.output_stats.silenced.store(true, Ordering::Relaxed);
+    state.deck_a.playing = false;
+    state.deck_b.playing = false;
+    state.microphone.enabled = false;
+    state.microphone.monitor = false;
+    for slot in 0..SAMPLER_SLOTS {
+      state.sampler.stop(slot);
+    }
+    state.crossfade.active = false;
+    state.crossfade.direction = None;
+    state.crossfade.remaining_frames = 0;
+    state.cancel_filter_fade();
+    state.scheduled.clear();
+    state.update_reason = Some("panic".to_string());
+    Ok(())
+  }
+
+  /// Let audio through again after panic() (decks, samplers and mic stay stopped)
+  #[napi]
+  pub fn release_panic(&self) -> Result<()> {
+    let mut state = self.state.lock();
+    state.panicked = false;
+    state.output_stats.silenced.store(false, Ordering::Relaxed);
+    state.update_reason = Some("panic_released".to_string());
+    Ok(())
+  }
+
+  /// Set the temporary cue point at the deck's current position (CDJ CUE button)
+  #[napi]
+  pub fn set_cue(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_some() {
+      deck_state.temp_cue = Some(deck_state.position);
+    }
+    state.update_reason = Some("cue".to_string());
+    Ok(())
+  }
+
+  /// Start previewing from the temporary cue point (CUE held while stopped)
+  #[napi]
+  pub fn cue_preview(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if let Some(cue) = deck_state.temp_cue {
+      deck_state.jump(cue);
+      deck_state.start();
+    }
+    state.update_reason = Some("cue".to_string());
+    Ok(())
+  }
+
+  /// Stop the cue preview and return to the temporary cue point (CUE released)
+  #[napi]
+  pub fn cue_release(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if let Some(cue) = deck_state.temp_cue {
+      deck_state.soft_stop(Some(cue));
+    }
+    state.update_reason = Some("cue".to_string());
+    Ok(())
+  }
+
   /// Seek within a deck (position: 0.0 to 1.0)
   #[napi]
   pub fn seek(&self, deck: u32, position: f64) -> Result<()> {
@@ -728,8 +2771,7 @@ impl AudioEngine {
 
     if let Some(ref pcm) = deck_state.pcm_data {
       let total_frames = pcm.len() / DEFAULT_CHANNELS as usize;
-      deck_state.position = (total_frames as f64 * position) as usize;
-      deck_state.time_stretcher.clear();
+      deck_state.jump((total_frames as f64 * position) as usize);
     }
 
     // Mark that a seek operation occurred
@@ -738,6 +2780,31 @@ impl AudioEngine {
     Ok(())
   }
 
+  /// Jump to the start of the track
+  #[napi]
+  pub fn to_start(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_some() {
+      deck_state.jump(0);
+    }
+    state.update_reason = Some("seek".to_string());
+    Ok(())
+  }
+
+  /// Jump to the last frame of the track (a playing deck ends on the next chunk)
+  #[napi]
+  pub fn to_end(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if let Some(ref pcm) = deck_state.pcm_data {
+      let total_frames = pcm.len() / DEFAULT_CHANNELS as usize;
+      deck_state.jump(total_frames.saturating_sub(1));
+    }
+    state.update_reason = Some("seek".to_string());
+    Ok(())
+  }
+
   /// Set crossfader position (0.0 = full A, 1.0 = full B)
   #[napi]
   pub fn set_crossfader_position(&self, position: f64) -> Result<()> {
@@ -746,31 +2813,107 @@ impl AudioEngine {
     Ok(())
   }
 
+  /// Set the crossfader center dead zone: positions within `width` of 0.5 play as exactly
+  /// center, so a jittery hardware fader doesn't wobble a blend (0 disables, max 0.25)
+  /// The rest of the travel is stretched to still reach the ends without a jump
+  #[napi]
+  pub fn set_crossfader_deadzone(&self, width: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.crossfade.deadzone = width.clamp(0.0, CROSSFADER_DEADZONE_MAX) as f32;
+    Ok(())
+  }
+
   /// Start auto crossfade
+  /// stop_on_complete (default true) stops the outgoing deck when the fade completes;
+  /// false keeps it playing so it can be brought back
   #[napi]
-  pub fn start_crossfade(&self, target_position: Option<f64>, duration: f64) -> Result<()> {
+  pub fn start_crossfade(
+    &self,
+    target_position: Option<f64>,
+    duration: f64,
+    stop_on_complete: Option<bool>,
+  ) -> Result<()> {
     let mut state = self.state.lock();
-    let current = state.crossfade.position;
+    let target = target_position.map(|p| p.clamp(0.0, 1.0) as f32);
+    let total_frames = (duration * self.sample_rate as f64) as usize;
+    state.begin_crossfade(target, total_frames, 0);
+    state.crossfade.stop_on_complete = stop_on_complete.unwrap_or(true);
+    Ok(())
+  }
 
-    let target = target_position
-      .map(|p| p.clamp(0.0, 1.0) as f32)
-      .unwrap_or(if state.deck_a.playing { 1.0 } else { 0.0 });
+  /// Transition from one deck to the other over `duration` seconds
+  /// Sweeps the outgoing deck's high-pass from 20 Hz to 8 kHz while auto-crossfading toward
+  /// the incoming deck (started if stopped), then stops the outgoing deck and restores its
+  /// high-pass setting
+  #[napi]
+  pub fn filter_fade(&self, from_deck: u32, to_deck: u32, duration: f64) -> Result<()> {
+    if (from_deck == 1) == (to_deck == 1) {
+      return Err(Error::from_reason("Filter fade needs two different decks"));
+    }
 
-    let direction = if target > current {
-      CrossfadeDirection::AtoB
-    } else {
-      CrossfadeDirection::BtoA
-    };
+    let mut state = self.state.lock();
+    state.cancel_filter_fade();
+    let total_frames = ((duration * self.sample_rate as f64) as usize).max(1);
+    let target = if to_deck == 1 { 0.0 } else { 1.0 };
+    state.begin_crossfade(Some(target), total_frames, 0);
+    if state.deck(to_deck).pcm_data.is_some() {
+      state.deck_mut(to_deck).playing = true;
+    }
 
-    let total_frames = (duration * self.sample_rate as f64) as usize;
+    let restore_hz = state.deck(from_deck).trim_filters.highpass_hz();
+    state.filter_fade = Some(FilterFade {
+      deck: from_deck,
+      total_frames,
+      remaining_frames: total_frames,
+      restore_hz,
+    });
+    state.update_reason = Some("filter_fade".to_string());
+    Ok(())
+  }
 
-    state.crossfade.active = true;
-    state.crossfade.direction = Some(direction);
-    state.crossfade.remaining_frames = total_frames;
-    state.crossfade.total_frames = total_frames;
-    state.crossfade.start_position = current;
-    state.crossfade.target_position = target;
+  /// Start a deck exactly on master clock beat `at_beat` (see masterBeat in the state)
+  /// e.g. the next downbeat is Math.ceil(masterBeat / 4) * 4
+  #[napi]
+  pub fn schedule_play(&self, deck: u32, at_beat: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.schedule(at_beat, Some(deck), ScheduledKind::Play, self.sample_rate)
+  }
+
+  /// Seek a deck (position: 0.0 to 1.0) exactly on master clock beat `at_beat`
+  #[napi]
+  pub fn schedule_seek(&self, deck: u32, at_beat: f64, position: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    let kind = ScheduledKind::Seek(position.clamp(0.0, 1.0));
+    state.schedule(at_beat, Some(deck), kind, self.sample_rate)
+  }
+
+  /// Start an auto crossfade exactly on master clock beat `at_beat`
+  #[napi]
+  pub fn schedule_crossfade(
+    &self,
+    at_beat: f64,
+    target_position: Option<f64>,
+    duration: f64,
+  ) -> Result<()> {
+    if !duration.is_finite() {
+      return Err(Error::from_reason("Crossfade duration must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    let kind = ScheduledKind::Crossfade {
+      target_position: target_position.map(|p| p.clamp(0.0, 1.0) as f32),
+      total_frames: ((duration * self.sample_rate as f64) as usize).max(1),
+    };
+    state.schedule(at_beat, None, kind, self.sample_rate)
+  }
 
+  /// Drop pending scheduled actions for a deck (null = all, including crossfades)
+  #[napi]
+  pub fn cancel_scheduled(&self, deck: Option<u32>) -> Result<()> {
+    let mut state = self.state.lock();
+    state
+      .scheduled
+      .retain(|action| deck.is_some() && action.deck != deck);
     Ok(())
   }
 
@@ -785,45 +2928,598 @@ impl AudioEngine {
     state.master_tempo = bpm as f32;
 
     // Update playback rates (SoundTouch handles tempo changes smoothly without clearing)
-    state.deck_a.rate = calculate_playback_rate(state.deck_a.bpm, state.master_tempo);
-    state.deck_b.rate = calculate_playback_rate(state.deck_b.bpm, state.master_tempo);
+    let master_tempo = state.master_tempo;
+    state.deck_a.update_rate(master_tempo);
+    state.deck_b.update_rate(master_tempo);
 
     Ok(())
   }
 
-  /// Set deck gain (0.0 to 1.0)
+  /// Designate the sync lock tempo reference (1 = A, 2 = B, null = internal clock)
+  #[napi]
+  pub fn set_sync_master(&self, deck: Option<u32>) -> Result<()> {
+    let mut state = self.state.lock();
+    state.sync_master = deck;
+    state.update_reason = Some("sync".to_string());
+    Ok(())
+  }
+
+  /// Enable sync lock for a deck so it stays phase-aligned to the master clock
+  /// first_beat_seconds anchors the deck's beat grid (defaults to the previous value; 0 after a load)
+  #[napi]
+  pub fn set_deck_sync(
+    &self,
+    deck: u32,
+    enabled: bool,
+    first_beat_seconds: Option<f64>,
+  ) -> Result<()> {
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    deck_state.sync_enabled = enabled;
+    deck_state.sync_correction = 0.0;
+    if let Some(first_beat) = first_beat_seconds {
+      deck_state.first_beat = first_beat;
+    }
+    state.update_reason = Some("sync".to_string());
+    Ok(())
+  }
+
+  /// Get a deck's beat grid (None without a BPM)
+  /// Without an imported grid the beats are the uniform grid over the loaded track
+  #[napi]
+  pub fn get_beat_grid(&self, deck: u32) -> Result<Option<BeatGridJs>> {
+    let state = self.state.lock();
+    let deck_state = state.deck(deck);
+    let Some(bpm) = deck_state.bpm.filter(|&b| b > 0.0).map(|b| b as f64) else {
+      return Ok(None);
+    };
+
+    let beats = if deck_state.beat_grid.is_empty() {
+      let duration = deck_state
+        .pcm_data
+        .as_ref()
+        .map_or(0.0, |pcm| pcm.len() as f64 / DEFAULT_CHANNELS as f64)
+        / self.sample_rate as f64;
+      let period = 60.0 / bpm;
+      let first = deck_state.first_beat.rem_euclid(period);
+      (0..)
+        .map(|i| first + i as f64 * period)
+        .take_while(|&t| t < duration)
+        .collect()
+    } else {
+      deck_state.beat_grid.clone()
+    };
+
+    Ok(Some(BeatGridJs {
+      bpm,
+      first_beat: deck_state.first_beat,
+      beats,
+    }))
+  }
+
+  /// Apply a saved beat grid to a deck (e.g. from getBeatGrid)
+  /// Sets the deck BPM and anchor; with two or more beats, quantize, sync and beat phase
+  /// follow the listed beats (needed for variable-tempo tracks)
+  #[napi]
+  pub fn set_beat_grid_full(&self, deck: u32, grid: BeatGridJs) -> Result<()> {
+    if !grid.bpm.is_finite() || grid.bpm <= 0.0 {
+      return Err(Error::from_reason(format!("Invalid BPM: {}", grid.bpm)));
+    }
+    let mut beats: Vec<f64> = grid.beats.into_iter().filter(|b| b.is_finite()).collect();
+    beats.sort_by(f64::total_cmp);
+    beats.dedup();
+
+    let mut state = self.state.lock();
+    let master_tempo = state.master_tempo;
+    let deck_state = state.deck_mut(deck);
+    deck_state.bpm = Some(grid.bpm as f32);
+    deck_state.first_beat = grid.first_beat;
+    deck_state.beat_grid = beats;
+    deck_state.update_rate(master_tempo);
+    state.update_reason = Some("beat_grid".to_string());
+    Ok(())
+  }
+
+  /// Set deck gain (0.0 to 1.0), mapped to linear gain through the deck's fader curve
+  #[napi]
+  pub fn set_deck_gain(&self, deck: u32, gain: f64) -> Result<()> {
+    let fader = gain.clamp(0.0, 1.0) as f32;
+
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    deck_state.fader = fader;
+    deck_state.gain = deck_state.fader_curve.gain(fader);
+    Ok(())
+  }
+
+  /// Set a deck's fader curve: "linear" (-6 dB at half travel), "square" (default, -12 dB at
+  /// half travel) or "logarithmic" (linear in dB from -60 dB to 0 dB, -30 dB at half travel)
+  /// The current fader position is re-mapped immediately
+  #[napi]
+  pub fn set_fader_curve(&self, deck: u32, curve: String) -> Result<()> {
+    let curve = FaderCurve::from_name(&curve)
+      .ok_or_else(|| Error::from_reason(format!("Invalid fader curve: {}", curve)))?;
+
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    deck_state.fader_curve = curve;
+    deck_state.gain = curve.gain(deck_state.fader);
+    Ok(())
+  }
+
+  /// Set deck trim / input gain in dB (-inf to +12), applied before EQ and the fader
+  /// Changes ramp in over the fader smoothing time
+  #[napi]
+  pub fn set_deck_trim(&self, deck: u32, gain_db: f64) -> Result<()> {
+    if gain_db.is_nan() {
+      return Err(Error::from_reason("Invalid trim: NaN"));
+    }
+    let trim = 10.0f64.powf(gain_db.min(12.0) / 20.0) as f32;
+    let mut state = self.state.lock();
+    state.deck_mut(deck).trim = trim;
+    Ok(())
+  }
+
+  /// Set the target deck's trim so its recent RMS level matches the reference deck's
+  /// One-shot: uses the ~3 s rolling RMS of each deck (post trim/EQ, pre-fader), so both
+  /// decks should have been playing for a few seconds. Returns the new trim in dB (max +12)
+  #[napi]
+  pub fn match_levels(&self, reference_deck: u32, target_deck: u32) -> Result<f64> {
+    if (reference_deck == 1) == (target_deck == 1) {
+      return Err(Error::from_reason("Level match needs two different decks"));
+    }
+
+    let mut state = self.state.lock();
+    let levels = &state.levels;
+    let (reference, target) = if target_deck == 1 {
+      (levels.deck_b_mean_square, levels.deck_a_mean_square)
+    } else {
+      (levels.deck_a_mean_square, levels.deck_b_mean_square)
+    };
+    if reference <= 0.0 || target <= 0.0 {
+      return Err(Error::from_reason("No recent level to match on both decks"));
+    }
+
+    // Mean squares are power ratios: 10 * log10 gives the dB difference
+    let deck_state = state.deck_mut(target_deck);
+    let old_trim = deck_state.trim;
+    let trim_db = 20.0 * (old_trim as f64).log10() + 10.0 * (reference / target).log10() as f64;
+    let trim_db = trim_db.min(12.0);
+    deck_state.trim = 10.0f64.powf(trim_db / 20.0) as f32;
+
+    // Show the new trim in the target's reading right away
+    let change = (deck_state.trim / old_trim).powi(2);
+    if target_deck == 1 {
+      state.levels.deck_a_mean_square *= change;
+    } else {
+      state.levels.deck_b_mean_square *= change;
+    }
+    state.update_reason = Some("match_levels".to_string());
+    Ok(trim_db)
+  }
+
+  /// Set a deck's high-pass cleanup filter cutoff in Hz (20 to 20000; 0 = off)
+  /// 12 dB/octave, after trim and before the EQ; independent of the EQ bands
+  #[napi]
+  pub fn set_deck_hpf(&self, deck: u32, hz: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.deck_mut(deck).trim_filters.set_highpass_hz(hz as f32);
+    Ok(())
+  }
+
+  /// Set a deck's low-pass cleanup filter cutoff in Hz (20 to 20000; 0 = off)
+  #[napi]
+  pub fn set_deck_lpf(&self, deck: u32, hz: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.deck_mut(deck).trim_filters.set_lowpass_hz(hz as f32);
+    Ok(())
+  }
+
+  /// Mute a deck on the master (the cue bus is unaffected)
+  /// Independent of fader and crossfader, so unmuting restores the previous level
+  #[napi]
+  pub fn set_deck_mute(&self, deck: u32, muted: bool) -> Result<()> {
+    let mut state = self.state.lock();
+    state.deck_mut(deck).muted = muted;
+    Ok(())
+  }
+
+  /// Solo a deck: while any deck is soloed, non-soloed decks are muted on the master
+  #[napi]
+  pub fn set_deck_solo(&self, deck: u32, soloed: bool) -> Result<()> {
+    let mut state = self.state.lock();
+    state.deck_mut(deck).soloed = soloed;
+    Ok(())
+  }
+
+  /// Set a deck's pitch fader range: "6", "10", "16" (±percent) or "wide" (0.5x to 2x)
+  /// The playback rate, including the tempo match, is clamped to this range
+  #[napi]
+  pub fn set_pitch_range(&self, deck: u32, range: String) -> Result<()> {
+    let range = PitchRange::from_name(&range)
+      .ok_or_else(|| Error::from_reason(format!("Invalid pitch range: {}", range)))?;
+
+    let mut state = self.state.lock();
+    let master_tempo = state.master_tempo;
+    let deck_state = state.deck_mut(deck);
+    deck_state.pitch_range = range;
+    let (min, max) = range.limits();
+    deck_state.pitch_adjust = deck_state
+      .pitch_adjust
+      .clamp((min - 1.0) * 100.0, (max - 1.0) * 100.0);
+    deck_state.update_rate(master_tempo);
+    Ok(())
+  }
+
+  /// Move a deck's pitch fader (percent, limited to the pitch range)
+  /// Applied on top of the master tempo match, independent of sync lock
+  #[napi]
+  pub fn set_deck_pitch_adjust(&self, deck: u32, percent: f64) -> Result<()> {
+    if !percent.is_finite() {
+      return Err(Error::from_reason("Pitch adjust must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    let master_tempo = state.master_tempo;
+    let deck_state = state.deck_mut(deck);
+    let (min, max) = deck_state.pitch_range.limits();
+    deck_state.pitch_adjust = (percent as f32).clamp((min - 1.0) * 100.0, (max - 1.0) * 100.0);
+    deck_state.update_rate(master_tempo);
+    Ok(())
+  }
+
+  /// Set deck pan (-1.0 left, 0.0 center, 1.0 right)
+  #[napi]
+  pub fn set_deck_pan(&self, deck: u32, pan: f64) -> Result<()> {
+    if !pan.is_finite() {
+      return Err(Error::from_reason("Deck pan must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.deck_mut(deck).pan = pan.clamp(-1.0, 1.0) as f32;
+    Ok(())
+  }
+
+  /// Assign a deck to a crossfader side: "a", "b" or "thru" (ignores the crossfader)
+  /// Defaults: deck A on side A, deck B on side B
+  #[napi]
+  pub fn set_crossfader_assign(&self, deck: u32, side: String) -> Result<()> {
+    let side = CrossfaderSide::from_name(&side)
+      .ok_or_else(|| Error::from_reason(format!("Invalid crossfader side: {}", side)))?;
+
+    let mut state = self.state.lock();
+    state.deck_mut(deck).crossfader_side = side;
+    Ok(())
+  }
+
+  /// Move a deck's jog wheel by delta_frames (negative = backwards)
+  /// Scratch mode plays the movement at the implied speed and direction, even
+  /// while paused; bend mode nudges the tempo of a playing deck instead.
+  /// Send deltas at least every chunk for a continuous scratch.
+  #[napi]
+  pub fn jog(&self, deck: u32, delta_frames: f64) -> Result<()> {
+    if !delta_frames.is_finite() {
+      return Err(Error::from_reason("Jog delta must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_none() {
+      return Ok(());
+    }
+    if deck_state.jog_mode == JogMode::Bend && !deck_state.playing {
+      return Ok(());
+    }
+    deck_state.jog_pending += delta_frames;
+    Ok(())
+  }
+
+  /// Set what a deck's jog wheel does: "scratch" (default) or "bend"
+  #[napi]
+  pub fn set_jog_mode(&self, deck: u32, mode: String) -> Result<()> {
+    let mode = JogMode::from_name(&mode)
+      .ok_or_else(|| Error::from_reason(format!("Invalid jog mode: {}", mode)))?;
+
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    deck_state.jog_mode = mode;
+    deck_state.jog_pending = 0.0;
+    Ok(())
+  }
+
+  /// Report deck positions as currently audible, i.e. minus outputLatencyMs
+  /// (for playheads, visuals and lighting that must match what is heard)
+  #[napi]
+  pub fn set_latency_compensation(&self, enabled: bool) -> Result<()> {
+    self.state.lock().latency_compensation = enabled;
+    Ok(())
+  }
+
+  /// Reset the output underrun counter reported as underrunCount
+  #[napi]
+  pub fn reset_underrun_count(&self) -> Result<()> {
+    let state = self.state.lock();
+    state.output_stats.underruns.store(0, Ordering::Relaxed);
+    Ok(())
+  }
+
+  /// Clear the latched clip indicators (deck A, deck B and master)
+  #[napi]
+  pub fn reset_clip_indicators(&self) -> Result<()> {
+    let mut state = self.state.lock();
+    state.levels.deck_a_clipped = false;
+    state.levels.deck_b_clipped = false;
+    state.levels.master_clipped = false;
+    Ok(())
+  }
+
+  /// Set peak-hold meter ballistics: hold time (0 to 10000 ms, default 1500) and
+  /// fall rate afterwards (0.5 to 100 dB/s, default 6)
+  #[napi]
+  pub fn set_meter_ballistics(&self, hold_ms: f64, decay_db_per_sec: f64) -> Result<()> {
+    if !(0.0..=10000.0).contains(&hold_ms) {
+      return Err(Error::from_reason(format!(
+        "Invalid hold time: {} ms",
+        hold_ms
+      )));
+    }
+    if !(0.5..=100.0).contains(&decay_db_per_sec) {
+      return Err(Error::from_reason(format!(
+        "Invalid decay rate: {} dB/s",
+        decay_db_per_sec
+      )));
+    }
+    let mut state = self.state.lock();
+    state.levels.hold_duration = Duration::from_secs_f64(hold_ms / 1000.0);
+    state.levels.decay_db_per_sec = decay_db_per_sec as f32;
+    Ok(())
+  }
+
+  /// Set smoothing time for deck gain, crossfader and mic gain changes (milliseconds)
+  /// 0 disables smoothing (changes apply instantly)
+  #[napi]
+  pub fn set_smoothing_time(&self, ms: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.smoothing_time = (ms.clamp(0.0, 1000.0) / 1000.0) as f32;
+    Ok(())
+  }
+
+  /// Enable soft-clip saturation on the master (drive: 1.0 to 10.0)
+  /// A hard clamp is still applied afterwards as a safety net
+  #[napi]
+  pub fn set_soft_clip(&self, enabled: bool, drive: Option<f64>) -> Result<()> {
+    let mut state = self.state.lock();
+    state.soft_clip_enabled = enabled;
+    if let Some(drive) = drive {
+      state.soft_clip_drive = drive.clamp(1.0, 10.0) as f32;
+    }
+    Ok(())
+  }
+
+  /// Set the master output trim in dB (finite, up to +12, default 0)
+  /// Applied to the final master mix before soft clip and the output clamp; the cue bus is unaffected
+  #[napi]
+  pub fn set_master_gain(&self, gain_db: f64) -> Result<()> {
+    if !gain_db.is_finite() {
+      return Err(Error::from_reason("Master gain must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.master_gain_db = gain_db.min(12.0) as f32;
+    Ok(())
+  }
+
+  /// Set the master tone control: low shelf (100 Hz), mid peak (1 kHz), high shelf (10 kHz)
+  /// Gains in dB, clamped to -12..+12; all at 0 dB bypasses the EQ
+  #[napi]
+  pub fn set_master_eq(&self, low_db: f64, mid_db: f64, high_db: f64) -> Result<()> {
+    if ![low_db, mid_db, high_db].iter().all(|db| db.is_finite()) {
+      return Err(Error::from_reason("Master EQ gains must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state
+      .master_eq
+      .set_gains_db(low_db as f32, mid_db as f32, high_db as f32);
+    Ok(())
+  }
+
+  /// Bypass the master tone control, keeping its band gains
+  #[napi]
+  pub fn set_master_eq_bypass(&self, bypassed: bool) -> Result<()> {
+    let mut state = self.state.lock();
+    state.master_eq.set_bypassed(bypassed);
+    Ok(())
+  }
+
+  /// Enable the master limiter
+  /// input_gain_db (-24 to +24) drives the limiter; headroom_db (0 to 40) sets its ceiling
+  /// below 0 dBFS. Gain reduction is reported as masterGainReductionDb
+  #[napi]
+  pub fn set_master_limiter(
+    &self,
+    enabled: bool,
+    input_gain_db: f64,
+    headroom_db: f64,
+  ) -> Result<()> {
+    if !input_gain_db.is_finite() || !headroom_db.is_finite() {
+      return Err(Error::from_reason(format!(
+        "Invalid master limiter: {} dB input gain / {} dB headroom",
+        input_gain_db, headroom_db
+      )));
+    }
+
+    let mut state = self.state.lock();
+    state.master_limiter_enabled = enabled;
+    state.master_limiter.set_input_gain_db(input_gain_db as f32);
+    state.master_limiter.set_ceiling_db(-headroom_db as f32);
+    Ok(())
+  }
+
+  /// Tune the master limiter: attack (0 to 100 ms), release (1 to 2000 ms) and lookahead
+  /// (0 to 20 ms). The lookahead delays the master by its length (masterLimiterLatencyMs),
+  /// which latency compensation accounts for while the limiter is enabled
+  #[napi]
+  pub fn set_limiter_params(
+    &self,
+    attack_ms: f64,
+    release_ms: f64,
+    lookahead_ms: f64,
+  ) -> Result<()> {
+    if !attack_ms.is_finite() || !release_ms.is_finite() || !lookahead_ms.is_finite() {
+      return Err(Error::from_reason(format!(
+        "Invalid limiter params: {} ms attack / {} ms release / {} ms lookahead",
+        attack_ms, release_ms, lookahead_ms
+      )));
+    }
+
+    let sample_rate = self.sample_rate;
+    let mut state = self.state.lock();
+    let limiter = &mut state.master_limiter;
+    limiter.set_attack((attack_ms.clamp(0.0, 100.0) / 1000.0) as f32, sample_rate);
+    limiter.set_release((release_ms.clamp(1.0, 2000.0) / 1000.0) as f32, sample_rate);
+    limiter.set_lookahead((lookahead_ms.clamp(0.0, 20.0) / 1000.0) as f32, sample_rate);
+    Ok(())
+  }
+
+  /// Set the master stereo width (0.0 = mono, 1.0 = normal, up to 2.0 = wider)
+  /// Scales the side signal of a mid/side split; the cue bus is unaffected
+  #[napi]
+  pub fn set_stereo_width(&self, width: f64) -> Result<()> {
+    if !width.is_finite() {
+      return Err(Error::from_reason("Stereo width must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.stereo_width = width.clamp(0.0, 2.0) as f32;
+    Ok(())
+  }
+
+  /// Sum the master to mono (club systems, broadcast); the cue bus stays stereo
+  #[napi]
+  pub fn set_mono_output(&self, enabled: bool) -> Result<()> {
+    let mut state = self.state.lock();
+    state.mono_output = enabled;
+    Ok(())
+  }
+
+  /// Set headphone (cue) output gain (0.0 to 2.0), independent of the master
+  #[napi]
+  pub fn set_cue_gain(&self, value: f64) -> Result<()> {
+    if !value.is_finite() {
+      return Err(Error::from_reason("Cue gain must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.cue_bus.gain = value.clamp(0.0, 2.0) as f32;
+    Ok(())
+  }
+
+  /// Enable the cue limiter with an output ceiling in dBFS (-40 to 0)
+  #[napi]
+  pub fn set_cue_limiter(&self, enabled: bool, ceiling_db: f64) -> Result<()> {
+    if !ceiling_db.is_finite() {
+      return Err(Error::from_reason("Cue limiter ceiling must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.cue_bus.limiter_enabled = enabled;
+    state.cue_bus.limiter.set_ceiling_db(ceiling_db as f32);
+    Ok(())
+  }
+
+  /// Set latency profile: "low", "balanced" or "safe" (default)
+  /// Lower profiles use smaller chunks and a shallower output queue at the cost of
+  /// underrun headroom; switch back to "safe" if the output glitches
+  #[napi]
+  pub fn set_latency_profile(&self, profile: String) -> Result<()> {
+    let latency_profile = LatencyProfile::from_name(&profile)
+      .ok_or_else(|| Error::from_reason(format!("Invalid latency profile: {}", profile)))?;
+    let mut state = self.state.lock();
+    state.latency_profile = latency_profile;
+    Ok(())
+  }
+
+  /// Set EQ cut (kill switch) for a specific band on a deck
+  /// A cut band drops to the kill depth (-26 dB by default, see setEqKillDepth)
+  /// band: "low", "mid", "high" (3-band) or "low", "lowmid", "highmid", "high" (4-band)
+  #[napi]
+  pub fn set_eq_cut(&self, deck: u32, band: String, enabled: bool) -> Result<()> {
+    let eq_band = EqBand::from_name(&band)
+      .ok_or_else(|| Error::from_reason(format!("Invalid EQ band: {}", band)))?;
+
+    let mut state = self.state.lock();
+    let eq = &mut state.deck_mut(deck).eq_processor;
+    if !eq.set_cut(eq_band, enabled) {
+      return Err(eq_band_unavailable(&band, eq.mode()));
+    }
+    Ok(())
+  }
+
+  /// Set EQ band gain for a deck (dB, clamped to -26..+6)
+  /// band: same names as setEqCut for the deck's current mode
+  #[napi]
+  pub fn set_eq_gain(&self, deck: u32, band: String, gain_db: f64) -> Result<()> {
+    if !gain_db.is_finite() {
+      return Err(Error::from_reason(format!("Invalid EQ gain: {}", gain_db)));
+    }
+
+    let eq_band = EqBand::from_name(&band)
+      .ok_or_else(|| Error::from_reason(format!("Invalid EQ band: {}", band)))?;
+
+    let mut state = self.state.lock();
+    let eq = &mut state.deck_mut(deck).eq_processor;
+    if !eq.set_gain_db(eq_band, gain_db as f32) {
+      return Err(eq_band_unavailable(&band, eq.mode()));
+    }
+    Ok(())
+  }
+
+  /// Set how far EQ cuts attenuate a band on a deck (dB, default -26)
+  /// Clamped to -60..0; pass -Infinity for a true kill
+  #[napi]
+  pub fn set_eq_kill_depth(&self, deck: u32, depth_db: f64) -> Result<()> {
+    if depth_db.is_nan() {
+      return Err(Error::from_reason("Invalid EQ kill depth: NaN"));
+    }
+
+    let mut state = self.state.lock();
+    state
+      .deck_mut(deck)
+      .eq_processor
+      .set_kill_depth_db(depth_db as f32);
+    Ok(())
+  }
+
+  /// Select the EQ layout for a deck: "3band" (default) or "4band"
+  /// Low/high cut and gain carry over; mid bands reset
   #[napi]
-  pub fn set_deck_gain(&self, deck: u32, gain: f64) -> Result<()> {
-    let gain = gain.clamp(0.0, 1.0) as f32;
-    // Apply logarithmic curve for natural volume control
-    let db_gain = if gain == 0.0 { 0.0 } else { gain * gain };
+  pub fn set_eq_mode(&self, deck: u32, mode: String) -> Result<()> {
+    let eq_mode = EqMode::from_name(&mode)
+      .ok_or_else(|| Error::from_reason(format!("Invalid EQ mode: {}", mode)))?;
 
     let mut state = self.state.lock();
-    if deck == 1 {
-      state.deck_a.gain = db_gain;
-    } else {
-      state.deck_b.gain = db_gain;
-    }
+    state.deck_mut(deck).eq_processor.set_mode(eq_mode);
+    state.update_reason = Some("eq_mode".to_string());
     Ok(())
   }
 
-  /// Set EQ cut (kill switch) for a specific band on a deck
-  /// band: "low", "mid", "high"
+  /// Set EQ crossover frequencies for a deck (Hz)
+  /// low_mid is clamped to 40-800 Hz, mid_high to 1-16 kHz; low_mid must be below mid_high
   #[napi]
-  pub fn set_eq_cut(&self, deck: u32, band: String, enabled: bool) -> Result<()> {
-    let eq_band = match band.as_str() {
-      "low" => EqBand::Low,
-      "mid" => EqBand::Mid,
-      "high" => EqBand::High,
-      _ => return Err(Error::from_reason(format!("Invalid EQ band: {}", band))),
-    };
+  pub fn set_eq_crossovers(&self, deck: u32, low_mid: f64, mid_high: f64) -> Result<()> {
+    if !low_mid.is_finite() || !mid_high.is_finite() || low_mid >= mid_high {
+      return Err(Error::from_reason(format!(
+        "Invalid EQ crossovers: {} Hz / {} Hz",
+        low_mid, mid_high
+      )));
+    }
 
     let mut state = self.state.lock();
-    if deck == 1 {
-      state.deck_a.eq_processor.set_cut(eq_band, enabled);
-    } else {
-      state.deck_b.eq_processor.set_cut(eq_band, enabled);
-    }
+    state
+      .deck_mut(deck)
+      .eq_processor
+      .set_crossovers(low_mid as f32, mid_high as f32);
     Ok(())
   }
 
@@ -831,16 +3527,7 @@ impl AudioEngine {
   #[napi]
   pub fn get_eq_cut_state(&self, deck: u32) -> Result<EqCutStateJs> {
     let state = self.state.lock();
-    let eq_state = if deck == 1 {
-      state.deck_a.eq_processor.get_cut_state()
-    } else {
-      state.deck_b.eq_processor.get_cut_state()
-    };
-    Ok(EqCutStateJs {
-      low: eq_state.low,
-      mid: eq_state.mid,
-      high: eq_state.high,
-    })
+    Ok(state.deck(deck).eq_processor.get_cut_state().into())
   }
 
   /// Set cue enabled for a deck
@@ -908,6 +3595,27 @@ impl AudioEngine {
     Ok(create_state_update(&state, self.sample_rate))
   }
 
+  /// Get downsampled peak data for a window of the deck's PCM around a position
+  /// Returns `bins` peak values (0.0-1.0, at most 8192); regions before 0 or past the end are zero
+  #[napi]
+  pub fn get_waveform_window(
+    &self,
+    deck: u32,
+    center_seconds: f64,
+    width_seconds: f64,
+    bins: u32,
+  ) -> Result<Vec<f64>> {
+    // Scan a shared handle to the PCM so the audio path never waits on it
+    let pcm = self.state.lock().deck(deck).pcm_data.clone();
+    Ok(waveform_window(
+      pcm.as_deref().map_or(&[], Vec::as_slice),
+      self.sample_rate,
+      center_seconds,
+      width_seconds,
+      bins as usize,
+    ))
+  }
+
   /// Enable or disable microphone input
   #[napi]
   pub fn set_mic_enabled(&self, enabled: bool) -> Result<()> {
@@ -932,6 +3640,50 @@ impl AudioEngine {
     Ok(())
   }
 
+  /// Set the mic pan (-1.0 = left, 0.0 = center, 1.0 = right)
+  /// Balances the two channels of a stereo mic; a mono mic is panned
+  #[napi]
+  pub fn set_mic_pan(&self, pan: f64) -> Result<()> {
+    if !pan.is_finite() {
+      return Err(Error::from_reason("Mic pan must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.microphone.pan = (pan as f32).clamp(-1.0, 1.0);
+    Ok(())
+  }
+
+  /// Set how much mic audio is buffered in ms (10 to 500, default 100)
+  /// Lower values cut talkover latency but underrun more easily
+  #[napi]
+  pub fn set_mic_latency_ms(&self, ms: f64) -> Result<()> {
+    if !ms.is_finite() {
+      return Err(Error::from_reason("Mic latency must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.microphone.latency_ms = (ms as f32).clamp(MIC_LATENCY_RANGE_MS.0, MIC_LATENCY_RANGE_MS.1);
+    Ok(())
+  }
+
+  /// Monitor the mic directly on the cue (default) or "main" output, e.g. for a soundcheck
+  /// Bypasses talkover: the music isn't ducked and the mic needn't be enabled (mic gain and
+  /// pan still apply). Mixed into the output callback straight from the mic input, so it
+  /// skips the output queue; talkover keeps its own buffer and works alongside it
+  #[napi]
+  pub fn set_mic_monitor(&self, enabled: bool, target: Option<String>) -> Result<()> {
+    let target = match target.as_deref() {
+      None => MicMonitorTarget::Cue,
+      Some(name) => MicMonitorTarget::from_name(name)
+        .ok_or_else(|| Error::from_reason(format!("Invalid mic monitor target: {}", name)))?,
+    };
+    let mut state = self.state.lock();
+    state.microphone.monitor = enabled;
+    state.microphone.monitor_target = target;
+    state.update_reason = Some("mic_monitor".to_string());
+    Ok(())
+  }
+
   /// Set talkover ducking level (0.0 to 1.0 - how much to reduce music)
   #[napi]
   pub fn set_talkover_ducking(&self, ducking: f64) -> Result<()> {
@@ -940,6 +3692,61 @@ impl AudioEngine {
     Ok(())
   }
 
+  /// Queue external (line-in) audio: interleaved stereo at the engine sample rate
+  /// Mixed into the master through its own EQ and fader; push continuously (at most 1 s
+  /// is buffered, older audio is dropped) and gaps play as silence
+  #[napi]
+  pub fn push_external_audio(&self, pcm: Float32Array) -> Result<()> {
+    if pcm.len() % DEFAULT_CHANNELS as usize != 0 {
+      return Err(Error::from_reason(
+        "External audio must be interleaved stereo",
+      ));
+    }
+    let max_samples = self.sample_rate as usize * DEFAULT_CHANNELS as usize;
+    let mut state = self.state.lock();
+    let buffer = &mut state.external_input.buffer;
+    buffer.extend(pcm.iter());
+    let excess = buffer.len().saturating_sub(max_samples);
+    buffer.drain(..excess);
+    Ok(())
+  }
+
+  /// Set the external input fader (0.0 to 1.0, square curve like setDeckGain's default)
+  #[napi]
+  pub fn set_external_gain(&self, gain: f64) -> Result<()> {
+    let mut state = self.state.lock();
+    state.external_input.gain = gain.clamp(0.0, 1.0) as f32;
+    Ok(())
+  }
+
+  /// Set an external input EQ band gain (dB, clamped to -26..+6; 3-band names)
+  #[napi]
+  pub fn set_external_eq_gain(&self, band: String, gain_db: f64) -> Result<()> {
+    let eq_band = EqBand::from_name(&band)
+      .ok_or_else(|| Error::from_reason(format!("Invalid EQ band: {}", band)))?;
+
+    let mut state = self.state.lock();
+    let eq = &mut state.external_input.eq_processor;
+    if !eq.set_gain_db(eq_band, gain_db as f32) {
+      return Err(eq_band_unavailable(&band, eq.mode()));
+    }
+    Ok(())
+  }
+
+  /// Set how fast talkover ducks the music (attack) and restores it (release), in ms
+  /// (0 to 5000 each; defaults 50 and 500)
+  #[napi]
+  pub fn set_talkover_times(&self, attack_ms: f64, release_ms: f64) -> Result<()> {
+    if !attack_ms.is_finite() || !release_ms.is_finite() {
+      return Err(Error::from_reason("Talkover times must be finite"));
+    }
+
+    let mut state = self.state.lock();
+    state.microphone.talkover_attack = (attack_ms.clamp(0.0, 5000.0) / 1000.0) as f32;
+    state.microphone.talkover_release = (release_ms.clamp(0.0, 5000.0) / 1000.0) as f32;
+    Ok(())
+  }
+
   /// Set loop region for a deck (positions in 0.0-1.0 range)
   #[napi]
   pub fn set_loop(&self, deck: u32, start: f64, end: f64, enabled: bool) -> Result<()> {
@@ -985,8 +3792,7 @@ impl AudioEngine {
 
         // Jump to loop start if currently past loop end or before loop start
         if deck_state.position >= loop_end || deck_state.position < loop_start {
-          deck_state.position = loop_start;
-          deck_state.time_stretcher.clear();
+          deck_state.jump(loop_start);
         }
       }
     }
@@ -994,6 +3800,121 @@ impl AudioEngine {
     Ok(())
   }
 
+  /// Loop `beats` beats from the playhead using the deck BPM (auto-loop buttons)
+  /// With an active loop under the playhead, only its length is adjusted
+  /// Emits update reason "auto_loop"
+  #[napi]
+  pub fn set_auto_loop(&self, deck: u32, beats: f64) -> Result<()> {
+    if !beats.is_finite() || beats <= 0.0 {
+      return Err(Error::from_reason(format!("Invalid beat count: {}", beats)));
+    }
+
+    self
+      .state
+      .lock()
+      .set_auto_loop(deck, beats, self.sample_rate)
+  }
+
+  /// Mark the loop in point at the playhead (CDJ LOOP IN)
+  /// Ends an active loop; the loop starts when loop_out() sets the out point
+  #[napi]
+  pub fn loop_in(&self, deck: u32) -> Result<()> {
+    let sample_rate = self.sample_rate;
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_none() {
+      return Ok(());
+    }
+
+    let position = deck_state.loop_punch_frame(sample_rate);
+    deck_state.loop_start = position;
+    deck_state.loop_end = position;
+    deck_state.loop_enabled = false;
+    state.update_reason = Some("loop_in".to_string());
+    Ok(())
+  }
+
+  /// Mark the loop out point at the playhead and start looping (CDJ LOOP OUT)
+  /// While looping this moves the out point; ignored at or before the in point
+  #[napi]
+  pub fn loop_out(&self, deck: u32) -> Result<()> {
+    let sample_rate = self.sample_rate;
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    if deck_state.pcm_data.is_none() {
+      return Ok(());
+    }
+
+    let position = deck_state.loop_punch_frame(sample_rate);
+    if position <= deck_state.loop_start {
+      return Ok(());
+    }
+    deck_state.loop_end = position;
+    deck_state.loop_enabled = true;
+    deck_state.jump(deck_state.loop_start);
+    state.update_reason = Some("loop_out".to_string());
+    Ok(())
+  }
+
+  /// Jump forward (positive) or back (negative) by a number of beats along the beat grid
+  /// With quantize on the playhead lands on a beat; clamped to the track, ignored without a BPM
+  #[napi]
+  pub fn jump_beats(&self, deck: u32, beats: f64) -> Result<()> {
+    let sample_rate = self.sample_rate;
+    let mut state = self.state.lock();
+    let deck_state = state.deck_mut(deck);
+    let Some(frame) = deck_state.beat_jump_frame(beats, sample_rate) else {
+      return Ok(());
+    };
+    deck_state.jump(frame);
+    state.update_reason = Some("beat_jump".to_string());
+    Ok(())
+  }
+
+  /// Jump forward (positive) or back (negative) by whole 4-beat bars (see jump_beats)
+  #[napi]
+  pub fn jump_bars(&self, deck: u32, bars: i32) -> Result<()> {
+    self.jump_beats(deck, bars as f64 * BEATS_PER_BAR)
+  }
+
+  /// Loop roll of `beats` beats (1/32 to 4) from the slip position; calling it again
+  /// while rolling changes the size in place. The track keeps running underneath and
+  /// roll_off() lands where it would have been without the roll
+  #[napi]
+  pub fn roll(&self, deck: u32, beats: f64) -> Result<()> {
+    if !(ROLL_MIN_BEATS..=ROLL_MAX_BEATS).contains(&beats) {
+      return Err(Error::from_reason(format!(
+        "Roll size must be 1/32 to 4 beats: {}",
+        beats
+      )));
+    }
+
+    let sample_rate = self.sample_rate;
+    let mut state = self.state.lock();
+    if state.deck_mut(deck).roll(beats, sample_rate) {
+      state.update_reason = Some("roll".to_string());
+    }
+    Ok(())
+  }
+
+  /// End a loop roll and continue from the slip position
+  #[napi]
+  pub fn roll_off(&self, deck: u32) -> Result<()> {
+    let mut state = self.state.lock();
+    if state.deck_mut(deck).roll_off() {
+      state.update_reason = Some("roll_off".to_string());
+    }
+    Ok(())
+  }
+
+  /// Enable quantize (snap loop starts to the beat grid) for a deck
+  #[napi]
+  pub fn set_quantize(&self, deck: u32, enabled: bool) -> Result<()> {
+    let mut state = self.state.lock();
+    state.deck_mut(deck).quantize = enabled;
+    Ok(())
+  }
+
   /// Clear loop for a deck
   #[napi]
   pub fn clear_loop(&self, deck: u32) -> Result<()> {
@@ -1007,55 +3928,374 @@ impl AudioEngine {
     deck_state.loop_enabled = false;
     deck_state.loop_start = 0;
     deck_state.loop_end = 0;
+    deck_state.roll = None;
+
+    Ok(())
+  }
+
+  /// Load stereo interleaved PCM into a sampler slot (0-7)
+  #[napi]
+  pub fn load_sample(&self, slot: u32, pcm_data: Float32Array) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    // Copy outside the lock, free the previous sample outside it too
+    let pcm = pcm_data.to_vec();
+    let previous = self.state.lock().sampler.load(slot, pcm);
+    drop(previous);
+    Ok(())
+  }
+
+  /// Trigger a sampler slot (one-shot, or restart if looped)
+  #[napi]
+  pub fn trigger_sample(&self, slot: u32) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    self.state.lock().sampler.trigger(slot);
+    Ok(())
+  }
+
+  /// Stop all playing voices of a sampler slot
+  #[napi]
+  pub fn stop_sample(&self, slot: u32) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    self.state.lock().sampler.stop(slot);
+    Ok(())
+  }
+
+  /// Set sampler slot gain (0.0 to 2.0)
+  #[napi]
+  pub fn set_sample_gain(&self, slot: u32, gain: f64) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    self.state.lock().sampler.set_gain(slot, gain as f32);
+    Ok(())
+  }
+
+  /// Set whether a sampler slot loops until stopped
+  #[napi]
+  pub fn set_sample_loop(&self, slot: u32, looped: bool) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    self.state.lock().sampler.set_loop(slot, looped);
+    Ok(())
+  }
 
+  /// Assign a sampler slot to a choke group (triggering one member stops the others)
+  /// Pass null for polyphonic playback
+  #[napi]
+  pub fn set_sample_choke_group(&self, slot: u32, group: Option<u32>) -> Result<()> {
+    let slot = sampler_slot(slot)?;
+    self.state.lock().sampler.set_choke_group(slot, group);
     Ok(())
   }
 
   /// Start recording to a WAV file
+  /// Fails if the file can't be created; later write failures stop the recording and are
+  /// reported as recordingError (update reason "recording_error")
+  /// Optional metadata is written as Vorbis comments (OGG) or a LIST/INFO chunk (WAV)
+  /// target: RecordingTarget.Master (default) or RecordingTarget.Cue (the PFL headphone bus)
+  /// maxDurationSeconds: continue in "<name>-002.<ext>", "-003", ... each time a file reaches
+  /// this length (gapless; a "recording_split" event carries each new path)
+  /// dither: TPDF dither on the 16-bit WAV conversion (default true; OGG ignores it)
   #[napi]
-  pub fn start_recording(&self, path: String, format: String) -> Result<()> {
+  pub fn start_recording(
+    &self,
+    path: String,
+    format: String,
+    metadata: Option<RecordingMetadata>,
+    target: Option<RecordingTarget>,
+    max_duration_seconds: Option<f64>,
+    dither: Option<bool>,
+  ) -> Result<()> {
     let recording_format = match format.as_str() {
       "wav" => crate::recorder::RecordingFormat::Wav,
       "ogg" => crate::recorder::RecordingFormat::Ogg,
       _ => return Err(Error::from_reason(format!("Unsupported recording format: {}", format))),
     };
+    let recording_target = target.unwrap_or(RecordingTarget::Master);
+    let max_frames = match max_duration_seconds {
+      None => None,
+      Some(seconds) if seconds.is_finite() && seconds > 0.0 => {
+        Some((seconds * self.sample_rate as f64).round() as u64)
+      }
+      Some(seconds) => {
+        return Err(Error::from_reason(format!(
+          "Invalid max recording duration: {}",
+          seconds
+        )))
+      }
+    };
+    {
+      let mut state = self.state.lock();
+      state.recording_target = recording_target;
+      state.recording_error = None;
+    }
+    if let Some(ref mut rt) = *self.recording_thread.lock() {
+      let metadata = metadata.unwrap_or_default();
+      let dither = dither.unwrap_or(true);
+      rt.start_recording(
+        path.clone(),
+        recording_format,
+        metadata,
+        self.sample_rate,
+        max_frames,
+        dither,
+      )?;
+      self.state.lock().recording_path = Some(path);
+    }
+    Ok(())
+  }
+
+  /// Stop recording
+  #[napi]
+  pub fn stop_recording(&self) -> Result<()> {
     if let Some(ref mut rt) = *self.recording_thread.lock() {
-      rt.start_recording(path, recording_format)?;
+      rt.stop()?;
+    }
+    self.state.lock().recording_path = None;
+    Ok(())
+  }
+
+  /// Render the mix without an output device (interleaved, device channel layout)
+  /// Advances decks, crossfades and loops exactly like live playback; for tests and bouncing.
+  /// Fails while an output device is streaming, or for a duration over 10 minutes
+  /// (call repeatedly for longer renders).
+  #[napi]
+  pub fn render_offline(&self, duration_seconds: f64) -> Result<Float32Array> {
+    if self.stream.lock().is_some() {
+      return Err(Error::from_reason(
+        "Offline rendering requires no output device to be configured",
+      ));
+    }
+    if !(0.0..=MAX_OFFLINE_SECONDS).contains(&duration_seconds) {
+      return Err(Error::from_reason(format!(
+        "Invalid duration: {}",
+        duration_seconds
+      )));
+    }
+
+    let frames = (duration_seconds * self.sample_rate as f64) as usize;
+    let output_channels = self.state.lock().channel_config.output_channels as usize;
+    let samples = frames * output_channels;
+    let mut output = Vec::with_capacity(samples + MAX_FRAMES_PER_CHUNK * output_channels);
+
+    // Lock per chunk, so a long render never holds the engine for its whole length
+    while output.len() < samples {
+      let (chunk, retired_pcm) = {
+        let mut state = self.state.lock();
+        let channels = state.channel_config.output_channels;
+        let (chunk, _, _) = process_audio_chunk(&mut state, self.sample_rate, channels);
+        state.events.clear();
+        (chunk, std::mem::take(&mut state.retired_pcm))
+      };
+      drop(retired_pcm);
+      output.extend_from_slice(&chunk);
+    }
+
+    output.truncate(samples);
+    Ok(output.into())
+  }
+
+  /// Clean up and stop the engine
+  /// Joins the processing thread; safe to call more than once
+  #[napi]
+  pub fn close(&self) -> Result<()> {
+    self.shutdown()
+  }
+}
+
+impl AudioEngine {
+  /// Open the device's output (and input, if any) streams, returning the device name
+  fn attach_device(&mut self, config: DeviceConfig) -> Result<String> {
+    let resampler = match config.resampler.as_deref() {
+      None => ResamplerQuality::Cubic,
+      Some(name) => ResamplerQuality::from_name(name)
+        .ok_or_else(|| Error::from_reason(format!("Invalid resampler: {}", name)))?,
+    };
+
+    // Get device once and reuse for both output and input
+    let device = get_device(config.device_id.as_deref())?;
+    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+    // Requested channel count, or the device default
+    let default_config = device
+      .default_output_config()
+      .map_err(|e| Error::from_reason(format!("Device '{}' error: {}", device_name, e)))?;
+    let output_channels = match config.output_channels {
+      Some(requested) => supported_output_channels(&device, &default_config, requested)?,
+      None => default_config.channels(),
+    };
+
+    // Stop old stream explicitly before dropping
+    {
+      let mut stream_guard = self.stream.lock();
+      if let Some(ref stream) = *stream_guard {
+        // Explicitly pause the stream before dropping
+        if let Err(e) = stream.pause() {
+          eprintln!("[AudioEngine] Warning: Failed to pause old stream: {e}");
+        }
+      }
+      // Drop the old stream
+      *stream_guard = None;
+    }
+
+    // Update channel config in state
+    {
+      let mut state = self.state.lock();
+      state.channel_config.output_channels = output_channels;
+
+      // Log input config
+      eprintln!(
+        "[AudioEngine] configureDevice input: main={:?}, cue={:?}",
+        config.main_channels, config.cue_channels
+      );
+
+      // Helper to clamp channel to valid range, or None if out of bounds
+      let clamp_channel = |c: i32| -> Option<u16> {
+        if c >= 0 && (c as u16) < output_channels {
+          Some(c as u16)
+        } else {
+          None
+        }
+      };
+
+      // Apply main/cue channel mapping (clamp to device's channel count)
+      if let Some(ref main) = config.main_channels {
+        state.channel_config.main_channels = [
+          main.first().copied().and_then(&clamp_channel),
+          main.get(1).copied().and_then(&clamp_channel),
+        ];
+      } else {
+        // No config provided: default to channels 0 and 1
+        state.channel_config.main_channels =
+          [Some(0), Some(1.min(output_channels.saturating_sub(1)))];
+      }
+
+      if let Some(ref cue) = config.cue_channels {
+        state.channel_config.cue_channels = [
+          cue.first().copied().and_then(&clamp_channel),
+          cue.get(1).copied().and_then(&clamp_channel),
+        ];
+      } else {
+        // Keep the previous cue mapping where it still fits the channel count
+        let cue_channels = &mut state.channel_config.cue_channels;
+        *cue_channels = cue_channels.map(|c| c.filter(|&c| c < output_channels));
+      }
+    }
+
+    // Fresh ring buffer (old data has wrong channel count)
+    let (producer, consumer) =
+      RingBuffer::new(output_queue_capacity(self.sample_rate, output_channels));
+    *self.output_producer.lock() = Some(producer);
+
+    // Mic input ring for the monitor, mixed in by the output callback
+    let (mic_producer, mic_consumer) = RingBuffer::new(mic_monitor_capacity(self.sample_rate));
+    let (output_stats, mic_monitor) = {
+      let state = self.state.lock();
+      (
+        Arc::clone(&state.output_stats),
+        Arc::clone(&state.mic_monitor),
+      )
+    };
+    let mic_monitor = MicMonitorMixer::new(mic_consumer, mic_monitor);
+
+    // Build and start new output stream
+    let new_stream = build_output_stream(
+      &device,
+      output_channels,
+      consumer,
+      output_stats,
+      self.sample_rate,
+      resampler,
+      mic_monitor,
+    )?;
+
+    // Set new output stream
+    {
+      let mut stream_guard = self.stream.lock();
+      *stream_guard = Some(new_stream);
+    }
+
+    // Try to build input stream for microphone (using same device)
+    let new_input_stream = build_input_stream(&device, Arc::clone(&self.state), mic_producer);
+
+    // Check if mic is available
+    let has_mic = new_input_stream.is_some();
+
+    // Set new input stream
+    {
+      let mut input_guard = self.input_stream.lock();
+      *input_guard = new_input_stream;
     }
-    Ok(())
-  }
 
-  /// Stop recording
-  #[napi]
-  pub fn stop_recording(&self) -> Result<()> {
-    if let Some(ref mut rt) = *self.recording_thread.lock() {
-      rt.stop()?;
+    // Resume process thread and log detailed config
+    {
+      let mut state = self.state.lock();
+      state.configuring = false;
+      state.mic_available = has_mic;
+      if !has_mic {
+        state
+          .mic_monitor
+          .input_sample_rate
+          .store(0, Ordering::Relaxed);
+      }
+      eprintln!(
+        "[AudioEngine] Device configured: channels={}, sample_rate={}, main={:?}, cue={:?}, mic={}",
+        output_channels,
+        self.sample_rate,
+        state.channel_config.main_channels,
+        state.channel_config.cue_channels,
+        if has_mic { "available" } else { "N/A" }
+      );
     }
-    Ok(())
+
+    Ok(device_name)
   }
 
-  /// Clean up and stop the engine
-  #[napi]
-  pub fn close(&self) -> Result<()> {
+  /// Stop streams and the processing thread (shared by close() and Drop)
+  fn shutdown(&self) -> Result<()> {
     // Stop the streams first
-    {
-      let mut stream_guard = self.stream.lock();
-      *stream_guard = None;
+    for stream in [&self.stream, &self.input_stream] {
+      if let Some(stream) = stream.lock().take() {
+        if let Err(e) = stream.pause() {
+          eprintln!("[AudioEngine] Warning: Failed to pause stream: {e}");
+        }
+      }
     }
+
+    *self.output_producer.lock() = None;
+
     {
-      let mut input_guard = self.input_stream.lock();
-      *input_guard = None;
+      let mut state = self.state.lock();
+      state.running = false;
+      state.deck_a.playing = false;
+      state.deck_b.playing = false;
+    }
+
+    // Wait for the processing thread to observe `running = false` and exit
+    if let Some(handle) = self.process_thread.lock().take() {
+      let deadline = Instant::now() + PROCESS_THREAD_JOIN_TIMEOUT;
+      while !handle.is_finished() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(5));
+      }
+      if handle.is_finished() {
+        handle
+          .join()
+          .map_err(|_| Error::from_reason("Process thread panicked"))?;
+      } else {
+        eprintln!("[AudioEngine] Warning: Process thread did not exit within timeout");
+      }
     }
 
-    let mut state = self.state.lock();
-    state.running = false;
-    state.deck_a.playing = false;
-    state.deck_b.playing = false;
-    state.output_queue.clear();
     Ok(())
   }
 }
 
+impl Drop for AudioEngine {
+  /// Tear down if JS garbage-collects the engine without calling close()
+  fn drop(&mut self) {
+    if let Err(e) = self.shutdown() {
+      eprintln!("[AudioEngine] Warning: Shutdown on drop failed: {e}");
+    }
+  }
+}
+
 /// Get device's max output channels
 /// Find audio device by name, or return default output device
 fn get_device(device_id: Option<&str>) -> Result<cpal::Device> {
@@ -1074,16 +4314,168 @@ fn get_device(device_id: Option<&str>) -> Result<cpal::Device> {
     eprintln!("[AudioEngine] Device '{}' not found, using default", name);
   }
 
-  host
-    .default_output_device()
-    .ok_or_else(|| Error::from_reason("No default output device available"))
+  host.default_output_device().ok_or_else(|| {
+    Error::from_reason("No audio output device available (no sound card, or none attached yet)")
+  })
+}
+
+/// Validate an explicit output channel count against the device's supported configs
+/// at the default sample format and rate (what the output stream is opened with)
+fn supported_output_channels(
+  device: &cpal::Device,
+  default_config: &cpal::SupportedStreamConfig,
+  requested: u32,
+) -> Result<u16> {
+  let sample_rate = default_config.sample_rate();
+  let mut supported: Vec<u16> = device
+    .supported_output_configs()
+    .map_err(map_err)?
+    .filter(|range| {
+      range.sample_format() == default_config.sample_format()
+        && range.min_sample_rate() <= sample_rate
+        && sample_rate <= range.max_sample_rate()
+    })
+    .map(|range| range.channels())
+    .collect();
+  supported.sort_unstable();
+  supported.dedup();
+
+  match u16::try_from(requested) {
+    Ok(channels) if supported.contains(&channels) => Ok(channels),
+    _ => Err(Error::from_reason(format!(
+      "Unsupported output channel count {} (device supports {:?})",
+      requested, supported
+    ))),
+  }
+}
+
+/// Push a rendered chunk into the output ring
+/// Only whole frames go in, so a full ring never shifts the channel interleave
+fn push_output(producer: &mut Producer<f32>, chunk: &[f32], output_channels: u16) {
+  let channels = output_channels as usize;
+  let samples = chunk.len().min(producer.slots() / channels * channels);
+  if let Ok(slots) = producer.write_chunk_uninit(samples) {
+    slots.fill_from_iter(chunk[..samples].iter().copied());
+  }
+}
+
+/// Fill a device buffer from the output ring; returns false on an underrun
+/// (the missing samples play as silence)
+fn pop_output(consumer: &mut Consumer<f32>, data: &mut [f32]) -> bool {
+  let mut complete = true;
+  for sample in data.iter_mut() {
+    *sample = consumer.pop().unwrap_or_else(|_| {
+      complete = false;
+      0.0
+    });
+  }
+  complete
+}
+
+/// Ring buffer capacity in samples: the deepest profile's fill target plus one chunk of slack
+/// Sized for the largest profile so switching profiles never needs a new ring
+fn output_queue_capacity(sample_rate: u32, output_channels: u16) -> usize {
+  let profile = LatencyProfile::SAFE;
+  (profile.target_queue_frames(sample_rate) * 2 + profile.frames_per_chunk)
+    * output_channels as usize
+}
+
+/// Mic monitor ring capacity in samples (stereo)
+fn mic_monitor_capacity(sample_rate: u32) -> usize {
+  (sample_rate as f32 * MIC_MONITOR_RING_MS / 1000.0) as usize * 2
+}
+
+/// Interpolation used by the output resampler
+#[derive(Clone, Copy, PartialEq)]
+enum ResamplerQuality {
+  Linear,
+  /// Catmull-Rom (4-point) interpolation
+  Cubic,
+}
+
+impl ResamplerQuality {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "linear" => Some(Self::Linear),
+      "cubic" => Some(Self::Cubic),
+      _ => None,
+    }
+  }
+}
+
+/// Converts engine-rate frames from the output queue to the device rate
+///
+/// Runs inside the output callback, so it only pops from the ring buffer and keeps a
+/// four-frame history; reading starts one frame behind to give the cubic its lookahead.
+struct OutputResampler {
+  quality: ResamplerQuality,
+  channels: usize,
+  /// Engine frames consumed per device frame
+  step: f64,
+  /// Read position between history frames 1 and 2 (0.0 to 1.0)
+  phase: f64,
+  /// Last four engine frames, oldest first (interleaved)
+  history: Vec<f32>,
+}
+
+impl OutputResampler {
+  fn new(quality: ResamplerQuality, channels: usize, engine_rate: u32, device_rate: u32) -> Self {
+    Self {
+      quality,
+      channels,
+      step: engine_rate as f64 / device_rate as f64,
+      phase: 0.0,
+      history: vec![0.0; channels * 4],
+    }
+  }
+
+  /// Fill interleaved device frames; returns false if the queue ran dry (silence fills in)
+  fn process(&mut self, consumer: &mut Consumer<f32>, data: &mut [f32]) -> bool {
+    let channels = self.channels;
+    let mut complete = true;
+    for frame in data.chunks_exact_mut(channels) {
+      while self.phase >= 1.0 {
+        self.history.copy_within(channels.., 0);
+        for sample in &mut self.history[channels * 3..] {
+          *sample = consumer.pop().unwrap_or_else(|_| {
+            complete = false;
+            0.0
+          });
+        }
+        self.phase -= 1.0;
+      }
+
+      let t = self.phase as f32;
+      for (channel, out) in frame.iter_mut().enumerate() {
+        let [y0, y1, y2, y3] = [0, 1, 2, 3].map(|i| self.history[i * channels + channel]);
+        *out = match self.quality {
+          ResamplerQuality::Linear => y1 + (y2 - y1) * t,
+          ResamplerQuality::Cubic => {
+            let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+            let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+            let c = 0.5 * (y2 - y0);
+            ((a * t + b) * t + c) * t + y1
+          }
+        };
+      }
+      self.phase += self.step;
+    }
+    complete
+  }
 }
 
 /// Build an audio output stream for the given device
+/// The callback only pops from the ring buffer consumer and never touches engine state;
+/// when the device runs at another rate than the engine, it resamples on the way out, and
+/// devices without f32 support get the engine's samples converted to their native format
 fn build_output_stream(
   device: &cpal::Device,
   output_channels: u16,
-  state: Arc<Mutex<EngineState>>,
+  mut consumer: Consumer<f32>,
+  output_stats: Arc<OutputStats>,
+  engine_sample_rate: u32,
+  resampler_quality: ResamplerQuality,
+  mut mic_monitor: MicMonitorMixer,
 ) -> Result<cpal::Stream> {
   let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
   eprintln!("[AudioEngine] Using device: {}", device_name);
@@ -1095,28 +4487,85 @@ fn build_output_stream(
     ))
   })?;
 
-  if config.sample_format() != SampleFormat::F32 {
-    return Err(Error::from_reason("Device does not support f32 output"));
-  }
-
+  let sample_format = config.sample_format();
   let mut final_config = config.config();
   final_config.channels = output_channels;
 
-  let state_for_audio = Arc::clone(&state);
-
-  let stream = device
-    .build_output_stream(
-      &final_config,
-      move |data: &mut [f32], _| {
-        let mut state = state_for_audio.lock();
-        for sample in data.iter_mut() {
-          *sample = state.output_queue.pop_front().unwrap_or(0.0);
-        }
-      },
-      move |err| eprintln!("[AudioEngine] Output stream error: {err}"),
-      None,
+  let device_sample_rate = final_config.sample_rate.0;
+  let mut resampler = (device_sample_rate != engine_sample_rate).then(|| {
+    eprintln!(
+      "[AudioEngine] Resampling output {} Hz -> {} Hz",
+      engine_sample_rate, device_sample_rate
+    );
+    OutputResampler::new(
+      resampler_quality,
+      output_channels as usize,
+      engine_sample_rate,
+      device_sample_rate,
     )
-    .map_err(|e| Error::from_reason(format!("Failed to build audio stream: {e}")))?;
+  });
+  output_stats
+    .device_sample_rate
+    .store(device_sample_rate, Ordering::Relaxed);
+
+  let render = move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+    // Time from this callback until its first frame reaches the DAC
+    let timestamp = info.timestamp();
+    if let Some(latency) = timestamp.playback.duration_since(&timestamp.callback) {
+      let micros = latency.as_micros() as u64;
+      output_stats
+        .device_latency_us
+        .store(micros, Ordering::Relaxed);
+    }
+
+    // Panic: throw away everything queued so no tail plays out
+    if output_stats.silenced.load(Ordering::Relaxed) {
+      while consumer.pop().is_ok() {}
+      data.fill(0.0);
+      return;
+    }
+
+    // Underruns play silence (counted once per short callback)
+    let complete = if let Some(ref mut resampler) = resampler {
+      resampler.process(&mut consumer, data)
+    } else {
+      pop_output(&mut consumer, data)
+    };
+    if !complete {
+      output_stats.underruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    mic_monitor.mix(data, output_channels as usize, device_sample_rate);
+  };
+
+  // f32 devices take the engine's samples as-is; integer formats convert in the callback
+  // through a scratch buffer sized for the device's largest callback
+  let max_callback_frames = match config.buffer_size() {
+    cpal::SupportedBufferSize::Range { max, .. } => (*max as usize).min(MAX_CALLBACK_FRAMES),
+    cpal::SupportedBufferSize::Unknown => MAX_CALLBACK_FRAMES,
+  };
+  let scratch_len = max_callback_frames * output_channels as usize;
+  let stream = match sample_format {
+    SampleFormat::F32 => {
+      device.build_output_stream(&final_config, render, output_stream_error, None)
+    }
+    SampleFormat::I16 => {
+      build_converting_output_stream::<i16>(device, &final_config, scratch_len, render)
+    }
+    SampleFormat::I32 => {
+      build_converting_output_stream::<i32>(device, &final_config, scratch_len, render)
+    }
+    SampleFormat::U16 => {
+      build_converting_output_stream::<u16>(device, &final_config, scratch_len, render)
+    }
+    other => {
+      return Err(Error::from_reason(format!(
+        "Device '{}' uses unsupported sample format {}",
+        device_name, other
+      )))
+    }
+  }
+  .map_err(|e| Error::from_reason(format!("Failed to build audio stream: {e}")))?;
 
   stream
     .play()
@@ -1125,10 +4574,47 @@ fn build_output_stream(
   Ok(stream)
 }
 
+/// Build an output stream for a device with an integer sample format
+/// `render` fills an f32 scratch buffer of `scratch_len` samples, allocated here, that is
+/// then converted into the device's samples; a larger callback is rendered in pieces
+fn build_converting_output_stream<T>(
+  device: &cpal::Device,
+  config: &cpal::StreamConfig,
+  scratch_len: usize,
+  mut render: impl FnMut(&mut [f32], &cpal::OutputCallbackInfo) + Send + 'static,
+) -> std::result::Result<cpal::Stream, cpal::BuildStreamError>
+where
+  T: SizedSample + FromSample<f32>,
+{
+  let channels = config.channels.max(1) as usize;
+  // Whole frames, so a piece never splits one
+  let mut scratch = vec![0.0f32; (scratch_len / channels).max(1) * channels];
+  device.build_output_stream(
+    config,
+    move |data: &mut [T], info: &cpal::OutputCallbackInfo| {
+      for piece in data.chunks_mut(scratch.len()) {
+        let scratch = &mut scratch[..piece.len()];
+        render(scratch, info);
+        for (out, &sample) in piece.iter_mut().zip(scratch.iter()) {
+          *out = T::from_sample(sample);
+        }
+      }
+    },
+    output_stream_error,
+    None,
+  )
+}
+
+fn output_stream_error(err: cpal::StreamError) {
+  eprintln!("[AudioEngine] Output stream error: {err}");
+}
+
 /// Build an audio input stream for microphone using the same device as output
+/// The input also goes to `monitor`, the mic monitor's ring, ahead of the state lock
 fn build_input_stream(
   device: &cpal::Device,
   state: Arc<Mutex<EngineState>>,
+  mut monitor: Producer<f32>,
 ) -> Option<cpal::Stream> {
   let input_config = match device.default_input_config() {
     Ok(config) => config,
@@ -1145,35 +4631,54 @@ fn build_input_stream(
 
   let input_sample_rate = input_config.sample_rate().0;
   let input_channels = input_config.channels();
+  {
+    let mut state = state.lock();
+    state.microphone.stereo = input_channels >= 2;
+    state
+      .mic_monitor
+      .input_sample_rate
+      .store(input_sample_rate, Ordering::Relaxed);
+  }
 
   let state_for_input = Arc::clone(&state);
 
   match device.build_input_stream(
     &input_config.into(),
     move |data: &[f32], _| {
+      // Stereo inputs keep their first two channels; a mono mic is duplicated to stereo
+      let ch = input_channels as usize;
+
+      // Monitor ring first, without waiting on the lock; whole frames only, dropped when full
+      for frame in data.chunks_exact(ch) {
+        if monitor.slots() < 2 {
+          break;
+        }
+        let _ = monitor.push(frame[0]);
+        let _ = monitor.push(if ch >= 2 { frame[1] } else { frame[0] });
+      }
+
       let mut state = state_for_input.lock();
 
       // Always buffer and track peak level (regardless of enabled state)
-      // Use first channel only (mono mic) and duplicate to stereo
-      let ch = input_channels as usize;
-      let frames = data.len() / ch;
 
-      for frame in 0..frames {
-        let sample = data[frame * ch]; // First channel only
-        state.microphone.input_buffer.push_back(sample);
-        state.microphone.input_buffer.push_back(sample); // Duplicate to stereo
+      for frame in data.chunks_exact(ch) {
+        let left = frame[0];
+        let right = if ch >= 2 { frame[1] } else { left };
+        state.microphone.input_buffer.push_back(left);
+        state.microphone.input_buffer.push_back(right);
       }
 
-      // Limit buffer size (keep ~100ms of audio at stereo)
-      let max_samples = (input_sample_rate as usize / 10) * 2;
+      // Limit buffer size to the configured mic latency (stereo)
+      let max_frames = input_sample_rate as f32 * state.microphone.latency_ms / 1000.0;
+      let max_samples = max_frames as usize * 2;
       while state.microphone.input_buffer.len() > max_samples {
         state.microphone.input_buffer.pop_front();
       }
 
-      // Update peak level (first channel only)
+      // Update peak level (the channels that are used)
       let mut peak = 0.0f32;
-      for frame in 0..frames {
-        peak = peak.max(data[frame * ch].abs());
+      for frame in data.chunks_exact(ch) {
+        peak = frame.iter().take(2).fold(peak, |peak, s| peak.max(s.abs()));
       }
       state.microphone.peak = state.microphone.peak * 0.9 + peak * 0.1;
     },
@@ -1198,10 +4703,102 @@ fn build_input_stream(
   }
 }
 
+/// Validate a sampler slot index
+fn sampler_slot(slot: u32) -> Result<usize> {
+  let slot = slot as usize;
+  if slot < SAMPLER_SLOTS {
+    Ok(slot)
+  } else {
+    Err(Error::from_reason(format!("Invalid sampler slot: {slot}")))
+  }
+}
+
+/// Load a track onto a deck (the body of load_track())
+/// The PCM is copied before the lock is taken and the replaced buffer is freed after it
+/// is released, so the audio path only ever waits for install_track()
+fn load_deck_track(
+  state: &Mutex<EngineState>,
+  deck: u32,
+  pcm_data: &[f32],
+  bpm: Option<f32>,
+  track_id: Option<String>,
+) {
+  let pcm = shared_pcm(state, deck, track_id.as_deref(), pcm_data)
+    .unwrap_or_else(|| Arc::new(pcm_data.to_vec()));
+  let headroom_db = headroom_db(simd::peak_abs(&pcm));
+
+  let previous_pcm = {
+    let mut state = state.lock();
+    let previous = install_track(&mut state, deck, pcm, bpm, track_id);
+    state.deck_mut(deck).headroom_db = Some(headroom_db);
+    previous
+  };
+
+  // Free the previous track's buffer outside the lock as well
+  drop(previous_pcm);
+}
+
+/// The other deck's PCM if it holds the same track (trackId and length), to share instead
+/// of copying; comparing every sample would cost as much as the copy
+fn shared_pcm(
+  state: &Mutex<EngineState>,
+  deck: u32,
+  track_id: Option<&str>,
+  pcm: &[f32],
+) -> Option<Arc<Vec<f32>>> {
+  let state = state.lock();
+  let other = state.deck(if deck == 1 { 2 } else { 1 });
+  if track_id.is_none() || other.track_id.as_deref() != track_id {
+    return None;
+  }
+  other
+    .pcm_data
+    .clone()
+    .filter(|other| other.len() == pcm.len())
+}
+
+/// Swap a prepared PCM buffer onto a deck, returning the previous buffer
+/// Callers hold the state lock only for this swap; allocation and deallocation happen outside
+fn install_track(
+  state: &mut EngineState,
+  deck: u32,
+  pcm: Arc<Vec<f32>>,
+  bpm: Option<f32>,
+  track_id: Option<String>,
+) -> Option<Arc<Vec<f32>>> {
+  let master_tempo = state.master_tempo;
+  let deck_state = if deck == 1 {
+    &mut state.deck_a
+  } else {
+    &mut state.deck_b
+  };
+
+  let previous = deck_state.pcm_data.replace(pcm);
+  deck_state.reset_for_track(0);
+  deck_state.playing = false;
+  deck_state.bpm = bpm;
+  deck_state.beat_grid.clear();
+  // The old anchor would skew the master clock if this deck is the sync master
+  deck_state.first_beat = 0.0;
+  deck_state.sync_correction = 0.0;
+  deck_state.update_rate(master_tempo);
+  deck_state.track_id = track_id;
+
+  state.update_reason = Some("load".to_string());
+
+  previous
+}
+
+/// Headroom below full scale in dB for a sample peak (floored at -120 dBFS)
+fn headroom_db(peak: f32) -> f32 {
+  -20.0 * peak.max(1e-6).log10()
+}
+
 /// Calculate playback rate based on track BPM and master tempo
+/// Unclamped; the deck's pitch range limits the final rate
 fn calculate_playback_rate(track_bpm: Option<f32>, master_tempo: f32) -> f32 {
   match track_bpm {
-    Some(bpm) if bpm > 0.0 => (master_tempo / bpm).clamp(0.5, 2.0),
+    Some(bpm) if bpm > 0.0 => master_tempo / bpm,
     _ => 1.0,
   }
 }
@@ -1211,106 +4808,107 @@ fn process_audio_chunk(
   state: &mut EngineState,
   sample_rate: u32,
   output_channels: u16,
-) -> (Vec<f32>, AudioEngineStateUpdate) {
-  let frames = FRAMES_PER_CHUNK;
+) -> (Vec<f32>, Vec<f32>, AudioEngineStateUpdate) {
+  let frames = state.latency_profile.frames_per_chunk;
   let channels = DEFAULT_CHANNELS as usize;
+  state.publish_mic_monitor();
+
+  // Global pause (or panic): output silence without advancing decks or crossfade
+  if state.paused || state.panicked {
+    state.levels.deck_a_peak = 0.0;
+    state.levels.deck_b_peak = 0.0;
+    state.levels.cue_left_peak = 0.0;
+    state.levels.cue_right_peak = 0.0;
+    state.update_peak_hold();
+    // The output is silent, so a deck still playing afterwards ramps back in
+    state.deck_a.was_playing = false;
+    state.deck_b.was_playing = false;
+
+    let state_update = create_state_update(state, sample_rate);
+    state.update_reason = None;
+    return (
+      vec![0.0; frames * output_channels as usize],
+      vec![0.0; frames * channels],
+      state_update,
+    );
+  }
 
   // Pre-allocate buffers
   let mut buffer_a = vec![0.0f32; frames * channels];
   let mut buffer_b = vec![0.0f32; frames * channels];
   let mut mix_buffer = vec![0.0f32; frames * channels];
 
-  // Process deck A with time stretching
-  if state.deck_a.playing {
-    if let Some(ref pcm) = state.deck_a.pcm_data {
-      let total_frames = pcm.len() / channels;
-      let rate = state.deck_a.rate;
-
-      // Use time stretcher for tempo adjustment with pitch preservation
-      let frames_consumed = state.deck_a.time_stretcher.process(
-        pcm,
-        state.deck_a.position,
-        rate,
-        frames,
-        &mut buffer_a,
-      );
-
-      // Apply EQ processing
-      state.deck_a.eq_processor.process(&mut buffer_a, frames);
-
-      state.deck_a.position += frames_consumed;
-
-      // Check for loop or track end
-      if state.deck_a.loop_enabled && state.deck_a.position >= state.deck_a.loop_end {
-        // Loop back to start
-        state.deck_a.position = state.deck_a.loop_start;
-        state.deck_a.time_stretcher.clear();
-      } else if state.deck_a.position >= total_frames {
-        state.deck_a.playing = false;
-        state.deck_a.position = 0;
-        state.deck_a.time_stretcher.clear();
-      }
+  // Scheduled actions landing in this chunk, applied at their exact frame
+  let due = state.take_due_actions(frames);
+  for &(offset, action) in &due {
+    if let ScheduledKind::Crossfade {
+      target_position,
+      total_frames,
+    } = action.kind
+    {
+      state.begin_crossfade(target_position, total_frames, offset);
+      state.update_reason = Some("scheduled".to_string());
     }
   }
 
-  // Process deck B with time stretching
-  if state.deck_b.playing {
-    if let Some(ref pcm) = state.deck_b.pcm_data {
-      let total_frames = pcm.len() / channels;
-      let rate = state.deck_b.rate;
-
-      // Use time stretcher for tempo adjustment with pitch preservation
-      let frames_consumed = state.deck_b.time_stretcher.process(
-        pcm,
-        state.deck_b.position,
-        rate,
-        frames,
-        &mut buffer_b,
-      );
-
-      // Apply EQ processing
-      state.deck_b.eq_processor.process(&mut buffer_b, frames);
+  // Filter fade moves the outgoing deck's high-pass ahead of rendering
+  state.update_filter_fade(frames);
 
-      state.deck_b.position += frames_consumed;
+  // Render each deck (time stretching or scratching, trim, EQ)
+  let coeff = smoothing_coefficient(state.smoothing_time, sample_rate);
+  state.render_deck(1, frames, &mut buffer_a, &due, coeff);
+  state.render_deck(2, frames, &mut buffer_b, &due, coeff);
+  state.collect_deck_events();
+  state.frame_clock += frames as u64;
 
-      // Check for loop or track end
-      if state.deck_b.loop_enabled && state.deck_b.position >= state.deck_b.loop_end {
-        // Loop back to start
-        state.deck_b.position = state.deck_b.loop_start;
-        state.deck_b.time_stretcher.clear();
-      } else if state.deck_b.position >= total_frames {
-        state.deck_b.playing = false;
-        state.deck_b.position = 0;
-        state.deck_b.time_stretcher.clear();
-      }
-    }
-  }
+  // Advance the master clock and steer synced decks toward it
+  update_sync(state, frames, sample_rate);
 
   // Handle auto crossfade
+  // The crossfader is advanced per frame inside the chunk so that short fades
+  // are not quantized to chunk-sized steps.
+  let xf_start = state.crossfade.position;
+  let mut xf_step = 0.0f32;
+  let mut xf_delay = 0;
+  let mut xf_ramp_frames = 0;
+
   if state.crossfade.active && state.crossfade.remaining_frames > 0 {
-    state.crossfade.remaining_frames = state.crossfade.remaining_frames.saturating_sub(frames);
+    xf_delay = state.crossfade.delay_frames.min(frames);
+    state.crossfade.delay_frames -= xf_delay;
+    xf_ramp_frames = state.crossfade.remaining_frames.min(frames - xf_delay);
+    xf_step = (state.crossfade.target_position - state.crossfade.start_position)
+      / state.crossfade.total_frames as f32;
+    state.crossfade.remaining_frames -= xf_ramp_frames;
 
     if state.crossfade.remaining_frames == 0 {
       // Crossfade complete
       state.crossfade.position = state.crossfade.target_position;
 
       if let Some(dir) = state.crossfade.direction {
-        match dir {
+        let stop_outgoing = state.crossfade.stop_on_complete;
+        let target_deck = match dir {
           CrossfadeDirection::AtoB => {
-            state.deck_a.playing = false;
+            if stop_outgoing {
+              state.deck_a.playing = false;
+            }
             state.deck_b.playing = true;
+            2
           }
           CrossfadeDirection::BtoA => {
-            state.deck_b.playing = false;
+            if stop_outgoing {
+              state.deck_b.playing = false;
+            }
             state.deck_a.playing = true;
+            1
           }
-        }
+        };
+        state.emit_event("crossfade_finished", target_deck);
       }
 
       state.crossfade.active = false;
       state.crossfade.direction = None;
     } else {
-      // Update crossfader position during crossfade
+      // Update crossfader position to the end of this chunk
       let progress =
         1.0 - (state.crossfade.remaining_frames as f32 / state.crossfade.total_frames as f32);
       state.crossfade.position = state.crossfade.start_position
@@ -1330,37 +4928,146 @@ fn process_audio_chunk(
       }
     }
   }
+  let xf_end = state.crossfade.position;
 
-  // Apply crossfader with Pioneer-style constant power curve
-  let position = state.crossfade.position;
-  let gain_a = if state.deck_a.playing {
-    (position * PI / 2.0).cos()
-  } else {
-    0.0
-  };
-  let gain_b = if state.deck_b.playing {
-    (position * PI / 2.0).sin()
-  } else {
-    0.0
-  };
-
-  let deck_a_gain = gain_a * state.deck_a.gain;
-  let deck_b_gain = gain_b * state.deck_b.gain;
+  // A scratched deck is audible even while stopped
+  let deck_a_active = state.deck_a.playing || state.deck_a.scratch_position.is_some();
+  let deck_b_active = state.deck_b.playing || state.deck_b.scratch_position.is_some();
 
   // Calculate peak levels (post deck-gain, pre-crossfade)
   state.levels.deck_a_peak = calculate_peak(&buffer_a, frames) * state.deck_a.gain;
   state.levels.deck_b_peak = calculate_peak(&buffer_b, frames) * state.deck_b.gain;
 
+  // Rolling RMS for level matching, only while the deck makes sound
+  let rms_coeff = 1.0 - (-(frames as f32) / (RMS_WINDOW_SECONDS * sample_rate as f32)).exp();
+  if deck_a_active {
+    update_mean_square(&mut state.levels.deck_a_mean_square, &buffer_a, rms_coeff);
+  }
+  if deck_b_active {
+    update_mean_square(&mut state.levels.deck_b_mean_square, &buffer_b, rms_coeff);
+  }
+
   // Update peak hold
-  update_peak_hold(&mut state.levels);
+  state.update_peak_hold();
+  state.levels.deck_a_clipped |= state.levels.deck_a_peak >= 1.0;
+  state.levels.deck_b_clipped |= state.levels.deck_b_peak >= 1.0;
+
+  // Mix decks, ramping the crossfader and deck gains per sample
+  let (pan_a_left, pan_a_right) = pan_gains(state.deck_a.pan);
+  let (pan_b_left, pan_b_right) = pan_gains(state.deck_b.pan);
+  let (side_a, side_b) = (state.deck_a.crossfader_side, state.deck_b.crossfader_side);
+  // Mute/solo scale the fader target, so they ramp with the gain smoother
+  let any_soloed = state.deck_a.soloed || state.deck_b.soloed;
+  let master_gain_a = master_enable(&state.deck_a, any_soloed) * state.deck_a.gain;
+  let master_gain_b = master_enable(&state.deck_b, any_soloed) * state.deck_b.gain;
+  let mut gains_a = std::mem::take(&mut state.mix_gains_a);
+  let mut gains_b = std::mem::take(&mut state.mix_gains_b);
+  for frame in 0..frames {
+    let target = if frame < xf_delay {
+      xf_start
+    } else if frame < xf_delay + xf_ramp_frames {
+      xf_start + xf_step * (frame - xf_delay + 1) as f32
+    } else {
+      xf_end
+    };
+    let position = state.crossfade.smoother.next(target, coeff);
+    let position = crossfader_deadzone(position, state.crossfade.deadzone);
+    let (gain_a, gain_b) = crossfader_gains(position);
+    let fader_a = state.deck_a.gain_smoother.next(master_gain_a, coeff);
+    let fader_b = state.deck_b.gain_smoother.next(master_gain_b, coeff);
+    let deck_a_gain = if deck_a_active {
+      side_a.gain(gain_a, gain_b) * fader_a
+    } else {
+      0.0
+    };
+    let deck_b_gain = if deck_b_active {
+      side_b.gain(gain_a, gain_b) * fader_b
+    } else {
+      0.0
+    };
 
-  // Mix decks
-  for i in 0..(frames * channels) {
-    mix_buffer[i] = buffer_a[i] * deck_a_gain + buffer_b[i] * deck_b_gain;
+    let base = frame * channels;
+    gains_a[base] = deck_a_gain * pan_a_left;
+    gains_a[base + 1] = deck_a_gain * pan_a_right;
+    gains_b[base] = deck_b_gain * pan_b_left;
+    gains_b[base + 1] = deck_b_gain * pan_b_right;
   }
+  let samples = frames * channels;
+  simd::mix_two(
+    &mut mix_buffer,
+    &buffer_a,
+    &gains_a[..samples],
+    &buffer_b,
+    &gains_b[..samples],
+  );
+  (state.mix_gains_a, state.mix_gains_b) = (gains_a, gains_b);
+
+  // External input joins the decks (and is ducked by talkover with them)
+  mix_external_input(&mut state.external_input, &mut mix_buffer, frames, coeff);
+
+  // Mix sampler voices into the master (ducked by talkover like the decks)
+  state.sampler.mix_into(&mut mix_buffer, frames);
 
   // Apply microphone input and talkover
-  apply_mic_talkover(state, &mut mix_buffer, frames);
+  apply_mic_talkover(state, &mut mix_buffer, frames, coeff, sample_rate);
+
+  // Master trim, ramped like the faders
+  let master_gain = 10.0f32.powf(state.master_gain_db / 20.0);
+  for frame in mix_buffer.chunks_exact_mut(channels) {
+    let gain = state.master_gain_smoother.next(master_gain, coeff);
+    frame[0] *= gain;
+    frame[1] *= gain;
+  }
+
+  // Master tone control ahead of the soft clip
+  state.master_eq.process(&mut mix_buffer, frames);
+
+  // Soft-clip the master before the final hard clamp
+  if state.soft_clip_enabled {
+    let drive = state.soft_clip_drive;
+    for sample in mix_buffer.iter_mut() {
+      *sample = soft_clip(*sample, drive);
+    }
+  }
+
+  // Stereo width: scale the side signal (width 0 is the same mono sum as below)
+  if state.stereo_width != 1.0 {
+    let width = state.stereo_width;
+    for frame in mix_buffer.chunks_exact_mut(channels) {
+      let mid = (frame[0] + frame[1]) * 0.5;
+      let side = (frame[0] - frame[1]) * 0.5 * width;
+      frame[0] = mid + side;
+      frame[1] = mid - side;
+    }
+  }
+
+  // Mono-sum the master so both main outputs carry the identical signal
+  if state.mono_output {
+    for frame in mix_buffer.chunks_exact_mut(channels) {
+      let mono = (frame[0] + frame[1]) * 0.5;
+      frame[0] = mono;
+      frame[1] = mono;
+    }
+  }
+
+  // Master limiter after all master processing, ahead of the clip check and clamp
+  state.master_gain_reduction_db = 0.0;
+  if state.master_limiter_enabled {
+    for frame in mix_buffer.chunks_exact_mut(channels) {
+      (frame[0], frame[1]) = state.master_limiter.process_frame(frame[0], frame[1]);
+    }
+    state.master_gain_reduction_db = state.master_limiter.take_gain_reduction_db();
+  }
+
+  // Correlation meter, smoothed once per chunk (silent chunks hold the previous reading)
+  if let Some(correlation) = calculate_correlation(&mix_buffer, frames) {
+    let chunk_time = CORRELATION_SMOOTHING_TIME / frames as f32;
+    let chunk_coeff = smoothing_coefficient(chunk_time, sample_rate);
+    state.master_correlation += (correlation - state.master_correlation) * chunk_coeff;
+  }
+
+  // Master clip indicator, checked before the output clamp
+  state.levels.master_clipped |= simd::peak_abs(&mix_buffer) >= 1.0;
 
   // Map to output channels
   // Always use map_channels if cue is enabled or channel mapping is non-default
@@ -1370,58 +5077,195 @@ fn process_audio_chunk(
     || state.channel_config.cue_channels[0].is_some()
     || state.channel_config.cue_channels[1].is_some();
 
+  // Sum the PFL cue bus (headphones and cue recording)
+  let cue_buffer = mix_cue_bus(
+    &state.channel_config,
+    &mut state.cue_bus,
+    &buffer_a,
+    &buffer_b,
+    frames,
+  );
+  (state.levels.cue_left_peak, state.levels.cue_right_peak) = cue_buffer
+    .as_deref()
+    .map(stereo_peaks)
+    .unwrap_or((0.0, 0.0));
+
   let output = if needs_channel_mapping {
     map_channels(
       &mix_buffer,
       frames,
       output_channels,
       &state.channel_config,
-      &buffer_a,
-      &buffer_b,
+      cue_buffer.as_deref(),
     )
   } else {
     // Clip output
     mix_buffer.iter().map(|s| s.clamp(-1.0, 1.0)).collect()
   };
 
+  let recording = match state.recording_target {
+    RecordingTarget::Master => mix_buffer.iter().map(|s| s.clamp(-1.0, 1.0)).collect(),
+    RecordingTarget::Cue => cue_buffer.unwrap_or_else(|| vec![0.0; frames * channels]),
+  };
+
   let state_update = create_state_update(state, sample_rate);
 
   // Reset pending reason after creating state update
   state.update_reason = None;
 
-  (output, state_update)
+  (output, recording, state_update)
+}
+
+/// Peak per bin over `width_seconds` of stereo PCM centered on `center_seconds`
+/// `bins` is capped at MAX_WAVEFORM_BINS
+fn waveform_window(
+  pcm: &[f32],
+  sample_rate: u32,
+  center_seconds: f64,
+  width_seconds: f64,
+  bins: usize,
+) -> Vec<f64> {
+  let channels = DEFAULT_CHANNELS as usize;
+  let total_frames = (pcm.len() / channels) as i64;
+  let bins = bins.min(MAX_WAVEFORM_BINS);
+  let mut peaks = vec![0.0f64; bins];
+  if bins == 0 || !(width_seconds > 0.0 && width_seconds.is_finite() && center_seconds.is_finite())
+  {
+    return peaks;
+  }
+
+  let start_frame = ((center_seconds - width_seconds / 2.0) * sample_rate as f64).floor() as i64;
+  let frames_per_bin = width_seconds * sample_rate as f64 / bins as f64;
+
+  for (bin, peak) in peaks.iter_mut().enumerate() {
+    let bin_start = start_frame + (bin as f64 * frames_per_bin) as i64;
+    let bin_end = start_frame + ((bin + 1) as f64 * frames_per_bin) as i64;
+    // Zero-pad outside the track
+    let from = bin_start.clamp(0, total_frames) as usize;
+    let to = bin_end.max(bin_start + 1).clamp(0, total_frames) as usize;
+    if from < to {
+      *peak = simd::peak_abs(&pcm[from * channels..to * channels]) as f64;
+    }
+  }
+
+  peaks
+}
+
+/// Advance the master clock and compute sync lock rate corrections
+/// Synced decks get a small proportional rate nudge toward the clock phase every chunk,
+/// so they stay locked over long mixes instead of drifting after a one-shot align
+fn update_sync(state: &mut EngineState, frames: usize, sample_rate: u32) {
+  state.clock_phase += frames as f64 / sample_rate as f64 * state.master_tempo as f64 / 60.0;
+
+  // A playing master deck drives the clock directly
+  if let Some(master) = state.sync_master {
+    let deck_state = state.deck(master);
+    if deck_state.playing {
+      if let Some(phase) = deck_state.beat_phase(sample_rate) {
+        state.clock_phase = phase;
+      }
+    }
+  }
+
+  let clock_phase = state.clock_phase;
+  let sync_master = state.sync_master;
+  for (deck, deck_state) in [(1, &mut state.deck_a), (2, &mut state.deck_b)] {
+    let following = deck_state.sync_enabled && deck_state.playing && sync_master != Some(deck);
+    deck_state.sync_correction = match deck_state.beat_phase(sample_rate) {
+      Some(phase) if following => {
+        // Wrap to the nearest beat: error in [-0.5, 0.5)
+        let error = (clock_phase - phase + 0.5).rem_euclid(1.0) - 0.5;
+        (error as f32 * SYNC_CORRECTION_GAIN).clamp(-SYNC_MAX_CORRECTION, SYNC_MAX_CORRECTION)
+      }
+      _ => 0.0,
+    };
+  }
+}
+
+/// Pan gains for (left, right) using a constant power law normalized to unity at center
+/// Hard-panned sides get +3 dB so perceived loudness stays constant across the sweep
+fn pan_gains(pan: f32) -> (f32, f32) {
+  if pan == 0.0 {
+    return (1.0, 1.0);
+  }
+  let angle = (pan + 1.0) * PI / 4.0;
+  (angle.cos() * SQRT_2, angle.sin() * SQRT_2)
+}
+
+/// Crossfader gains for (deck A, deck B) using a Pioneer-style constant power curve
+fn crossfader_gains(position: f32) -> (f32, f32) {
+  ((position * PI / 2.0).cos(), (position * PI / 2.0).sin())
+}
+
+/// Hold the crossfader at center within `deadzone` of 0.5 and stretch the rest of the
+/// travel to still reach 0 and 1, so leaving the dead zone doesn't jump
+fn crossfader_deadzone(position: f32, deadzone: f32) -> f32 {
+  let offset = position - 0.5;
+  if offset.abs() <= deadzone {
+    return 0.5;
+  }
+  0.5 + offset.signum() * (offset.abs() - deadzone) / (1.0 - 2.0 * deadzone)
+}
+
+/// Soft saturation curve (tanh), continuous and bounded to (-1, 1)
+#[inline]
+fn soft_clip(sample: f32, drive: f32) -> f32 {
+  (sample * drive).tanh()
 }
 
 /// Calculate peak level from buffer
 fn calculate_peak(buffer: &[f32], frames: usize) -> f32 {
   let channels = DEFAULT_CHANNELS as usize;
   let available = frames.min(buffer.len() / channels);
-  let mut peak = 0.0f32;
+  simd::peak_abs(&buffer[..available * channels])
+}
 
-  for i in 0..available {
-    for ch in 0..channels {
-      peak = peak.max(buffer[i * channels + ch].abs());
-    }
+/// Normalized cross-correlation of left and right (None when silent)
+fn calculate_correlation(buffer: &[f32], frames: usize) -> Option<f32> {
+  let (mut lr, mut ll, mut rr) = (0.0f32, 0.0f32, 0.0f32);
+  for frame in buffer.chunks_exact(DEFAULT_CHANNELS as usize).take(frames) {
+    lr += frame[0] * frame[1];
+    ll += frame[0] * frame[0];
+    rr += frame[1] * frame[1];
+  }
+  let energy = (ll * rr).sqrt();
+  if energy < 1e-9 {
+    return None;
   }
+  Some((lr / energy).clamp(-1.0, 1.0))
+}
 
-  peak
+/// Per-channel absolute peaks of a stereo interleaved buffer
+fn stereo_peaks(buffer: &[f32]) -> (f32, f32) {
+  buffer
+    .chunks_exact(DEFAULT_CHANNELS as usize)
+    .fold((0.0f32, 0.0f32), |(left, right), frame| {
+      (left.max(frame[0].abs()), right.max(frame[1].abs()))
+    })
 }
 
-/// Update peak hold values
-fn update_peak_hold(levels: &mut LevelMeterState) {
-  const HOLD_DURATION: Duration = Duration::from_millis(1500);
-  const DECAY_RATE: f32 = 6.0; // dB per second
+/// Fold one chunk's mean square into a rolling (exponential) mean square
+fn update_mean_square(mean_square: &mut f32, buffer: &[f32], coeff: f32) {
+  if buffer.is_empty() {
+    return;
+  }
+  let chunk = buffer.iter().map(|s| s * s).sum::<f32>() / buffer.len() as f32;
+  *mean_square += (chunk - *mean_square) * coeff;
+}
 
-  let now = Instant::now();
+/// Update peak hold values
+fn update_peak_hold(levels: &mut LevelMeterState, now: Instant) {
+  let hold_duration = levels.hold_duration;
+  let decay_rate = levels.decay_db_per_sec;
 
   // Deck A
   if levels.deck_a_peak > levels.deck_a_peak_hold {
     levels.deck_a_peak_hold = levels.deck_a_peak;
     levels.deck_a_peak_hold_time = now;
-  } else if now.duration_since(levels.deck_a_peak_hold_time) > HOLD_DURATION {
+  } else if now.duration_since(levels.deck_a_peak_hold_time) > hold_duration {
     let decay_time =
-      (now.duration_since(levels.deck_a_peak_hold_time) - HOLD_DURATION).as_secs_f32();
-    let decay_db = DECAY_RATE * decay_time;
+      (now.duration_since(levels.deck_a_peak_hold_time) - hold_duration).as_secs_f32();
+    let decay_db = decay_rate * decay_time;
     let current_db = if levels.deck_a_peak_hold > 0.0 {
       20.0 * levels.deck_a_peak_hold.log10()
     } else {
@@ -1439,10 +5283,10 @@ fn update_peak_hold(levels: &mut LevelMeterState) {
   if levels.deck_b_peak > levels.deck_b_peak_hold {
     levels.deck_b_peak_hold = levels.deck_b_peak;
     levels.deck_b_peak_hold_time = now;
-  } else if now.duration_since(levels.deck_b_peak_hold_time) > HOLD_DURATION {
+  } else if now.duration_since(levels.deck_b_peak_hold_time) > hold_duration {
     let decay_time =
-      (now.duration_since(levels.deck_b_peak_hold_time) - HOLD_DURATION).as_secs_f32();
-    let decay_db = DECAY_RATE * decay_time;
+      (now.duration_since(levels.deck_b_peak_hold_time) - hold_duration).as_secs_f32();
+    let decay_db = decay_rate * decay_time;
     let current_db = if levels.deck_b_peak_hold > 0.0 {
       20.0 * levels.deck_b_peak_hold.log10()
     } else {
@@ -1457,8 +5301,54 @@ fn update_peak_hold(levels: &mut LevelMeterState) {
   }
 }
 
+/// Play buffered external input through its EQ and fader into the mix
+fn mix_external_input(
+  input: &mut ExternalInputState,
+  mix_buffer: &mut [f32],
+  frames: usize,
+  coeff: f32,
+) {
+  let channels = DEFAULT_CHANNELS as usize;
+  let available = input.buffer.len().min(frames * channels);
+  if available == 0 {
+    input.peak = 0.0;
+    return;
+  }
+
+  // Short reads are padded with silence
+  let buffer = &mut input.scratch[..frames * channels];
+  buffer.fill(0.0);
+  for (sample, pushed) in buffer.iter_mut().zip(input.buffer.drain(..available)) {
+    *sample = pushed;
+  }
+  input.eq_processor.process(buffer, frames);
+
+  // Squared fader curve, as for the decks
+  let target = input.gain * input.gain;
+  let mut peak = 0.0f32;
+  for (frame, mix) in buffer
+    .chunks_exact(channels)
+    .zip(mix_buffer.chunks_exact_mut(channels))
+  {
+    let gain = input.gain_smoother.next(target, coeff);
+    for (out, &sample) in mix.iter_mut().zip(frame) {
+      let sample = sample * gain;
+      peak = peak.max(sample.abs());
+      *out += sample;
+    }
+  }
+  input.peak = peak;
+}
+
 /// Apply microphone input and talkover to mixed audio
-fn apply_mic_talkover(state: &mut EngineState, mix_buffer: &mut [f32], frames: usize) {
+/// The music ducks with the talkover attack time and recovers with the release time
+fn apply_mic_talkover(
+  state: &mut EngineState,
+  mix_buffer: &mut [f32],
+  frames: usize,
+  coeff: f32,
+  sample_rate: u32,
+) {
   let channels = DEFAULT_CHANNELS as usize;
   let mic = &mut state.microphone;
 
@@ -1471,14 +5361,17 @@ fn apply_mic_talkover(state: &mut EngineState, mix_buffer: &mut [f32], frames: u
     return;
   }
 
-  // Calculate music attenuation and mic gain only when enabled
-  let (music_attenuation, mic_gain) = if mic.enabled {
+  // Target music attenuation and mic gain (only when enabled)
+  let (attenuation_target, gain_target) = if mic.enabled {
     (1.0 - mic.talkover_ducking, mic.gain)
   } else {
     (1.0, 0.0) // No ducking, no mic output when disabled
   };
 
-  let mut peak = 0.0f32;
+  let attack_coeff = smoothing_coefficient(mic.talkover_attack, sample_rate);
+  let release_coeff = smoothing_coefficient(mic.talkover_release, sample_rate);
+  let (pan_left, pan_right) = pan_gains(mic.pan);
+  let (mut peak_left, mut peak_right) = (0.0f32, 0.0f32);
 
   for i in 0..frames {
     let base = i * channels;
@@ -1492,17 +5385,29 @@ fn apply_mic_talkover(state: &mut EngineState, mix_buffer: &mut [f32], frames: u
     };
 
     // Track peak level (always, regardless of enabled state)
-    peak = peak.max(mic_left.abs()).max(mic_right.abs());
+    peak_left = peak_left.max(mic_left.abs());
+    peak_right = peak_right.max(mic_right.abs());
+
+    let ducking_coeff = if attenuation_target < mic.ducking_smoother.current {
+      attack_coeff
+    } else {
+      release_coeff
+    };
+    let music_attenuation = mic.ducking_smoother.next(attenuation_target, ducking_coeff);
+    let mic_gain = mic.gain_smoother.next(gain_target, coeff);
 
-    // Apply talkover: attenuate music and add mic (only when enabled)
-    mix_buffer[base] = mix_buffer[base] * music_attenuation + mic_left * mic_gain;
+    // Apply talkover: attenuate music and add the panned mic (only when enabled)
+    mix_buffer[base] = mix_buffer[base] * music_attenuation + mic_left * mic_gain * pan_left;
     if channels > 1 {
-      mix_buffer[base + 1] = mix_buffer[base + 1] * music_attenuation + mic_right * mic_gain;
+      mix_buffer[base + 1] =
+        mix_buffer[base + 1] * music_attenuation + mic_right * mic_gain * pan_right;
     }
   }
 
-  // Update peak level
-  mic.peak = peak;
+  // Update peak levels
+  mic.peak_left = peak_left;
+  mic.peak_right = peak_right;
+  mic.peak = peak_left.max(peak_right);
 }
 
 /// Map stereo mix to output channels with main/cue routing
@@ -1511,8 +5416,7 @@ fn map_channels(
   frames: usize,
   output_channels: u16,
   config: &ChannelConfig,
-  buffer_a: &[f32],
-  buffer_b: &[f32],
+  cue: Option<&[f32]>,
 ) -> Vec<f32> {
   let channels = DEFAULT_CHANNELS as usize;
   let out_ch = output_channels as usize;
@@ -1540,44 +5444,18 @@ fn map_channels(
     }
 
     // Cue outputs
-    let cue_enabled = config.deck_a_cue || config.deck_b_cue;
-    if cue_enabled && (cue_l.is_some() || cue_r.is_some()) {
-      let mut cue_left = 0.0;
-      let mut cue_right = 0.0;
-      let mut cue_sources = 0;
-
-      if config.deck_a_cue {
-        cue_left += buffer_a[mix_base];
-        cue_right += buffer_a
-          .get(mix_base + 1)
-          .copied()
-          .unwrap_or(buffer_a[mix_base]);
-        cue_sources += 1;
-      }
-
-      if config.deck_b_cue {
-        cue_left += buffer_b[mix_base];
-        cue_right += buffer_b
-          .get(mix_base + 1)
-          .copied()
-          .unwrap_or(buffer_b[mix_base]);
-        cue_sources += 1;
-      }
-
-      if cue_sources > 0 {
-        let norm = 1.0 / cue_sources as f32;
-        cue_left = (cue_left * norm).clamp(-1.0, 1.0);
-        cue_right = (cue_right * norm).clamp(-1.0, 1.0);
-        let mono_cue = (cue_left + cue_right) * 0.5;
-
-        if let (Some(l), Some(r)) = (cue_l, cue_r) {
-          output[out_base + l as usize] = cue_left;
-          output[out_base + r as usize] = cue_right;
-        } else if let Some(l) = cue_l {
-          output[out_base + l as usize] = mono_cue;
-        } else if let Some(r) = cue_r {
-          output[out_base + r as usize] = mono_cue;
-        }
+    if let Some(cue) = cue {
+      let cue_left = cue[mix_base];
+      let cue_right = cue[mix_base + 1];
+      let mono_cue = (cue_left + cue_right) * 0.5;
+
+      if let (Some(l), Some(r)) = (cue_l, cue_r) {
+        output[out_base + l as usize] = cue_left;
+        output[out_base + r as usize] = cue_right;
+      } else if let Some(l) = cue_l {
+        output[out_base + l as usize] = mono_cue;
+      } else if let Some(r) = cue_r {
+        output[out_base + r as usize] = mono_cue;
       }
     }
   }
@@ -1587,21 +5465,95 @@ fn map_channels(
   output
 }
 
+/// 1.0 if a deck reaches the master under the current mute/solo state, else 0.0
+fn master_enable(deck: &DeckState, any_soloed: bool) -> f32 {
+  if deck.muted || (any_soloed && !deck.soloed) {
+    0.0
+  } else {
+    1.0
+  }
+}
+
+/// Sum the cue-enabled decks into a stereo PFL bus with cue gain and limiter applied
+/// Returns None when no deck is cued
+fn mix_cue_bus(
+  config: &ChannelConfig,
+  cue_bus: &mut CueBusState,
+  buffer_a: &[f32],
+  buffer_b: &[f32],
+  frames: usize,
+) -> Option<Vec<f32>> {
+  let cue_sources = config.deck_a_cue as usize + config.deck_b_cue as usize;
+  if cue_sources == 0 {
+    return None;
+  }
+
+  let channels = DEFAULT_CHANNELS as usize;
+  let norm = cue_bus.gain / cue_sources as f32;
+  let mut cue = vec![0.0f32; frames * channels];
+
+  for frame in 0..frames {
+    let base = frame * channels;
+    let mut cue_left = 0.0;
+    let mut cue_right = 0.0;
+
+    if config.deck_a_cue {
+      cue_left += buffer_a[base];
+      cue_right += buffer_a[base + 1];
+    }
+
+    if config.deck_b_cue {
+      cue_left += buffer_b[base];
+      cue_right += buffer_b[base + 1];
+    }
+
+    cue_left *= norm;
+    cue_right *= norm;
+    if cue_bus.limiter_enabled {
+      (cue_left, cue_right) = cue_bus.limiter.process_frame(cue_left, cue_right);
+    }
+    cue[base] = cue_left.clamp(-1.0, 1.0);
+    cue[base + 1] = cue_right.clamp(-1.0, 1.0);
+  }
+
+  Some(cue)
+}
+
+fn eq_band_unavailable(band: &str, mode: EqMode) -> Error {
+  Error::from_reason(format!(
+    "EQ band {} is not available in {} mode",
+    band,
+    mode.name()
+  ))
+}
+
+fn eq_gains_db(eq: &EqProcessor) -> Vec<f64> {
+  eq.gains_db().into_iter().map(|db| db as f64).collect()
+}
+
+/// Per-band levels of a deck's last chunk; a stopped deck renders nothing and reads silent
+fn band_levels(deck: &DeckState) -> Vec<f64> {
+  if !deck.playing {
+    return vec![0.0; 3];
+  }
+  let levels = deck.eq_processor.band_levels();
+  levels.iter().map(|&level| level as f64).collect()
+}
+
 /// Create state update for JavaScript
 fn create_state_update(state: &EngineState, sample_rate: u32) -> AudioEngineStateUpdate {
   // Calculate position for deck A
-  let deck_a_position = state
-    .deck_a
-    .pcm_data
-    .as_ref()
-    .map(|_| state.deck_a.position as f64 / sample_rate as f64);
+  let latency_seconds = output_latency_seconds(state, sample_rate);
+  let device_sample_rate = state
+    .output_stats
+    .device_sample_rate
+    .load(Ordering::Relaxed);
+  let deck_a_position = reported_position(&state.deck_a, state, latency_seconds, sample_rate);
 
   // Calculate position for deck B
-  let deck_b_position = state
-    .deck_b
-    .pcm_data
-    .as_ref()
-    .map(|_| state.deck_b.position as f64 / sample_rate as f64);
+  let deck_b_position = reported_position(&state.deck_b, state, latency_seconds, sample_rate);
+  let deck_a_duration = track_duration(&state.deck_a, sample_rate);
+  let deck_b_duration = track_duration(&state.deck_b, sample_rate);
 
   // Use update_reason if set, otherwise "periodic"
   let update_reason = state
@@ -1621,6 +5573,12 @@ fn create_state_update(state: &EngineState, sample_rate: u32) -> AudioEngineStat
       enabled: state.deck_a.loop_enabled,
       start: state.deck_a.loop_start as f64 / total_frames as f64,
       end: state.deck_a.loop_end as f64 / total_frames as f64,
+      roll_beats: state.deck_a.roll.as_ref().map(|roll| roll.beats),
+      slip_position: state
+        .deck_a
+        .roll
+        .as_ref()
+        .map(|roll| roll.slip_position / total_frames as f64),
     }
   } else {
     LoopStateJs::default()
@@ -1632,6 +5590,12 @@ fn create_state_update(state: &EngineState, sample_rate: u32) -> AudioEngineStat
       enabled: state.deck_b.loop_enabled,
       start: state.deck_b.loop_start as f64 / total_frames as f64,
       end: state.deck_b.loop_end as f64 / total_frames as f64,
+      roll_beats: state.deck_b.roll.as_ref().map(|roll| roll.beats),
+      slip_position: state
+        .deck_b
+        .roll
+        .as_ref()
+        .map(|roll| roll.slip_position / total_frames as f64),
     }
   } else {
     LoopStateJs::default()
@@ -1640,40 +5604,1241 @@ fn create_state_update(state: &EngineState, sample_rate: u32) -> AudioEngineStat
   AudioEngineStateUpdate {
     deck_a_position,
     deck_b_position,
-    deck_a_playing: state.deck_a.playing,
-    deck_b_playing: state.deck_b.playing,
+    deck_a_source_position: deck_a_position,
+    deck_b_source_position: deck_b_position,
+    deck_a_output_position: output_position(&state.deck_a, state, latency_seconds, sample_rate),
+    deck_b_output_position: output_position(&state.deck_b, state, latency_seconds, sample_rate),
+    deck_a_duration,
+    deck_b_duration,
+    deck_a_remaining: remaining_seconds(&state.deck_a, deck_a_duration, deck_a_position),
+    deck_b_remaining: remaining_seconds(&state.deck_b, deck_b_duration, deck_b_position),
+    deck_a_playing: state.deck_a.reports_playing(),
+    deck_b_playing: state.deck_b.reports_playing(),
     crossfader_position: state.crossfade.position as f64,
+    crossfader_deadzone: state.crossfade.deadzone as f64,
     is_crossfading: state.crossfade.active,
+    deck_a_crossfader_side: state.deck_a.crossfader_side.name().to_string(),
+    deck_b_crossfader_side: state.deck_b.crossfader_side.name().to_string(),
     deck_a_peak: state.levels.deck_a_peak as f64,
     deck_b_peak: state.levels.deck_b_peak as f64,
     deck_a_peak_hold: state.levels.deck_a_peak_hold as f64,
     deck_b_peak_hold: state.levels.deck_b_peak_hold as f64,
+    deck_a_rms: state.levels.deck_a_mean_square.sqrt() as f64,
+    deck_b_rms: state.levels.deck_b_mean_square.sqrt() as f64,
+    deck_a_clipped: state.levels.deck_a_clipped,
+    deck_b_clipped: state.levels.deck_b_clipped,
+    master_clipped: state.levels.master_clipped,
+    cue_left_peak: state.levels.cue_left_peak as f64,
+    cue_right_peak: state.levels.cue_right_peak as f64,
     master_tempo: state.master_tempo as f64,
     deck_a_track_id: state.deck_a.track_id.clone(),
     deck_b_track_id: state.deck_b.track_id.clone(),
+    recording_error: state.recording_error.clone(),
+    recording_path: state.recording_path.clone(),
+    underrun_count: state.output_stats.underruns.load(Ordering::Relaxed) as f64,
+    output_latency_ms: latency_seconds * 1000.0,
+    engine_sample_rate: sample_rate,
+    device_sample_rate: Some(device_sample_rate).filter(|&rate| rate > 0),
+    device_name: state.device_name.clone(),
+    device_error: state.device_error.clone(),
+    deck_a_muted: state.deck_a.muted,
+    deck_b_muted: state.deck_b.muted,
+    deck_a_soloed: state.deck_a.soloed,
+    deck_b_soloed: state.deck_b.soloed,
+    deck_a_next_track_id: next_track_id(&state.deck_a),
+    deck_b_next_track_id: next_track_id(&state.deck_b),
     deck_a_gain: state.deck_a.gain as f64,
     deck_b_gain: state.deck_b.gain as f64,
+    deck_a_fader_curve: state.deck_a.fader_curve.name().to_string(),
+    deck_b_fader_curve: state.deck_b.fader_curve.name().to_string(),
+    deck_a_pan: state.deck_a.pan as f64,
+    deck_b_pan: state.deck_b.pan as f64,
+    deck_a_trim_db: 20.0 * (state.deck_a.trim as f64).log10(),
+    deck_b_trim_db: 20.0 * (state.deck_b.trim as f64).log10(),
+    deck_a_hpf_hz: state.deck_a.trim_filters.highpass_hz() as f64,
+    deck_b_hpf_hz: state.deck_b.trim_filters.highpass_hz() as f64,
+    deck_a_lpf_hz: state.deck_a.trim_filters.lowpass_hz() as f64,
+    deck_b_lpf_hz: state.deck_b.trim_filters.lowpass_hz() as f64,
+    deck_a_pitch_adjust: state.deck_a.pitch_adjust as f64,
+    deck_b_pitch_adjust: state.deck_b.pitch_adjust as f64,
     deck_a_cue_enabled: state.channel_config.deck_a_cue,
     deck_b_cue_enabled: state.channel_config.deck_b_cue,
-    deck_a_eq_cut: EqCutStateJs {
-      low: deck_a_eq.low,
-      mid: deck_a_eq.mid,
-      high: deck_a_eq.high,
-    },
-    deck_b_eq_cut: EqCutStateJs {
-      low: deck_b_eq.low,
-      mid: deck_b_eq.mid,
-      high: deck_b_eq.high,
-    },
+    deck_a_eq_cut: deck_a_eq.into(),
+    deck_b_eq_cut: deck_b_eq.into(),
+    deck_a_eq_mode: state.deck_a.eq_processor.mode().name().to_string(),
+    deck_b_eq_mode: state.deck_b.eq_processor.mode().name().to_string(),
+    deck_a_eq_gains_db: eq_gains_db(&state.deck_a.eq_processor),
+    deck_b_eq_gains_db: eq_gains_db(&state.deck_b.eq_processor),
+    deck_a_band_levels: band_levels(&state.deck_a),
+    deck_b_band_levels: band_levels(&state.deck_b),
     deck_a_loop,
     deck_b_loop,
+    deck_a_cue_point: state
+      .deck_a
+      .temp_cue
+      .map(|cue| cue as f64 / sample_rate as f64),
+    deck_b_cue_point: state
+      .deck_b
+      .temp_cue
+      .map(|cue| cue as f64 / sample_rate as f64),
+    deck_a_headroom_db: state.deck_a.headroom_db.map(|db| db as f64),
+    deck_b_headroom_db: state.deck_b.headroom_db.map(|db| db as f64),
     mic_available: state.mic_available,
     mic_enabled: state.microphone.enabled,
     mic_peak: state.microphone.peak as f64,
+    mic_peak_left: state.microphone.peak_left as f64,
+    mic_peak_right: state.microphone.peak_right as f64,
+    mic_stereo: state.microphone.stereo,
+    mic_pan: state.microphone.pan as f64,
+    mic_latency_ms: state.microphone.latency_ms as f64,
+    mic_monitor: state.microphone.monitor,
+    mic_monitor_target: state.microphone.monitor_target.name().to_string(),
+    external_peak: state.external_input.peak as f64,
+    external_gain: state.external_input.gain as f64,
+    paused: state.paused,
+    panicked: state.panicked,
+    sampler_playing: state.sampler.playing_slots(),
+    deck_a_synced: state.deck_a.sync_enabled,
+    deck_b_synced: state.deck_b.sync_enabled,
+    sync_master: state.sync_master,
+    mono_output: state.mono_output,
+    stereo_width: state.stereo_width as f64,
+    master_gain: state.master_gain_db as f64,
+    master_eq_gains_db: state
+      .master_eq
+      .gains_db()
+      .iter()
+      .map(|&db| db as f64)
+      .collect(),
+    master_eq_bypassed: state.master_eq.bypassed(),
+    master_limiter_enabled: state.master_limiter_enabled,
+    master_gain_reduction_db: state.master_gain_reduction_db as f64,
+    master_limiter_latency_ms: state.master_limiter.latency_frames() as f64 * 1000.0
+      / sample_rate as f64,
+    master_correlation: state.master_correlation as f64,
+    master_beat: state.clock_phase,
+    scheduled_actions: state.scheduled.len() as u32,
     update_reason,
   }
 }
 
+/// Estimated time from rendering a chunk to hearing it (seconds)
+/// The process thread tops the queue up whenever it drops below twice the target,
+/// so it holds about twice the target plus half a chunk on average
+fn output_latency_seconds(state: &EngineState, sample_rate: u32) -> f64 {
+  let profile = state.latency_profile;
+  let queued_frames = 2 * profile.target_queue_frames(sample_rate) + profile.frames_per_chunk / 2;
+  let device_us = state.output_stats.device_latency_us.load(Ordering::Relaxed);
+  // The master limiter's lookahead delays everything it processes
+  let limiter_frames = if state.master_limiter_enabled {
+    state.master_limiter.latency_frames()
+  } else {
+    0
+  };
+  (queued_frames + limiter_frames) as f64 / sample_rate as f64 + device_us as f64 / 1_000_000.0
+}
+
+/// Deck position in seconds (None without a track), latency-compensated if enabled
+fn reported_position(
+  deck: &DeckState,
+  state: &EngineState,
+  latency_seconds: f64,
+  sample_rate: u32,
+) -> Option<f64> {
+  deck.pcm_data.as_ref()?;
+  let seconds = deck.position as f64 / sample_rate as f64;
+  if state.latency_compensation && deck.playing {
+    // The audible frame trails the render position by latency at the deck's rate
+    Some((seconds - latency_seconds * deck.rate as f64).max(0.0))
+  } else {
+    Some(seconds)
+  }
+}
+
+/// Deck output-time position in seconds (None without a track), latency-compensated if enabled
+fn output_position(
+  deck: &DeckState,
+  state: &EngineState,
+  latency_seconds: f64,
+  sample_rate: u32,
+) -> Option<f64> {
+  deck.pcm_data.as_ref()?;
+  let seconds = deck.output_frames / sample_rate as f64;
+  if state.latency_compensation && deck.playing {
+    Some((seconds - latency_seconds).max(0.0))
+  } else {
+    Some(seconds)
+  }
+}
+
+/// Length of the deck's loaded track in seconds
+fn track_duration(deck: &DeckState, sample_rate: u32) -> Option<f64> {
+  let pcm = deck.pcm_data.as_ref()?;
+  Some((pcm.len() / DEFAULT_CHANNELS as usize) as f64 / sample_rate as f64)
+}
+
+/// Time to the track end as heard, scaled by the playback rate (tempo match and pitch)
+fn remaining_seconds(
+  deck: &DeckState,
+  duration: Option<f64>,
+  position: Option<f64>,
+) -> Option<f64> {
+  let remaining = (duration? - position?).max(0.0);
+  Some(remaining / deck.rate as f64)
+}
+
+/// Track ID of the track queued on a deck
+fn next_track_id(deck: &DeckState) -> Option<String> {
+  deck
+    .next_track
+    .as_ref()
+    .and_then(|next| next.track_id.clone())
+}
+
 fn map_err<E: ToString>(err: E) -> Error {
   Error::from_reason(err.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_soft_clip_bounded_and_continuous() {
+    // Moderate overdrive: input up to +6 dB over full scale
+    let drive = 2.0;
+    let step = 0.001;
+    let mut x = -2.0f32;
+    let mut prev = soft_clip(x, drive);
+    while x < 2.0 {
+      x += step;
+      let y = soft_clip(x, drive);
+      assert!(y.abs() <= 1.0, "soft_clip({x}) = {y} is out of range");
+      // No jumps and no flat hard-clip corner: monotonic with bounded slope
+      assert!(y >= prev, "soft_clip is not monotonic at {x}");
+      assert!(y - prev <= drive * step + 1e-6, "soft_clip jumps at {x}");
+      prev = y;
+    }
+    assert!(soft_clip(1.5, drive) < soft_clip(2.0, drive));
+  }
+
+  #[test]
+  fn test_load_large_track_does_not_stall_playing_deck() {
+    let state = Mutex::new(EngineState::new(DEFAULT_SAMPLE_RATE));
+    load_deck_track(&state, 1, &tone(4.0, 0), Some(128.0), None);
+    state.lock().deck_a.playing = true;
+
+    // 50 MB of f32 stereo PCM per load while deck A keeps playing
+    let large = vec![0.2f32; 50 * 1024 * 1024 / 4];
+    for _ in 0..5 {
+      let previous = state.lock().deck_b.pcm_data.as_ref().map(Arc::downgrade);
+      let position = state.lock().deck_a.position;
+
+      load_deck_track(&state, 2, &large, Some(124.0), None);
+
+      let mut s = state.lock();
+      let installed = s.deck_b.pcm_data.clone().unwrap();
+      assert_eq!(installed.as_slice(), large.as_slice());
+      assert!(previous.is_none_or(|p| p.upgrade().is_none()));
+      assert!(s.deck_a.playing);
+      assert_eq!(s.deck_a.position, position);
+      process_audio_chunk(&mut s, DEFAULT_SAMPLE_RATE, DEFAULT_CHANNELS);
+      assert!(s.deck_a.position > position);
+    }
+
+    // Under the lock the swap only moves pointers: the prepared buffer is installed as is
+    // and the replaced one is handed back to be freed after unlocking
+    let prepared = Arc::new(large);
+    let mut s = state.lock();
+    let previous = install_track(&mut s, 2, Arc::clone(&prepared), Some(124.0), None).unwrap();
+    assert!(Arc::ptr_eq(s.deck_b.pcm_data.as_ref().unwrap(), &prepared));
+    assert_eq!(Arc::strong_count(&previous), 1);
+  }
+
+  #[test]
+  fn test_queue_next_advances_without_stopping() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    let one_second = DEFAULT_SAMPLE_RATE as usize * 2;
+    let first = Arc::new(vec![0.1; one_second]);
+    install_track(&mut state, 1, first, None, Some("first".into()));
+    state.deck_a.next_track = Some(QueuedTrack {
+      pcm: vec![0.2; one_second],
+      bpm: None,
+      track_id: Some("second".into()),
+      headroom_db: 0.0,
+    });
+    state.deck_a.playing = true;
+
+    let mut advanced = false;
+    for _ in 0..100 {
+      let (_, _, update) = process_audio_chunk(&mut state, DEFAULT_SAMPLE_RATE, DEFAULT_CHANNELS);
+      if update.update_reason == "advanced" {
+        assert_eq!(update.deck_a_track_id.as_deref(), Some("second"));
+        advanced = true;
+        break;
+      }
+    }
+
+    assert!(advanced, "deck never advanced into the queued track");
+    assert!(state.deck_a.playing);
+    assert!(state.deck_a.next_track.is_none());
+    assert_eq!(state.retired_pcm.len(), 1);
+    // The old tail fades out in the output; the new track's PCM is untouched
+    let pcm = state.deck_a.pcm_data.as_ref().unwrap();
+    assert_eq!(pcm[0], 0.2);
+    assert!(!state.deck_a.seam_tail.is_empty());
+    assert!(state.deck_a.seam_tail.iter().all(|&s| s == 0.1));
+
+    let mut output = vec![0.0; 256 * DEFAULT_CHANNELS as usize];
+    state.deck_a.render(256, &mut output, 1.0);
+    assert!(
+      (output[0] - 0.1).abs() < 1e-6,
+      "seam starts from the old tail"
+    );
+    assert!(state.deck_a.seam_tail.is_empty());
+  }
+
+  #[test]
+  fn test_seek_after_advance_plays_the_unaltered_track() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    let one_second = DEFAULT_SAMPLE_RATE as usize * 2;
+    let second: Vec<f32> = (0..one_second).map(|i| 0.3 + i as f32 * 1e-6).collect();
+    install_track(&mut state, 1, Arc::new(vec![0.1; one_second]), None, None);
+    state.deck_a.next_track = Some(QueuedTrack {
+      pcm: second.clone(),
+      bpm: None,
+      track_id: Some("second".into()),
+      headroom_db: 0.0,
+    });
+    state.deck_a.playing = true;
+
+    for _ in 0..100 {
+      let (_, _, update) = process_audio_chunk(&mut state, DEFAULT_SAMPLE_RATE, DEFAULT_CHANNELS);
+      if update.update_reason == "advanced" {
+        break;
+      }
+    }
+    assert!(state.deck_a.next_track.is_none());
+
+    state.deck_a.jump(0);
+    assert_eq!(state.deck_a.pcm_data.as_deref(), Some(&second));
+    let mut output = vec![0.0; 256 * DEFAULT_CHANNELS as usize];
+    state.deck_a.render(256, &mut output, 1.0);
+    assert_eq!(&output[..2], &second[..2]);
+  }
+
+  #[test]
+  fn test_instant_double_drops_the_target_loop() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(
+      &mut state,
+      1,
+      Arc::new(tone(2.0, 0)),
+      Some(120.0),
+      Some("a".into()),
+    );
+    install_track(
+      &mut state,
+      2,
+      Arc::new(tone(2.0, 1)),
+      None,
+      Some("b".into()),
+    );
+    state.deck_a.jump(30_000);
+    state.deck_b.loop_start = 1_000;
+    state.deck_b.loop_end = 2_000;
+    state.deck_b.loop_enabled = true;
+    state.deck_b.fade_out = SOFT_START_FRAMES;
+
+    let previous = state.instant_double(1).unwrap();
+    assert!(previous.is_some());
+    assert_eq!(state.update_reason.as_deref(), Some("load"));
+    let deck_b = &state.deck_b;
+    assert!(Arc::ptr_eq(
+      deck_b.pcm_data.as_ref().unwrap(),
+      state.deck_a.pcm_data.as_ref().unwrap()
+    ));
+    assert!(!deck_b.loop_enabled);
+    assert_eq!(deck_b.fade_out, 0);
+    assert_eq!(deck_b.position, 30_000);
+    assert_eq!(deck_b.jump_bridge, JUMP_BRIDGE_FRAMES);
+    assert_eq!(deck_b.track_id.as_deref(), Some("a"));
+
+    // Plays on from the doubled position instead of looping back
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, 22_050);
+    assert!(state.deck_b.playing);
+    assert!(state.deck_b.position > 30_000);
+  }
+
+  #[test]
+  fn test_instant_double_plays_in_phase() {
+    let frames = 1024;
+    // A chirp, so any offset between the decks shows up as a different sample
+    let pcm: Vec<f32> = (0..DEFAULT_SAMPLE_RATE as usize * 4)
+      .flat_map(|i| {
+        let t = i as f32 / DEFAULT_SAMPLE_RATE as f32;
+        let s = 0.5 * (2.0 * PI * (200.0 + 400.0 * t) * t).sin();
+        [s, s]
+      })
+      .collect();
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(pcm), None, None);
+    state.deck_a.start();
+    let mut output = vec![0.0; frames * 2];
+    for _ in 0..20 {
+      state.render_deck(1, frames, &mut output, &[], 1.0);
+    }
+    // The stretcher has read ahead of what deck A has played
+    assert!(state.deck_a.position as f64 >= state.deck_a.audible_position + frames as f64);
+
+    state.instant_double(1).unwrap();
+    assert!(state.deck_b.playing);
+    let (mut a, mut b) = (vec![0.0; frames * 2], vec![0.0; frames * 2]);
+    for _ in 0..4 {
+      state.render_deck(1, frames, &mut a, &[], 1.0);
+      state.render_deck(2, frames, &mut b, &[], 1.0);
+    }
+    let max_diff = a
+      .iter()
+      .zip(&b)
+      .map(|(x, y)| (x - y).abs())
+      .fold(0.0, f32::max);
+    assert!(max_diff < 1e-3, "decks out of phase by up to {}", max_diff);
+  }
+
+  #[test]
+  fn test_recording_splits_queue_one_event_each() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    state.emit_recording_split("set-002.wav".to_string());
+    state.update_reason = Some("play".to_string());
+    state.emit_recording_split("set-003.wav".to_string());
+
+    let paths: Vec<_> = state.events.iter().map(|e| e.path.as_deref()).collect();
+    assert_eq!(paths, [Some("set-002.wav"), Some("set-003.wav")]);
+    assert!(state.events.iter().all(|e| e.kind == "recording_split"));
+    assert_eq!(state.recording_path.as_deref(), Some("set-003.wav"));
+  }
+
+  #[test]
+  fn test_instant_double_soft_starts() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    let pcm = vec![0.5; DEFAULT_SAMPLE_RATE as usize * 2];
+    install_track(&mut state, 1, Arc::new(pcm.clone()), None, None);
+    install_track(&mut state, 2, Arc::new(pcm), None, None);
+    // Deck B is already playing its own track; the double still ramps in
+    state.deck_b.start();
+    let mut output = vec![0.0; 1024 * 2];
+    state.render_deck(2, 1024, &mut output, &[], 1.0);
+
+    state.instant_double(1).unwrap();
+    state.render_deck(2, 1024, &mut output, &[], 1.0);
+    assert_eq!(output[0], 0.0);
+    assert!(output[SOFT_START_FRAMES * 2] > 0.4);
+  }
+
+  #[test]
+  fn test_jump_bridge_wraps_inside_the_loop() {
+    let channels = DEFAULT_CHANNELS as usize;
+    let pcm: Vec<f32> = (0..4_000 * channels)
+      .map(|i| (i / channels) as f32 / 4_000.0)
+      .collect();
+    let mut deck = DeckState::new(DEFAULT_SAMPLE_RATE);
+    deck.jump(1_050);
+
+    let mut output = vec![0.0; 256 * channels];
+    deck.apply_jump_bridge(&pcm, 4_000, Some((1_000, 1_100)), 1.0, &mut output);
+
+    assert!((1_000.0..1_100.0).contains(&deck.bridge_position));
+    // 60 frames in, the read has wrapped from 1110 back to 1010
+    let fade = 1.0 - (JUMP_BRIDGE_FRAMES - 60) as f32 / JUMP_BRIDGE_FRAMES as f32;
+    let expected = pcm[1_010 * channels] * (1.0 - fade);
+    assert!((output[60 * channels] - expected).abs() < 1e-6);
+  }
+
+  #[test]
+  fn test_output_position_counts_played_time_across_a_rate_change() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+    state.deck_a.playing = true;
+
+    let one_second = DEFAULT_SAMPLE_RATE as usize;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, one_second);
+    state.deck_a.rate = 1.5;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, one_second);
+
+    let update = create_state_update(&state, DEFAULT_SAMPLE_RATE);
+    let source = update.deck_a_source_position.unwrap();
+    let output = update.deck_a_output_position.unwrap();
+    let chunk = MAX_FRAMES_PER_CHUNK as f64 / DEFAULT_SAMPLE_RATE as f64;
+    // Two seconds played, 2.5 seconds of track covered (within a chunk of overshoot)
+    assert!((output - 2.0).abs() < 2.0 * chunk, "output {output}");
+    assert!((source - 2.5).abs() < 3.0 * chunk, "source {source}");
+
+    // A seek restarts it from the new position at the current rate
+    state.deck_a.jump(DEFAULT_SAMPLE_RATE as usize * 3);
+    let update = create_state_update(&state, DEFAULT_SAMPLE_RATE);
+    assert!((update.deck_a_output_position.unwrap() - 2.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_crossfader_deadzone_is_continuous() {
+    let deadzone = 0.1;
+    assert_eq!(crossfader_deadzone(0.0, deadzone), 0.0);
+    assert_eq!(crossfader_deadzone(1.0, deadzone), 1.0);
+    assert_eq!(crossfader_deadzone(0.45, deadzone), 0.5);
+    assert_eq!(crossfader_deadzone(0.3, 0.0), 0.3);
+
+    // No step at either edge of the dead zone
+    for edge in [0.4, 0.6] {
+      let inside = crossfader_deadzone(edge, deadzone);
+      let outside = crossfader_deadzone(edge + (edge - 0.5) * 0.01, deadzone);
+      assert!(
+        (outside - inside).abs() < 0.01,
+        "step at {edge}: {inside} -> {outside}"
+      );
+    }
+    // Monotonic across the travel
+    let curve: Vec<f32> = (0..=100)
+      .map(|i| crossfader_deadzone(i as f32 / 100.0, deadzone))
+      .collect();
+    assert!(curve.windows(2).all(|w| w[1] >= w[0]));
+  }
+
+  /// Drive process_audio_chunk directly for `frames` frames and collect the device output
+  fn render_offline(state: &mut EngineState, sample_rate: u32, frames: usize) -> Vec<f32> {
+    let output_channels = state.channel_config.output_channels;
+    let samples = frames * output_channels as usize;
+    let mut output = Vec::with_capacity(samples + MAX_FRAMES_PER_CHUNK * output_channels as usize);
+
+    while output.len() < samples {
+      let (chunk, _, _) = process_audio_chunk(state, sample_rate, output_channels);
+      output.extend_from_slice(&chunk);
+    }
+    state.retired_pcm.clear();
+    state.events.clear();
+
+    output.truncate(samples);
+    output
+  }
+
+  /// Stereo test tone: 440 Hz sine at 0.5 on the given channel, silence on the other
+  fn tone(seconds: f32, channel: usize) -> Vec<f32> {
+    let frames = (seconds * DEFAULT_SAMPLE_RATE as f32) as usize;
+    (0..frames)
+      .flat_map(|i| {
+        let t = i as f32 / DEFAULT_SAMPLE_RATE as f32;
+        let s = 0.5 * (2.0 * PI * 440.0 * t).sin();
+        if channel == 0 {
+          [s, 0.0]
+        } else {
+          [0.0, s]
+        }
+      })
+      .collect()
+  }
+
+  /// RMS of one channel of interleaved stereo
+  fn channel_rms(buffer: &[f32], channel: usize) -> f32 {
+    let samples: Vec<f32> = buffer.iter().skip(channel).step_by(2).copied().collect();
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+  }
+
+  #[test]
+  fn test_offline_auto_crossfade_moves_to_deck_b() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    // Deck A on the left, deck B on the right
+    install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+    install_track(&mut state, 2, Arc::new(tone(4.0, 1)), None, None);
+    state.deck_a.playing = true;
+    state.deck_b.playing = true;
+
+    // Crossfader at A: only deck A is heard
+    let before = render_offline(&mut state, DEFAULT_SAMPLE_RATE, 22_050);
+    let settled = &before[before.len() / 2..];
+    assert!(channel_rms(settled, 0) > 0.2);
+    assert!(channel_rms(settled, 1) < 1e-3);
+
+    // One-second auto crossfade to B
+    state.crossfade.active = true;
+    state.crossfade.direction = Some(CrossfadeDirection::AtoB);
+    state.crossfade.total_frames = DEFAULT_SAMPLE_RATE as usize;
+    state.crossfade.remaining_frames = DEFAULT_SAMPLE_RATE as usize;
+    state.crossfade.start_position = 0.0;
+    state.crossfade.target_position = 1.0;
+
+    let two_seconds = DEFAULT_SAMPLE_RATE as usize * 2;
+    let after = render_offline(&mut state, DEFAULT_SAMPLE_RATE, two_seconds);
+    assert!(!state.crossfade.active);
+    assert_eq!(state.crossfade.position, 1.0);
+    assert!(!state.deck_a.playing && state.deck_b.playing);
+    let settled = &after[after.len() / 2..];
+    assert!(channel_rms(settled, 0) < 1e-3);
+    assert!(channel_rms(settled, 1) > 0.2);
+  }
+
+  #[test]
+  fn test_rolling_rms_tracks_deck_trim() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+    install_track(&mut state, 2, Arc::new(tone(4.0, 0)), None, None);
+    state.deck_a.playing = true;
+    state.deck_b.playing = true;
+    // Trim already settled, so the ramp doesn't skew the reading
+    state.deck_b.trim = 0.5;
+    state.deck_b.trim_smoother = ParamSmoother::new(0.5);
+
+    let two_seconds = DEFAULT_SAMPLE_RATE as usize * 2;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, two_seconds);
+    let levels = &state.levels;
+    let (a, b) = (levels.deck_a_mean_square, levels.deck_b_mean_square);
+    assert!(a > 0.0);
+    // Same material 6 dB down reads as a quarter of the power
+    assert!((a / b - 4.0).abs() < 0.01);
+
+    // A stopped deck keeps its last reading
+    state.deck_a.playing = false;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, 22_050);
+    assert_eq!(state.levels.deck_a_mean_square, a);
+  }
+
+  #[test]
+  fn test_fader_curves_at_half_travel() {
+    let db = |curve: FaderCurve| 20.0 * curve.gain(0.5).log10();
+    assert!((db(FaderCurve::Linear) + 6.02).abs() < 0.01);
+    assert!((db(FaderCurve::Square) + 12.04).abs() < 0.01);
+    assert!((db(FaderCurve::Logarithmic) + 30.0).abs() < 0.01);
+    for name in ["linear", "square", "logarithmic"] {
+      let curve = FaderCurve::from_name(name).unwrap();
+      assert_eq!(curve.gain(0.0), 0.0);
+      assert_eq!(curve.gain(1.0), 1.0);
+    }
+  }
+
+  #[test]
+  fn test_auto_crossfade_can_keep_outgoing_deck_playing() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+    install_track(&mut state, 2, Arc::new(tone(4.0, 1)), None, None);
+    state.deck_a.playing = true;
+
+    let one_second = DEFAULT_SAMPLE_RATE as usize;
+    state.begin_crossfade(Some(1.0), one_second / 2, 0);
+    state.crossfade.stop_on_complete = false;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, one_second);
+    assert!(!state.crossfade.active);
+    assert!(state.deck_a.playing && state.deck_b.playing);
+
+    // Bringing the outgoing deck back with the crossfader makes it audible again
+    state.crossfade.position = 0.0;
+    let back = render_offline(&mut state, DEFAULT_SAMPLE_RATE, 22_050);
+    assert!(channel_rms(&back[back.len() / 2..], 0) > 0.2);
+  }
+
+  #[test]
+  fn test_auto_loop_follows_playhead_and_reports_reason() {
+    let sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut state = EngineState::new(sample_rate);
+    install_track(&mut state, 1, Arc::new(tone(10.0, 0)), Some(120.0), None);
+    install_track(&mut state, 2, Arc::new(tone(10.0, 0)), None, None);
+    state.update_reason = None;
+    state.deck_a.position = 22_050;
+
+    // Four beats at 120 BPM: two seconds from the playhead
+    state.set_auto_loop(1, 4.0, sample_rate).unwrap();
+    assert_eq!(
+      (state.deck_a.loop_start, state.deck_a.loop_end),
+      (22_050, 110_250)
+    );
+    let update = create_state_update(&state, sample_rate);
+    assert_eq!(update.update_reason, "auto_loop");
+    assert!(update.deck_a_loop.enabled);
+
+    // Another size inside the loop only changes its length
+    state.deck_a.position = 30_000;
+    state.set_auto_loop(1, 1.0, sample_rate).unwrap();
+    assert_eq!(
+      (state.deck_a.loop_start, state.deck_a.loop_end),
+      (22_050, 44_100)
+    );
+    assert_eq!(state.deck_a.position, 30_000);
+
+    // Quantize snaps a new loop to the nearest beat
+    state.deck_a.loop_enabled = false;
+    state.deck_a.quantize = true;
+    state.deck_a.position = 50_000;
+    state.set_auto_loop(1, 1.0, sample_rate).unwrap();
+    assert_eq!(state.deck_a.loop_start, 44_100);
+
+    assert!(state.set_auto_loop(2, 4.0, sample_rate).is_err());
+  }
+
+  #[test]
+  fn test_offline_loop_stays_inside_loop() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    // First second on the left, the rest on the right
+    let mut pcm = tone(1.0, 0);
+    pcm.extend(tone(2.0, 1));
+    install_track(&mut state, 1, Arc::new(pcm), None, None);
+    state.deck_a.loop_enabled = true;
+    state.deck_a.loop_start = 0;
+    state.deck_a.loop_end = DEFAULT_SAMPLE_RATE as usize / 2;
+    state.deck_a.playing = true;
+
+    // Four times the loop length: without the loop this would reach the right-channel part
+    let two_seconds = DEFAULT_SAMPLE_RATE as usize * 2;
+    let output = render_offline(&mut state, DEFAULT_SAMPLE_RATE, two_seconds);
+    assert!(state.deck_a.playing);
+    assert!(state.deck_a.position < state.deck_a.loop_end);
+    // Allow for the stretcher's startup latency
+    assert!(channel_rms(&output, 0) > 0.1);
+    assert!(channel_rms(&output, 1) < 1e-3);
+  }
+
+  #[test]
+  fn test_offline_sub_chunk_loop_plays_continuously() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    let mut pcm = tone(1.0, 0);
+    pcm.extend(tone(1.0, 1));
+    install_track(&mut state, 1, Arc::new(pcm), None, None);
+    // A quarter of a chunk (about 1/32 beat at 130 BPM)
+    state.deck_a.loop_enabled = true;
+    state.deck_a.loop_start = 1000;
+    state.deck_a.loop_end = 1000 + MAX_FRAMES_PER_CHUNK / 4;
+    state.deck_a.position = 1000;
+    state.deck_a.playing = true;
+
+    let one_second = DEFAULT_SAMPLE_RATE as usize;
+    let output = render_offline(&mut state, DEFAULT_SAMPLE_RATE, one_second);
+    assert!(state.deck_a.playing);
+    assert!((state.deck_a.loop_start..state.deck_a.loop_end).contains(&state.deck_a.position));
+    // After the stretcher's startup, no window drops out and nothing past the loop leaks in
+    for window in output[output.len() / 4..].chunks_exact(1024) {
+      assert!(channel_rms(window, 0) > 0.1);
+      assert!(channel_rms(window, 1) < 1e-3);
+    }
+  }
+
+  #[test]
+  fn test_trim_change_ramps() {
+    let coeff = smoothing_coefficient(DEFAULT_SMOOTHING_TIME, DEFAULT_SAMPLE_RATE);
+    let pcm = Arc::new(tone(2.0, 0));
+    let mut reference = DeckState::new(DEFAULT_SAMPLE_RATE);
+    let mut trimmed = DeckState::new(DEFAULT_SAMPLE_RATE);
+    for deck in [&mut reference, &mut trimmed] {
+      deck.pcm_data = Some(Arc::clone(&pcm));
+      deck.playing = true;
+    }
+    let frames = 2048;
+    let (mut a, mut b) = (vec![0.0f32; frames * 2], vec![0.0f32; frames * 2]);
+    for _ in 0..2 {
+      reference.render(frames, &mut a, coeff);
+      trimmed.render(frames, &mut b, coeff);
+    }
+
+    // -6 dB: the level starts at unity and settles at half instead of stepping
+    trimmed.trim = 0.5;
+    reference.render(frames, &mut a, coeff);
+    trimmed.render(frames, &mut b, coeff);
+    let ratios: Vec<f32> = a
+      .chunks_exact(2)
+      .zip(b.chunks_exact(2))
+      .filter(|(a, _)| a[0].abs() > 0.1)
+      .map(|(a, b)| b[0] / a[0])
+      .collect();
+    assert!(ratios[0] > 0.95, "first ratio {}", ratios[0]);
+    assert!(ratios.windows(2).all(|w| w[1] <= w[0] + 1e-3));
+    assert!((ratios[ratios.len() - 1] - 0.5).abs() < 0.02);
+  }
+
+  #[test]
+  fn test_jump_sounds_immediately() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(2.0, 0)), None, None);
+    state.deck_a.playing = true;
+    let frames = 512;
+    let mut output = vec![0.0f32; frames * 2];
+    for _ in 0..20 {
+      state.deck_a.render(frames, &mut output, 1.0);
+    }
+
+    // Jump (as a hot cue would) to a frame near a peak of the 440 Hz tone
+    state.deck_a.jump(20_071);
+    state.deck_a.render(frames, &mut output, 1.0);
+    let first_sound = output
+      .chunks_exact(2)
+      .position(|frame| frame[0].abs() > 0.1);
+    assert_eq!(first_sound, Some(0), "frames until sound after the jump");
+  }
+
+  #[test]
+  fn test_scheduled_play_starts_on_beat() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+    state
+      .schedule(1.0, Some(1), ScheduledKind::Play, DEFAULT_SAMPLE_RATE)
+      .unwrap();
+    let beat_seconds = 60.0 / state.master_tempo as f64;
+    let beat_frame = (beat_seconds * DEFAULT_SAMPLE_RATE as f64).round() as usize;
+
+    let output = render_offline(&mut state, DEFAULT_SAMPLE_RATE, beat_frame * 2);
+    assert!(state.deck_a.playing);
+    assert!(state.scheduled.is_empty());
+    // Silent up to the beat, which lands mid-chunk, then deck A is heard
+    assert!(output[..beat_frame * 2].iter().all(|&s| s == 0.0));
+    assert!(channel_rms(&output[beat_frame * 2..], 0) > 0.1);
+    // Soft-started like play()
+    assert!(output[beat_frame * 2 + 2].abs() < 0.01);
+  }
+
+  #[test]
+  fn test_schedule_rejects_non_finite_beats() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(1.0, 0)), None, None);
+    for beat in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+      assert!(state
+        .schedule(beat, Some(1), ScheduledKind::Play, DEFAULT_SAMPLE_RATE)
+        .is_err());
+    }
+    assert!(state.scheduled.is_empty());
+
+    // A beat too far out to reach saturates instead of overflowing the frame clock
+    state.frame_clock = 1000;
+    state
+      .schedule(1e300, Some(1), ScheduledKind::Play, DEFAULT_SAMPLE_RATE)
+      .unwrap();
+    assert_eq!(state.scheduled[0].frame, u64::MAX);
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, 1024);
+    assert!(!state.deck_a.playing);
+  }
+
+  #[test]
+  fn test_scheduled_play_cancels_a_fade_out() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(1.0, 0)), None, None);
+    state.deck_a.start();
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, 1024);
+
+    // Scheduled play lands while the stop is still fading, so the deck keeps playing
+    state.deck_a.soft_stop(None);
+    state.apply_scheduled(1, ScheduledKind::Play);
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, 1024);
+    assert!(state.deck_a.playing);
+  }
+
+  #[test]
+  fn test_play_ramps_up_and_stop_fades_out() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    let pcm = vec![0.5; DEFAULT_SAMPLE_RATE as usize * 2];
+    install_track(&mut state, 1, Arc::new(pcm), None, None);
+    state.deck_a.start();
+
+    // The first frames rise from silence instead of jumping to the DC level
+    let mut output = vec![0.0; 1024 * 2];
+    state.render_deck(1, 1024, &mut output, &[], 1.0);
+    let ramp: Vec<f32> = output.chunks_exact(2).map(|frame| frame[0]).collect();
+    assert_eq!(ramp[0], 0.0);
+    assert!(ramp[..SOFT_START_FRAMES].windows(2).all(|w| w[1] > w[0]));
+    assert!(ramp[SOFT_START_FRAMES / 2] < 0.3);
+
+    // Stop keeps the deck going just long enough to fade out, then it goes quiet
+    state.deck_a.soft_stop(None);
+    assert!(state.deck_a.playing);
+    let mut output = vec![0.0; 1024 * 2];
+    state.render_deck(1, 1024, &mut output, &[], 1.0);
+    assert!(!state.deck_a.playing);
+    assert!(output[SOFT_START_FRAMES * 2..].iter().all(|&s| s == 0.0));
+  }
+
+  #[test]
+  fn test_play_after_panic_release_ramps_up() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    state.smoothing_time = 0.0;
+    let pcm = vec![0.5; DEFAULT_SAMPLE_RATE as usize * 2];
+    install_track(&mut state, 1, Arc::new(pcm), None, None);
+    state.deck_a.start();
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, MAX_FRAMES_PER_CHUNK);
+
+    // Stop reports the deck stopped right away, while it fades out
+    state.deck_a.soft_stop(None);
+    let update = create_state_update(&state, DEFAULT_SAMPLE_RATE);
+    assert!(!update.deck_a_playing);
+
+    // Panic while playing, release, play again
+    state.deck_a.start();
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, MAX_FRAMES_PER_CHUNK);
+    state.panicked = true;
+    state.deck_a.playing = false;
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, MAX_FRAMES_PER_CHUNK);
+    state.panicked = false;
+    state.deck_a.start();
+
+    let output = render_offline(&mut state, DEFAULT_SAMPLE_RATE, MAX_FRAMES_PER_CHUNK);
+    let left: Vec<f32> = output.chunks_exact(2).map(|frame| frame[0]).collect();
+    assert!(left[0].abs() < 0.01, "no fade-in: {}", left[0]);
+    assert!(left[SOFT_START_FRAMES * 2] > 0.1);
+  }
+
+  /// Mic monitor mixer on the state's published settings, with `frames` of mic input queued
+  fn mic_monitor_mixer(state: &EngineState, frames: usize, value: f32) -> MicMonitorMixer {
+    let (mut producer, consumer) = RingBuffer::new(mic_monitor_capacity(DEFAULT_SAMPLE_RATE));
+    for _ in 0..frames * 2 {
+      producer.push(value).unwrap();
+    }
+    state
+      .mic_monitor
+      .input_sample_rate
+      .store(DEFAULT_SAMPLE_RATE, Ordering::Relaxed);
+    state.publish_mic_monitor();
+    MicMonitorMixer::new(consumer, Arc::clone(&state.mic_monitor))
+  }
+
+  #[test]
+  fn test_mic_monitor_routes_mic_without_talkover() {
+    let frames = 4096;
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    state.channel_config.output_channels = 4;
+    state.channel_config.cue_channels = [Some(2), Some(3)];
+    state.microphone.monitor = true;
+
+    // Headphones get the mic (talkover is off), the main outputs stay silent
+    let mut mixer = mic_monitor_mixer(&state, frames, 0.5);
+    let mut output = vec![0.0; frames * 4];
+    mixer.mix(&mut output, 4, DEFAULT_SAMPLE_RATE);
+    let last = &output[(frames - 1) * 4..];
+    assert_eq!(&last[..2], &[0.0, 0.0]);
+    assert!(last[2..].iter().all(|&s| (s - 0.5).abs() < 1e-3));
+
+    state.microphone.monitor_target = MicMonitorTarget::Main;
+    let mut mixer = mic_monitor_mixer(&state, frames, 0.5);
+    let mut output = vec![0.0; frames * 4];
+    mixer.mix(&mut output, 4, DEFAULT_SAMPLE_RATE);
+    let last = &output[(frames - 1) * 4..];
+    assert!(last[..2].iter().all(|&s| (s - 0.5).abs() < 1e-3));
+    assert_eq!(&last[2..], &[0.0, 0.0]);
+
+    // Talkover reads its own buffer: monitoring takes nothing from it and ducks nothing
+    let queued = DEFAULT_SAMPLE_RATE as usize * 2;
+    state.microphone.input_buffer = std::iter::repeat_n(0.5, queued).collect();
+    render_offline(&mut state, DEFAULT_SAMPLE_RATE, MAX_FRAMES_PER_CHUNK);
+    assert_eq!(
+      state.microphone.input_buffer.len(),
+      queued - MAX_FRAMES_PER_CHUNK * 2
+    );
+    assert_eq!(state.microphone.ducking_smoother.current, 1.0);
+  }
+
+  #[test]
+  fn test_mic_monitor_plays_the_newest_input_and_fades_on_underrun() {
+    let frames = 256;
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    state.microphone.monitor = true;
+    state.channel_config.cue_channels = [Some(0), Some(1)];
+
+    // A backlog is skipped down to this callback plus the slack, not played late
+    let mut mixer = mic_monitor_mixer(&state, DEFAULT_SAMPLE_RATE as usize / 20, 0.5);
+    let mut output = vec![0.0; frames * 2];
+    mixer.mix(&mut output, 2, DEFAULT_SAMPLE_RATE);
+    let slack = (DEFAULT_SAMPLE_RATE as f32 * MIC_MONITOR_SLACK_MS / 1000.0) as usize;
+    assert_eq!(mixer.input.slots(), slack * 2);
+
+    // Running dry mid-callback fades out from the last frame instead of dropping to silence
+    let mut mixer = mic_monitor_mixer(&state, frames / 2, 0.5);
+    mixer.gain_smoother = ParamSmoother::new(1.0);
+    let mut output = vec![0.0; frames * 2];
+    mixer.mix(&mut output, 2, DEFAULT_SAMPLE_RATE);
+    let left: Vec<f32> = output.chunks_exact(2).map(|frame| frame[0]).collect();
+    assert_eq!(left[frames / 2 - 1], 0.5);
+    assert!(left[frames / 2] > 0.49);
+    assert!(left[frames / 2..].windows(2).all(|w| w[1] < w[0]));
+    assert!(left[frames - 1] < 0.25);
+  }
+
+  #[test]
+  fn test_mic_monitor_is_silent_while_paused() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    state.microphone.monitor = true;
+    state.channel_config.cue_channels = [Some(0), Some(1)];
+    state.paused = true;
+    let mut mixer = mic_monitor_mixer(&state, 1024, 0.5);
+    let mut output = vec![0.0; 512 * 2];
+    mixer.mix(&mut output, 2, DEFAULT_SAMPLE_RATE);
+    assert!(output.iter().all(|&s| s == 0.0));
+    // Nothing stale is left to play on resume
+    assert_eq!(mixer.input.slots(), 0);
+  }
+
+  #[test]
+  fn test_peak_hold_follows_manual_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let mut state = EngineState::with_clock(DEFAULT_SAMPLE_RATE, clock.clone());
+    state.levels.deck_a_peak = 1.0;
+    state.update_peak_hold();
+    state.levels.deck_a_peak = 0.0;
+
+    // Held for the hold time...
+    clock.advance(Duration::from_secs(1));
+    state.update_peak_hold();
+    assert_eq!(state.levels.deck_a_peak_hold, 1.0);
+
+    // ...then falling 6 dB per second
+    clock.advance(Duration::from_millis(1500));
+    state.update_peak_hold();
+    let hold_db = 20.0 * state.levels.deck_a_peak_hold.log10();
+    assert!((hold_db + 6.0).abs() < 1e-3, "{hold_db}");
+  }
+
+  /// Advance the clock and render what a device would have pulled by then (whole chunks)
+  fn run_for(state: &mut EngineState, clock: &ManualClock, duration: Duration) {
+    clock.advance(duration);
+    let elapsed = clock.now().duration_since(clock.start).as_secs_f64();
+    let due = (elapsed * DEFAULT_SAMPLE_RATE as f64) as u64;
+    while state.frame_clock < due {
+      process_audio_chunk(state, DEFAULT_SAMPLE_RATE, DEFAULT_CHANNELS);
+    }
+  }
+
+  #[test]
+  fn test_manual_clock_drives_scheduled_play_and_crossfade() {
+    let clock = Arc::new(ManualClock::new());
+    let mut state = EngineState::with_clock(DEFAULT_SAMPLE_RATE, clock.clone());
+    install_track(&mut state, 1, Arc::new(tone(8.0, 0)), None, None);
+    install_track(&mut state, 2, Arc::new(tone(8.0, 1)), None, None);
+    state.deck_a.playing = true;
+    state.crossfade.position = 0.0;
+
+    // At 120 BPM, beat 4 is two seconds out; a two-second crossfade is queued with it
+    state.master_tempo = 120.0;
+    state
+      .schedule(4.0, Some(2), ScheduledKind::Play, DEFAULT_SAMPLE_RATE)
+      .unwrap();
+    let crossfade = ScheduledKind::Crossfade {
+      target_position: Some(1.0),
+      total_frames: 2 * DEFAULT_SAMPLE_RATE as usize,
+    };
+    state
+      .schedule(4.0, None, crossfade, DEFAULT_SAMPLE_RATE)
+      .unwrap();
+
+    run_for(&mut state, &clock, Duration::from_millis(1900));
+    assert!(!state.deck_b.playing);
+    assert!(!state.crossfade.active);
+
+    run_for(&mut state, &clock, Duration::from_millis(200));
+    assert!(state.deck_b.playing);
+    assert!(state.crossfade.active);
+
+    // Halfway through the crossfade one second later...
+    run_for(&mut state, &clock, Duration::from_secs(1));
+    assert!((state.crossfade.position - 0.5).abs() < 0.1);
+
+    // ...and done after the rest of it
+    run_for(&mut state, &clock, Duration::from_millis(1100));
+    assert!(!state.crossfade.active);
+    assert_eq!(state.crossfade.position, 1.0);
+    assert!(!state.deck_a.playing);
+  }
+
+  #[test]
+  fn test_imported_beat_grid_phase() {
+    let sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut deck = DeckState::new(sample_rate);
+    deck.bpm = Some(120.0);
+    // Tempo drifts: the third beat comes late
+    deck.beat_grid = vec![0.5, 1.0, 1.6, 2.0];
+    let at = |seconds: f64| (seconds * sample_rate as f64).round() as usize;
+
+    deck.position = at(1.3);
+    assert!((deck.beat_phase(sample_rate).unwrap() - 1.5).abs() < 1e-3);
+    deck.position = at(1.45);
+    assert_eq!(deck.nearest_beat_frame(sample_rate), Some(at(1.6)));
+
+    // Extended at the BPM past the last beat
+    deck.position = at(2.25);
+    assert!((deck.beat_phase(sample_rate).unwrap() - 3.5).abs() < 1e-3);
+    assert_eq!(deck.nearest_beat_frame(sample_rate), Some(at(2.5)));
+  }
+
+  #[test]
+  fn test_loading_the_sync_master_resets_its_beat_anchor() {
+    let mut state = EngineState::new(DEFAULT_SAMPLE_RATE);
+    install_track(&mut state, 1, Arc::new(tone(2.0, 0)), Some(120.0), None);
+    state.sync_master = Some(1);
+    state.deck_a.first_beat = 0.3;
+
+    install_track(&mut state, 1, Arc::new(tone(2.0, 0)), Some(120.0), None);
+    assert_eq!(state.deck_a.first_beat, 0.0);
+
+    // The master clock follows the new track's grid: beat 0 at its first frame
+    state.deck_a.playing = true;
+    update_sync(&mut state, 0, DEFAULT_SAMPLE_RATE);
+    assert_eq!(state.clock_phase, 0.0);
+  }
+
+  #[test]
+  fn test_beat_jump_follows_grid_and_clamps() {
+    let sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut deck = DeckState::new(sample_rate);
+    deck.pcm_data = Some(Arc::new(tone(10.0, 0)));
+    deck.bpm = Some(120.0);
+    deck.first_beat = 0.25;
+    let at = |seconds: f64| (seconds * sample_rate as f64).round() as usize;
+
+    // Half a second per beat, keeping the offset from the beat
+    deck.position = at(1.0);
+    assert_eq!(deck.beat_jump_frame(4.0, sample_rate), Some(at(3.0)));
+    assert_eq!(deck.beat_jump_frame(-1.0, sample_rate), Some(at(0.5)));
+
+    // Quantize lands on the beat
+    deck.quantize = true;
+    assert_eq!(deck.beat_jump_frame(4.0, sample_rate), Some(at(3.25)));
+
+    // Clamped at both ends of the track
+    assert_eq!(deck.beat_jump_frame(-16.0, sample_rate), Some(0));
+    let last = at(10.0) - 1;
+    assert_eq!(deck.beat_jump_frame(64.0, sample_rate), Some(last));
+
+    deck.bpm = None;
+    assert_eq!(deck.beat_jump_frame(4.0, sample_rate), None);
+  }
+
+  #[test]
+  fn test_roll_resizes_in_place_and_releases_at_slip_position() {
+    let sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut deck = DeckState::new(sample_rate);
+    deck.pcm_data = Some(Arc::new(tone(10.0, 0)));
+    deck.bpm = Some(120.0);
+    deck.playing = true;
+    deck.position = 10_000;
+    let mut output = vec![0.0; 1024 * DEFAULT_CHANNELS as usize];
+    let mut render_chunks = |deck: &mut DeckState, chunks: usize| {
+      for _ in 0..chunks {
+        deck.render(1024, &mut output, 1.0);
+      }
+    };
+
+    // One beat (half a second) from the playhead
+    assert!(deck.roll(1.0, sample_rate));
+    assert_eq!((deck.loop_start, deck.loop_end), (10_000, 32_050));
+    render_chunks(&mut deck, 40);
+    assert!((deck.loop_start..deck.loop_end).contains(&deck.position));
+
+    // A new size restarts the roll from where the track has got to
+    let slip = 10_000 + 40 * 1024;
+    assert!(deck.roll(0.5, sample_rate));
+    assert_eq!(deck.roll.as_ref().map(|roll| roll.beats), Some(0.5));
+    assert_eq!((deck.loop_start, deck.loop_end), (slip, slip + 11_025));
+    render_chunks(&mut deck, 20);
+
+    // Release lands where playback would have been and the loop is gone
+    assert!(deck.roll_off());
+    assert_eq!(deck.position, slip + 20 * 1024);
+    assert!(!deck.loop_enabled);
+    assert!(!deck.roll_off());
+
+    deck.bpm = None;
+    assert!(!deck.roll(1.0, sample_rate));
+  }
+
+  #[test]
+  fn test_waveform_window_caps_bins() {
+    let pcm = tone(1.0, 0);
+    let peaks = waveform_window(&pcm, DEFAULT_SAMPLE_RATE, 0.5, 1.0, u32::MAX as usize);
+    assert_eq!(peaks.len(), MAX_WAVEFORM_BINS);
+    assert!(peaks.iter().all(|&p| p <= 0.5));
+    assert!(peaks.iter().any(|&p| p > 0.4));
+
+    // A window past the end, or of a bad width, is all zeros
+    let beyond = waveform_window(&pcm, DEFAULT_SAMPLE_RATE, 10.0, 1.0, 64);
+    assert!(beyond.iter().all(|&p| p == 0.0));
+    let infinite = waveform_window(&pcm, DEFAULT_SAMPLE_RATE, 0.5, f64::INFINITY, 64);
+    assert!(infinite.iter().all(|&p| p == 0.0));
+  }
+
+  #[test]
+  fn test_latency_profiles_do_not_underrun() {
+    let (sample_rate, channels) = (DEFAULT_SAMPLE_RATE, DEFAULT_CHANNELS);
+    // A common device period: each callback pops this many frames
+    let device_frames = 256;
+    let period = device_frames as u64 * 1_000_000 / sample_rate as u64;
+
+    for profile in [
+      LatencyProfile::LOW,
+      LatencyProfile::BALANCED,
+      LatencyProfile::SAFE,
+    ] {
+      let mut state = EngineState::new(sample_rate);
+      state.latency_profile = profile;
+      install_track(&mut state, 1, Arc::new(tone(4.0, 0)), None, None);
+      state.deck_a.playing = true;
+      let (mut producer, mut consumer) =
+        RingBuffer::new(output_queue_capacity(sample_rate, channels));
+
+      // Interleave the processing thread's wakeups and the device callbacks over two
+      // seconds of simulated time (microseconds)
+      let interval = profile.interval(sample_rate).as_micros() as u64;
+      let mut device_buffer = vec![0.0; device_frames * channels as usize];
+      let (mut next_wake, mut next_callback, mut underruns) = (0, period, 0);
+      while next_callback < 2_000_000 {
+        if next_wake <= next_callback {
+          let queued = producer.buffer().capacity() - producer.slots();
+          if profile.needs_chunk(sample_rate, channels, queued) {
+            let (chunk, _, _) = process_audio_chunk(&mut state, sample_rate, channels);
+            push_output(&mut producer, &chunk, channels);
+          }
+          next_wake += interval;
+        } else {
+          if !pop_output(&mut consumer, &mut device_buffer) {
+            underruns += 1;
+          }
+          next_callback += period;
+        }
+      }
+
+      assert_eq!(
+        underruns, 0,
+        "{} frames per chunk underran",
+        profile.frames_per_chunk
+      );
+    }
+  }
+
+  #[test]
+  fn test_output_resampler_converts_44k1_to_48k() {
+    let (engine_rate, device_rate) = (44_100u32, 48_000u32);
+    let sine = |seconds: f64| (2.0 * std::f64::consts::PI * 1000.0 * seconds).sin() as f32;
+    // Worst sample error against the ideal 1 kHz sine at the device rate
+    let max_error = |quality: ResamplerQuality| {
+      let (mut producer, mut consumer) = RingBuffer::new(engine_rate as usize * 2);
+      for i in 0..engine_rate as usize {
+        let s = sine(i as f64 / engine_rate as f64);
+        producer.push(s).unwrap();
+        producer.push(-s).unwrap();
+      }
+
+      let mut resampler = OutputResampler::new(quality, 2, engine_rate, device_rate);
+      let mut output = vec![0.0f32; device_rate as usize / 2 * 2];
+      assert!(resampler.process(&mut consumer, &mut output));
+      let step = engine_rate as f64 / device_rate as f64;
+      output
+        .chunks_exact(2)
+        .enumerate()
+        .skip(8)
+        .map(|(n, frame)| {
+          // The read position trails the newest queued frame by three engine frames
+          let expected = sine((n as f64 * step - 3.0) / engine_rate as f64);
+          (frame[0] - expected).abs().max((frame[1] + expected).abs())
+        })
+        .fold(0.0f32, f32::max)
+    };
+
+    assert!(max_error(ResamplerQuality::Cubic) < 1e-3);
+    assert!(max_error(ResamplerQuality::Linear) < 5e-3);
+
+    // An empty queue reports the underrun and plays silence
+    let (_producer, mut consumer) = RingBuffer::<f32>::new(16);
+    let mut resampler = OutputResampler::new(ResamplerQuality::Cubic, 2, engine_rate, device_rate);
+    let mut output = vec![1.0f32; 64];
+    assert!(!resampler.process(&mut consumer, &mut output));
+    assert!(output.iter().all(|&s| s == 0.0));
+  }
+}
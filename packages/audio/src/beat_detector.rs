@@ -7,6 +7,23 @@
 use rustfft::{num_complex::Complex, FftPlanner};
 use std::f32::consts::PI;
 
+/// Tempo range considered by the octave decision (BPM)
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+/// Center of the log-Gaussian tempo prior (BPM), one octave wide
+const PREFERRED_BPM: f32 = 120.0;
+/// Onsets per beat above which a tempo is implausibly slow (subdivisions finer than 16ths)
+const MAX_ONSETS_PER_BEAT: f32 = 4.5;
+
+/// Beats per bar used for downbeat alignment
+const BEATS_PER_BAR: f32 = 4.0;
+/// Upper edge of the kick band used for first-kick anchoring (Hz)
+const KICK_MAX_HZ: f32 = 150.0;
+/// How far into the track to look for the first kick (seconds)
+const KICK_SEARCH_SECONDS: f32 = 15.0;
+/// Low-band flux (relative to the track maximum) that counts as a clear kick onset
+const KICK_ONSET_THRESHOLD: f32 = 0.3;
+
 /// Result of beat detection
 pub struct BeatDetectionResult {
     /// Detected BPM
@@ -17,33 +34,113 @@ pub struct BeatDetectionResult {
     pub confidence: f32,
 }
 
+/// Intermediate detection data for plotting and diagnosing mis-detections
+#[derive(Clone, Debug, Default)]
+pub struct BeatDetectionDebug {
+    /// Weighted, normalized combination of the five ODFs
+    pub combined_odf: Vec<f32>,
+    /// ODF frame rate (frames per second)
+    pub odf_sample_rate: f32,
+    /// Tempo (BPM) of each autocorrelation lag, from fastest to slowest
+    pub autocorrelation_bpms: Vec<f32>,
+    /// Autocorrelation of the combined ODF at each lag (normalized by ODF length)
+    pub autocorrelation: Vec<f32>,
+    /// Autocorrelation peaks as (BPM, strength), strongest first
+    pub candidates: Vec<(f32, f32)>,
+}
+
+/// Beat detector options
+#[derive(Clone, Debug)]
+pub struct BeatDetectorConfig {
+    /// Shift the grid so a downbeat lands on the first clear low-frequency onset (the first kick)
+    pub anchor_to_first_kick: bool,
+    /// Relative weight of each ODF in the combination step
+    /// (complex, energy, mel, beat-emphasis, info-gain); renormalized to sum to 1
+    pub odf_weights: [f32; 5],
+    /// Fixed periodicity reference for the beat-emphasis ODF (BPM); None scans the track's
+    /// own strongest periods instead, so no tempo is favored
+    pub emphasis_reference_bpm: Option<f32>,
+    /// How many of the strongest periods the beat-emphasis ODF combines when scanning
+    pub emphasis_periods: usize,
+}
+
+impl Default for BeatDetectorConfig {
+    fn default() -> Self {
+        Self {
+            anchor_to_first_kick: false,
+            odf_weights: [1.0; 5],
+            emphasis_reference_bpm: None,
+            emphasis_periods: 3,
+        }
+    }
+}
+
+/// Onset detection functions of one track, normalized, at a 512-sample hop
+struct OnsetFunctions {
+    complex: Vec<f32>,
+    energy: Vec<f32>,
+    mel: Vec<f32>,
+    beat_emphasis: Vec<f32>,
+    info_gain: Vec<f32>,
+    /// Kick (low), snare (mid) and hi-hat (high) band onset envelopes
+    bands: [Vec<f32>; 3],
+}
+
 /// Multi-feature beat detector (paper-compliant implementation)
 pub struct BeatDetector {
     sample_rate: f32,
     fft_planner: FftPlanner<f32>,
+    config: BeatDetectorConfig,
 }
 
 impl BeatDetector {
     pub fn new(sample_rate: f32) -> Self {
+        Self::with_config(sample_rate, BeatDetectorConfig::default())
+    }
+
+    pub fn with_config(sample_rate: f32, config: BeatDetectorConfig) -> Self {
         Self {
             sample_rate,
             fft_planner: FftPlanner::new(),
+            config,
         }
     }
 
     /// Detect BPM and beat positions from mono audio data
     pub fn detect(&mut self, audio: &[f32]) -> Option<BeatDetectionResult> {
+        self.detect_inner(audio, None)
+    }
+
+    /// Like `detect`, but also return the intermediate ODF and tempo candidates
+    /// The debug data is filled as far as detection got, even when it fails
+    pub fn detect_with_debug(
+        &mut self,
+        audio: &[f32],
+    ) -> (Option<BeatDetectionResult>, BeatDetectionDebug) {
+        let mut debug = BeatDetectionDebug::default();
+        let result = self.detect_inner(audio, Some(&mut debug));
+        (result, debug)
+    }
+
+    fn detect_inner(
+        &mut self,
+        audio: &[f32],
+        mut debug: Option<&mut BeatDetectionDebug>,
+    ) -> Option<BeatDetectionResult> {
         if audio.len() < self.sample_rate as usize * 2 {
             return None;
         }
 
         // Step 1: Compute multiple onset detection functions (paper Section III)
-        // Use consistent hop_size = 512 for all ODFs
-        let odf_complex = self.compute_complex_spectral_diff(audio);
-        let odf_energy = self.compute_energy_flux(audio);
-        let odf_mel = self.compute_mel_spectral_flux(audio);
-        let odf_beat_emphasis = self.compute_beat_emphasis(audio);
-        let odf_infogain = self.compute_info_gain(audio);
+        // Use consistent hop_size = 512 for all ODFs, all from one STFT pass
+        let OnsetFunctions {
+            complex: odf_complex,
+            energy: odf_energy,
+            mel: odf_mel,
+            beat_emphasis: odf_beat_emphasis,
+            info_gain: odf_infogain,
+            bands,
+        } = self.compute_onset_functions(audio);
 
         // Step 2: Combine ODFs (weighted sum)
         let min_len = [
@@ -61,15 +158,26 @@ impl BeatDetector {
             return None;
         }
 
+        // Renormalize weights; fall back to equal weighting if they're unusable
+        let mut weights = self
+            .config
+            .odf_weights
+            .map(|w| if w.is_finite() { w.max(0.0) } else { 0.0 });
+        let weight_sum: f32 = weights.iter().sum();
+        if weight_sum > 0.0 {
+            weights.iter_mut().for_each(|w| *w /= weight_sum);
+        } else {
+            weights = [0.2; 5];
+        }
+
+        let odfs = [&odf_complex, &odf_energy, &odf_mel, &odf_beat_emphasis, &odf_infogain];
         let mut combined_odf = vec![0.0f32; min_len];
         for i in 0..min_len {
-            // Weight each ODF equally
-            combined_odf[i] = (odf_complex.get(i).unwrap_or(&0.0)
-                + odf_energy.get(i).unwrap_or(&0.0)
-                + odf_mel.get(i).unwrap_or(&0.0)
-                + odf_beat_emphasis.get(i).unwrap_or(&0.0)
-                + odf_infogain.get(i).unwrap_or(&0.0))
-                / 5.0;
+            combined_odf[i] = odfs
+                .iter()
+                .zip(weights.iter())
+                .map(|(odf, w)| odf.get(i).unwrap_or(&0.0) * w)
+                .sum();
         }
 
         // Normalize combined ODF
@@ -83,16 +191,14 @@ impl BeatDetector {
         // Step 3: Estimate tempo from combined ODF
         let hop_size = 512;
         let odf_sr = self.sample_rate / hop_size as f32;
-        let (bpm, _tempo_confidence) = self.estimate_tempo_from_odf(&combined_odf)?;
-
-        // Refine BPM to typical DJ range (80-170) first
-        let mut refined_bpm = bpm;
-        while refined_bpm < 80.0 {
-            refined_bpm *= 2.0;
-        }
-        while refined_bpm > 170.0 {
-            refined_bpm /= 2.0;
+        if let Some(debug) = debug.as_deref_mut() {
+            debug.combined_odf = combined_odf.clone();
+            debug.odf_sample_rate = odf_sr;
         }
+        let (bpm, _tempo_confidence) = self.estimate_tempo_from_odf(&combined_odf, debug)?;
+
+        // Resolve half/double tempo ambiguity using band onsets and onset density
+        let refined_bpm = self.choose_tempo_octave(bpm, &bands, odf_sr);
         // Round BPM to 2 decimal places (like Mixxx)
         let refined_bpm = (refined_bpm * 100.0).round() / 100.0;
 
@@ -112,6 +218,16 @@ impl BeatDetector {
         // Find the best phase offset by voting from detected beats
         let first_beat = self.find_optimal_first_beat(&detected_beats, beat_interval);
 
+        // Optionally re-anchor so a downbeat lands on the first kick
+        // The grid keeps whole bars before the kick so bar boundaries stay aligned
+        let first_beat = if self.config.anchor_to_first_kick {
+            self.find_first_kick(audio)
+                .map(|kick| kick % (beat_interval * BEATS_PER_BAR))
+                .unwrap_or(first_beat)
+        } else {
+            first_beat
+        };
+
         // Step 6: Generate constant-tempo beat grid from first beat
         let beats = self.generate_beat_grid(first_beat, beat_interval, duration);
 
@@ -125,95 +241,56 @@ impl BeatDetector {
         })
     }
 
-    /// Complex Spectral Difference (paper Section III.A.1)
-    /// Measures changes in both magnitude and phase of FFT
-    fn compute_complex_spectral_diff(&mut self, audio: &[f32]) -> Vec<f32> {
+    /// All onset detection functions from a single STFT pass (frame 2048, hop 512, Hann)
+    /// Every frame is windowed and transformed once and feeds each ODF in turn
+    fn compute_onset_functions(&mut self, audio: &[f32]) -> OnsetFunctions {
         let frame_size = 2048;
         let hop_size = 512; // Unified hop size
         let num_frames = (audio.len().saturating_sub(frame_size)) / hop_size;
+        let num_mel_bands = 40;
+        let num_bins = 20; // Information gain histogram bins
 
         let fft = self.fft_planner.plan_fft_forward(frame_size);
         let window = self.hann_window(frame_size);
+        let mel_filterbank = self.create_mel_filterbank(frame_size, num_mel_bands);
+        let bin_hz = self.sample_rate / frame_size as f32;
+        let band_edges = [(20.0, 150.0), (300.0, 3000.0), (5000.0, self.sample_rate / 2.0)];
+        let band_bins: Vec<(usize, usize)> = band_edges
+            .iter()
+            .map(|&(lo, hi)| ((lo / bin_hz) as usize, ((hi / bin_hz) as usize).min(frame_size / 2)))
+            .collect();
+
+        let mut complex = Vec::with_capacity(num_frames);
+        let mut energy_flux = Vec::with_capacity(num_frames);
+        let mut mel = Vec::with_capacity(num_frames);
+        let mut spectral_flux = Vec::with_capacity(num_frames);
+        let mut info_gain = Vec::with_capacity(num_frames);
+        let mut bands: [Vec<f32>; 3] = Default::default();
 
         let mut prev_spectrum: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); frame_size];
         let mut prev_prev_spectrum: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); frame_size];
-        let mut odf = Vec::with_capacity(num_frames * 2); // Will upsample 2x
-
-        for i in 0..num_frames {
-            let start = i * hop_size;
-            let mut buffer: Vec<Complex<f32>> = audio[start..start + frame_size]
-                .iter()
-                .zip(window.iter())
-                .map(|(&s, &w)| Complex::new(s * w, 0.0))
-                .collect();
-
-            fft.process(&mut buffer);
-
-            // Complex spectral difference: compare predicted phase with actual
-            let mut diff = 0.0f32;
-            for k in 0..frame_size / 2 {
-                // Predicted spectrum (linear extrapolation of phase)
-                let predicted = prev_spectrum[k] * 2.0 - prev_prev_spectrum[k];
-                let actual = buffer[k];
-                diff += (actual - predicted).norm();
-            }
-
-            odf.push(diff);
-
-            prev_prev_spectrum = prev_spectrum;
-            prev_spectrum = buffer;
-        }
-
-        self.normalize_and_smooth(&mut odf);
-        odf
-    }
-
-    /// Energy Flux / RMS onset detection (paper Section III.A.2)
-    fn compute_energy_flux(&mut self, audio: &[f32]) -> Vec<f32> {
-        let frame_size = 2048;
-        let hop_size = 512; // Unified hop size
-        let num_frames = (audio.len().saturating_sub(frame_size)) / hop_size;
-
-        let window = self.hann_window(frame_size);
         let mut prev_energy = 0.0f32;
-        let mut odf = Vec::with_capacity(num_frames);
+        let mut prev_mag_spectrum = vec![0.0f32; frame_size / 2];
+        let mut prev_mel_spectrum = vec![0.0f32; num_mel_bands];
+        let mut prev_histogram = vec![0.0f32; num_bins];
+        let mut prev_band = [0.0f32; 3];
 
         for i in 0..num_frames {
             let start = i * hop_size;
-            let energy: f32 = audio[start..start + frame_size]
+            let frame = &audio[start..start + frame_size];
+
+            // Energy Flux / RMS onset detection (paper Section III.A.2)
+            let energy: f32 = frame
                 .iter()
                 .zip(window.iter())
                 .map(|(&s, &w)| (s * w).powi(2))
                 .sum();
             let energy = energy.sqrt();
-
             // Half-wave rectified difference
-            let flux = (energy - prev_energy).max(0.0);
-            odf.push(flux);
+            energy_flux.push((energy - prev_energy).max(0.0));
             prev_energy = energy;
-        }
 
-        self.normalize_and_smooth(&mut odf);
-        odf
-    }
-
-    /// Mel-frequency Spectral Flux (paper Section III.A.3)
-    fn compute_mel_spectral_flux(&mut self, audio: &[f32]) -> Vec<f32> {
-        let frame_size = 2048;
-        let hop_size = 512; // Unified hop size
-        let num_frames = (audio.len().saturating_sub(frame_size)) / hop_size;
-        let num_mel_bands = 40;
-
-        let fft = self.fft_planner.plan_fft_forward(frame_size);
-        let window = self.hann_window(frame_size);
-        let mel_filterbank = self.create_mel_filterbank(frame_size, num_mel_bands);
-
-        let mut prev_mel_spectrum = vec![0.0f32; num_mel_bands];
-        let mut odf = Vec::with_capacity(num_frames);
-
-        for i in 0..num_frames {
-            let start = i * hop_size;
-            let mut buffer: Vec<Complex<f32>> = audio[start..start + frame_size]
+            let mut buffer: Vec<Complex<f32>> = frame
                 .iter()
                 .zip(window.iter())
                 .map(|(&s, &w)| Complex::new(s * w, 0.0))
@@ -221,13 +298,32 @@ impl BeatDetector {
 
             fft.process(&mut buffer);
 
+            // Complex Spectral Difference (paper Section III.A.1):
+            // compare predicted phase with actual
+            let mut diff = 0.0f32;
+            for k in 0..frame_size / 2 {
+                // Predicted spectrum (linear extrapolation of phase)
+                let predicted = prev_spectrum[k] * 2.0 - prev_prev_spectrum[k];
+                let actual = buffer[k];
+                diff += (actual - predicted).norm();
+            }
+            complex.push(diff);
+
             // Compute magnitude spectrum
             let mag_spectrum: Vec<f32> = buffer[..frame_size / 2]
                 .iter()
                 .map(|c| c.norm())
                 .collect();
 
-            // Apply mel filterbank
+            // Linear spectral flux, the input of the beat emphasis
+            let flux: f32 = mag_spectrum
+                .iter()
+                .zip(prev_mag_spectrum.iter())
+                .map(|(&curr, &prev)| (curr - prev).max(0.0))
+                .sum();
+            spectral_flux.push(flux);
+
+            // Mel-frequency Spectral Flux (paper Section III.A.3)
             let mel_spectrum: Vec<f32> = mel_filterbank
                 .iter()
                 .map(|filter| {
@@ -240,116 +336,16 @@ impl BeatDetector {
                         .max(0.0)
                 })
                 .collect();
-
-            // Spectral flux: sum of positive differences
             let flux: f32 = mel_spectrum
                 .iter()
                 .zip(prev_mel_spectrum.iter())
                 .map(|(&curr, &prev)| (curr - prev).max(0.0))
                 .sum();
-
-            odf.push(flux);
+            mel.push(flux);
             prev_mel_spectrum = mel_spectrum;
-        }
-
-        self.normalize_and_smooth(&mut odf);
-        odf
-    }
-
-    /// Beat Emphasis Function (paper Section III.A.4)
-    /// Emphasizes periodic beat patterns
-    fn compute_beat_emphasis(&mut self, audio: &[f32]) -> Vec<f32> {
-        let frame_size = 2048;
-        let hop_size = 512;
-        let num_frames = (audio.len().saturating_sub(frame_size)) / hop_size;
-
-        let fft = self.fft_planner.plan_fft_forward(frame_size);
-        let window = self.hann_window(frame_size);
-
-        // First compute spectral flux
-        let mut prev_spectrum = vec![0.0f32; frame_size / 2];
-        let mut spectral_flux = Vec::with_capacity(num_frames);
-
-        for i in 0..num_frames {
-            let start = i * hop_size;
-            let end = (start + frame_size).min(audio.len());
-            if end - start < frame_size {
-                break;
-            }
-
-            let mut buffer: Vec<Complex<f32>> = audio[start..start + frame_size]
-                .iter()
-                .zip(window.iter())
-                .map(|(&s, &w)| Complex::new(s * w, 0.0))
-                .collect();
-
-            fft.process(&mut buffer);
-
-            let mag_spectrum: Vec<f32> = buffer[..frame_size / 2]
-                .iter()
-                .map(|c| c.norm())
-                .collect();
-
-            let flux: f32 = mag_spectrum
-                .iter()
-                .zip(prev_spectrum.iter())
-                .map(|(&curr, &prev)| (curr - prev).max(0.0))
-                .sum();
-
-            spectral_flux.push(flux);
-            prev_spectrum = mag_spectrum;
-        }
-
-        // Apply beat emphasis: weight by periodicity
-        let odf_sr = self.sample_rate / hop_size as f32;
-        let beat_period_samples = (60.0 / 120.0 * odf_sr) as usize; // Reference: 120 BPM
-
-        let mut odf = vec![0.0f32; spectral_flux.len()];
-        for i in beat_period_samples..spectral_flux.len() {
-            // Correlation with previous beat position
-            let emphasis = spectral_flux[i] * spectral_flux[i - beat_period_samples];
-            odf[i] = emphasis.sqrt();
-        }
-
-        self.normalize_and_smooth(&mut odf);
-        odf
-    }
-
-    /// Information Gain (paper Section III.A.5)
-    /// Measures spectral change using histogram-based entropy
-    fn compute_info_gain(&mut self, audio: &[f32]) -> Vec<f32> {
-        let frame_size = 2048;
-        let hop_size = 512;
-        let num_frames = (audio.len().saturating_sub(frame_size)) / hop_size;
-        let num_bins = 20; // Histogram bins
-
-        let fft = self.fft_planner.plan_fft_forward(frame_size);
-        let window = self.hann_window(frame_size);
-
-        let mut prev_histogram = vec![0.0f32; num_bins];
-        let mut odf = Vec::with_capacity(num_frames);
-
-        for i in 0..num_frames {
-            let start = i * hop_size;
-            let end = (start + frame_size).min(audio.len());
-            if end - start < frame_size {
-                break;
-            }
-
-            let mut buffer: Vec<Complex<f32>> = audio[start..start + frame_size]
-                .iter()
-                .zip(window.iter())
-                .map(|(&s, &w)| Complex::new(s * w, 0.0))
-                .collect();
-
-            fft.process(&mut buffer);
-
-            // Compute magnitude spectrum and histogram
-            let mag_spectrum: Vec<f32> = buffer[..frame_size / 2]
-                .iter()
-                .map(|c| c.norm())
-                .collect();
 
+            // Information Gain (paper Section III.A.5):
+            // spectral change as histogram-based entropy
             let max_mag = mag_spectrum.iter().cloned().fold(0.0f32, f32::max);
             let mut histogram = vec![0.0f32; num_bins];
 
@@ -369,23 +365,116 @@ impl BeatDetector {
             }
 
             // Information gain: KL divergence from previous histogram
-            let mut info_gain = 0.0f32;
+            let mut gain = 0.0f32;
             for (curr, prev) in histogram.iter().zip(prev_histogram.iter()) {
                 if *curr > 0.0 && *prev > 0.0 {
-                    info_gain += curr * (curr / prev).ln();
+                    gain += curr * (curr / prev).ln();
                 }
             }
-
-            odf.push(info_gain.max(0.0));
+            info_gain.push(gain.max(0.0));
             prev_histogram = histogram;
+
+            // Onset envelopes for the kick (low), snare (mid) and hi-hat (high) bands
+            for (band, &(lo, hi)) in band_bins.iter().enumerate() {
+                let energy = mag_spectrum[lo..hi].iter().sum::<f32>();
+                bands[band].push((energy - prev_band[band]).max(0.0));
+                prev_band[band] = energy;
+            }
+
+            prev_mag_spectrum = mag_spectrum;
+            prev_prev_spectrum = prev_spectrum;
+            prev_spectrum = buffer;
+        }
+
+        let beat_emphasis = self.compute_beat_emphasis(&spectral_flux);
+        for odf in [&mut complex, &mut energy_flux, &mut mel, &mut info_gain] {
+            self.normalize_and_smooth(odf);
+        }
+        for band in bands.iter_mut() {
+            let max_val = band.iter().cloned().fold(0.0f32, f32::max);
+            if max_val > 0.0 {
+                band.iter_mut().for_each(|v| *v /= max_val);
+            }
+        }
+
+        OnsetFunctions {
+            complex,
+            energy: energy_flux,
+            mel,
+            beat_emphasis,
+            info_gain,
+            bands,
+        }
+    }
+
+    /// Beat Emphasis Function (paper Section III.A.4)
+    /// Emphasizes periodic beat patterns in the spectral flux
+    fn compute_beat_emphasis(&self, spectral_flux: &[f32]) -> Vec<f32> {
+        let hop_size = 512;
+
+        // Apply beat emphasis: weight by periodicity
+        let odf_sr = self.sample_rate / hop_size as f32;
+        let mut odf = vec![0.0f32; spectral_flux.len()];
+        for (period, weight) in self.emphasis_periods(spectral_flux, odf_sr) {
+            for i in period..spectral_flux.len() {
+                // Correlation with the position one period earlier
+                let emphasis = spectral_flux[i] * spectral_flux[i - period];
+                odf[i] += weight * emphasis.sqrt();
+            }
         }
 
         self.normalize_and_smooth(&mut odf);
         odf
     }
 
+    /// Periods (in ODF frames) and weights for the beat emphasis: the configured reference
+    /// tempo, or the strongest autocorrelation peaks of the flux between MIN_BPM and MAX_BPM
+    fn emphasis_periods(&self, flux: &[f32], odf_sr: f32) -> Vec<(usize, f32)> {
+        let period = |bpm: f32| (60.0 / bpm * odf_sr).round() as usize;
+        if let Some(bpm) = self.config.emphasis_reference_bpm.filter(|&b| b > 0.0) {
+            return vec![(period(bpm).max(1), 1.0)];
+        }
+
+        let min_lag = period(MAX_BPM).max(1);
+        let max_lag = period(MIN_BPM).min(flux.len() / 2);
+        if max_lag <= min_lag {
+            return Vec::new();
+        }
+
+        // Mean-removed autocorrelation, so short lags don't win on the flux offset alone
+        let mean = flux.iter().sum::<f32>() / flux.len() as f32;
+        let centered: Vec<f32> = flux.iter().map(|&f| f - mean).collect();
+        let scores: Vec<f32> = (min_lag - 1..=max_lag + 1)
+            .map(|lag| {
+                let sum: f32 = centered[lag..]
+                    .iter()
+                    .zip(&centered)
+                    .map(|(&a, &b)| a * b)
+                    .sum();
+                sum / (flux.len() - lag) as f32
+            })
+            .collect();
+
+        let mut peaks: Vec<(usize, f32)> = (1..scores.len() - 1)
+            .filter(|&i| scores[i] > 0.0 && scores[i] >= scores[i - 1] && scores[i] > scores[i + 1])
+            .map(|i| (min_lag - 1 + i, scores[i]))
+            .collect();
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        peaks.truncate(self.config.emphasis_periods.max(1));
+
+        let total: f32 = peaks.iter().map(|&(_, score)| score).sum();
+        peaks
+            .into_iter()
+            .map(|(lag, score)| (lag, score / total))
+            .collect()
+    }
+
     /// Estimate tempo using autocorrelation
-    fn estimate_tempo_from_odf(&self, odf: &[f32]) -> Option<(f32, f32)> {
+    fn estimate_tempo_from_odf(
+        &self,
+        odf: &[f32],
+        debug: Option<&mut BeatDetectionDebug>,
+    ) -> Option<(f32, f32)> {
         let hop_size = 512;
         let odf_sr = self.sample_rate / hop_size as f32;
 
@@ -419,6 +508,18 @@ impl BeatDetector {
             }
         }
 
+        if let Some(debug) = debug {
+            let norm = odf.len() as f32;
+            let to_bpm = |lag: usize| 60.0 / (lag as f32 / odf_sr);
+            debug.autocorrelation_bpms = correlations.iter().map(|&(lag, _)| to_bpm(lag)).collect();
+            debug.autocorrelation = correlations.iter().map(|&(_, corr)| corr / norm).collect();
+            debug.candidates = peaks
+                .iter()
+                .map(|&(lag, corr)| (to_bpm(lag), corr / norm))
+                .collect();
+            debug.candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        }
+
         if peaks.is_empty() {
             // Fallback to max
             let (best_lag, max_corr) = correlations
@@ -429,34 +530,85 @@ impl BeatDetector {
             return Some((bpm, max_corr / odf.len() as f32));
         }
 
-        // Sort peaks by correlation strength
-        peaks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // Take the strongest peak; half/double ambiguity is resolved later by
+        // choose_tempo_octave, which has more to go on than a fixed BPM window
+        let (best_lag, best_corr) = peaks
+            .iter()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .copied()?;
+        let bpm = 60.0 / (best_lag as f32 / odf_sr);
 
-        // Choose the first peak that gives BPM in preferred range (80-160)
-        // This helps avoid half/double tempo detection
-        let preferred_min = 80.0;
-        let preferred_max = 160.0;
+        Some((bpm, best_corr / odf.len() as f32))
+    }
 
-        for &(lag, corr) in &peaks {
-            let bpm = 60.0 / (lag as f32 / odf_sr);
-            if bpm >= preferred_min && bpm <= preferred_max {
-                return Some((bpm, corr / odf.len() as f32));
+    /// Pick between a tempo and its half/double
+    ///
+    /// Each octave in range is scored by:
+    /// - a log-Gaussian prior around 120 BPM
+    /// - the snare backbeat: mid-band onsets recur every two beats rather than every beat
+    /// - kick/snare alternation: low-band onsets are answered by mid-band onsets one beat later
+    /// - onset density: more than ~4 onsets per beat (finer than 16ths) means the tempo is too slow
+    fn choose_tempo_octave(&self, bpm: f32, bands: &[Vec<f32>; 3], odf_sr: f32) -> f32 {
+        let [low, mid, _] = bands;
+        let onset_rate = Self::onset_rate(bands, odf_sr);
+
+        let mut best = (bpm, f32::MIN);
+        for factor in [0.5, 1.0, 2.0] {
+            let candidate = bpm * factor;
+            if !(MIN_BPM..=MAX_BPM).contains(&candidate) {
+                continue;
+            }
+
+            let period = 60.0 / candidate * odf_sr;
+            let backbeat =
+                Self::periodicity(mid, mid, 2.0 * period) - Self::periodicity(mid, mid, period);
+            let alternation =
+                Self::periodicity(low, mid, period) - Self::periodicity(low, mid, 2.0 * period);
+            let onsets_per_beat = onset_rate * 60.0 / candidate;
+            let density = if onsets_per_beat > MAX_ONSETS_PER_BEAT { 0.5 } else { 1.0 };
+            let prior = (-0.5 * (candidate / PREFERRED_BPM).log2().powi(2)).exp();
+
+            let score = prior * density * (1.0 + backbeat.max(0.0) + alternation.max(0.0));
+            if score > best.1 {
+                best = (candidate, score);
             }
         }
 
-        // If no peak in preferred range, use strongest peak and adjust
-        let (best_lag, best_corr) = peaks[0];
-        let mut bpm = 60.0 / (best_lag as f32 / odf_sr);
+        best.0
+    }
 
-        // Adjust to preferred range
-        while bpm < preferred_min && bpm > 30.0 {
-            bpm *= 2.0;
-        }
-        while bpm > preferred_max && bpm < 300.0 {
-            bpm /= 2.0;
+    /// Normalized cross-correlation of `b` following `a` by `lag` frames (best within ±1 frame)
+    fn periodicity(a: &[f32], b: &[f32], lag: f32) -> f32 {
+        let energy = |x: &[f32]| x.iter().map(|v| v * v).sum::<f32>();
+        let norm = (energy(a) * energy(b)).sqrt();
+        if norm <= 0.0 {
+            return 0.0;
         }
 
-        Some((bpm, best_corr / odf.len() as f32))
+        let center = lag.round() as usize;
+        (center.saturating_sub(1)..=center + 1)
+            .filter(|&l| l > 0 && l < b.len())
+            .map(|l| a.iter().zip(&b[l..]).map(|(x, y)| x * y).sum::<f32>() / norm)
+            .fold(0.0f32, f32::max)
+    }
+
+    /// Onsets per second in the busiest band (local maxima above 20% of the band peak)
+    ///
+    /// Bands are counted separately so quiet hi-hats aren't masked by the kick.
+    fn onset_rate(bands: &[Vec<f32>; 3], odf_sr: f32) -> f32 {
+        bands
+            .iter()
+            .filter(|band| band.len() >= 3)
+            .map(|band| {
+                let threshold = 0.2 * band.iter().cloned().fold(0.0f32, f32::max);
+                let onsets = (1..band.len() - 1)
+                    .filter(|&i| {
+                        band[i] > threshold && band[i] > band[i - 1] && band[i] >= band[i + 1]
+                    })
+                    .count();
+                onsets as f32 / (band.len() as f32 / odf_sr)
+            })
+            .fold(0.0f32, f32::max)
     }
 
     /// Dynamic programming beat tracking (improved)
@@ -553,6 +705,52 @@ impl BeatDetector {
         }.max(0.0)
     }
 
+    /// Find the onset time of the first clear kick (low-band onset) within the search window
+    fn find_first_kick(&self, audio: &[f32]) -> Option<f32> {
+        let hop_size = 512;
+
+        // Two cascaded one-pole low-passes isolate the kick band
+        let coeff = 1.0 - (-2.0 * PI * KICK_MAX_HZ / self.sample_rate).exp();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        let low: Vec<f32> = audio
+            .iter()
+            .map(|&x| {
+                s1 += (x - s1) * coeff;
+                s2 += (s1 - s2) * coeff;
+                s2
+            })
+            .collect();
+
+        // Low-band RMS flux per hop
+        let energies: Vec<f32> = low
+            .chunks(hop_size)
+            .map(|block| (block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32).sqrt())
+            .collect();
+        let flux: Vec<f32> = energies.windows(2).map(|w| (w[1] - w[0]).max(0.0)).collect();
+
+        let max_flux = flux.iter().cloned().fold(0.0f32, f32::max);
+        if max_flux <= 0.0 {
+            return None;
+        }
+
+        // flux[i] is the rise into block i + 1
+        let search_blocks = (KICK_SEARCH_SECONDS * self.sample_rate / hop_size as f32) as usize;
+        let block = flux
+            .iter()
+            .take(search_blocks)
+            .position(|&f| f > KICK_ONSET_THRESHOLD * max_flux)?
+            + 1;
+
+        // Refine to the first sample reaching 10% of the kick's low-band peak
+        let start = (block - 1) * hop_size;
+        let end = (start + 3 * hop_size + (0.05 * self.sample_rate) as usize).min(low.len());
+        let region = &low[start..end];
+        let peak = region.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        let onset = region.iter().position(|s| s.abs() >= peak * 0.1)?;
+
+        Some((start + onset) as f32 / self.sample_rate)
+    }
+
     /// Smooth a histogram using a simple moving average
     fn smooth_histogram(&self, histogram: &[f32]) -> Vec<f32> {
         let window = 5;
@@ -746,4 +944,162 @@ mod tests {
             result.bpm
         );
     }
+
+    /// Synthesize a drum loop; hit positions are in beats within a 4-beat bar
+    fn drum_loop(bpm: f32, kicks: &[f32], snares: &[f32], hats: &[f32]) -> Vec<f32> {
+        let sample_rate = 44100.0;
+        let beat = 60.0 / bpm * sample_rate;
+        let duration_samples = sample_rate as usize * 30;
+        let mut audio = vec![0.0f32; duration_samples];
+        let mut noise_state = 1u32;
+        let mut noise = move || {
+            noise_state = noise_state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (noise_state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+        };
+
+        let mut bar_start = 0.0;
+        while (bar_start as usize) < duration_samples {
+            for (hits, kind) in [(kicks, 0), (snares, 1), (hats, 2)] {
+                for &hit in hits {
+                    let start = (bar_start + hit * beat) as usize;
+                    let mut prev = 0.0f32;
+                    for i in 0..(0.15 * sample_rate) as usize {
+                        if start + i >= duration_samples {
+                            break;
+                        }
+                        let t = i as f32 / sample_rate;
+                        audio[start + i] += match kind {
+                            // 60 Hz kick
+                            0 => 0.8 * (2.0 * PI * 60.0 * t).sin() * (-t / 0.05).exp(),
+                            // Snare: 200 Hz body plus noise
+                            1 => {
+                                let body = 0.3 * (2.0 * PI * 200.0 * t).sin();
+                                (body + 0.4 * noise()) * (-t / 0.04).exp()
+                            }
+                            // Hi-hat: differentiated (high-passed) noise
+                            _ => {
+                                let n = noise();
+                                let hp = n - prev;
+                                prev = n;
+                                0.15 * hp * (-t / 0.01).exp()
+                            }
+                        };
+                    }
+                }
+            }
+            bar_start += 4.0 * beat;
+        }
+
+        audio
+    }
+
+    fn detected_bpm(audio: &[f32]) -> f32 {
+        let mut detector = BeatDetector::new(44100.0);
+        detector.detect(audio).expect("beats detected").bpm
+    }
+
+    #[test]
+    fn test_octave_house_124() {
+        // Four-on-the-floor kick, clap on 2 and 4, hats on the off-beats
+        let audio = drum_loop(124.0, &[0.0, 1.0, 2.0, 3.0], &[1.0, 3.0], &[0.5, 1.5, 2.5, 3.5]);
+        let bpm = detected_bpm(&audio);
+        assert!((bpm - 124.0).abs() < 2.0, "Expected house at ~124 BPM, got {}", bpm);
+    }
+
+    #[test]
+    fn test_octave_dnb_174() {
+        // Two-step: kick on 1 and the and of 3, snare on 2 and 4, eighth hats
+        let eighths: Vec<f32> = (0..8).map(|i| i as f32 * 0.5).collect();
+        let audio = drum_loop(174.0, &[0.0, 2.5], &[1.0, 3.0], &eighths);
+        let bpm = detected_bpm(&audio);
+        assert!((bpm - 174.0).abs() < 3.0, "Expected DnB at ~174 BPM, got {}", bpm);
+    }
+
+    #[test]
+    fn test_octave_hip_hop_85() {
+        // Boom bap: kick on 1 and the and of 3, snare on 2 and 4, eighth hats
+        let eighths: Vec<f32> = (0..8).map(|i| i as f32 * 0.5).collect();
+        let audio = drum_loop(85.0, &[0.0, 2.5], &[1.0, 3.0], &eighths);
+        let bpm = detected_bpm(&audio);
+        assert!((bpm - 85.0).abs() < 2.0, "Expected hip-hop at ~85 BPM, got {}", bpm);
+    }
+
+    #[test]
+    fn test_octave_trap_140() {
+        // Half-time snare on 3, kick on 1 and the and of 3, rolling sixteenth hats
+        let sixteenths: Vec<f32> = (0..16).map(|i| i as f32 * 0.25).collect();
+        let audio = drum_loop(140.0, &[0.0, 2.5], &[2.0], &sixteenths);
+        let bpm = detected_bpm(&audio);
+        assert!((bpm - 140.0).abs() < 2.0, "Expected trap at ~140 BPM, got {}", bpm);
+    }
+
+    #[test]
+    fn test_beat_emphasis_not_pulled_toward_120() {
+        // Judge the beat-emphasis ODF on its own, on grooves well away from 120 BPM
+        let eighths: Vec<f32> = (0..8).map(|i| i as f32 * 0.5).collect();
+        for bpm in [90.0, 160.0] {
+            let audio = drum_loop(bpm, &[0.0, 2.5], &[1.0, 3.0], &eighths);
+            let config = BeatDetectorConfig {
+                odf_weights: [0.0, 0.0, 0.0, 1.0, 0.0],
+                ..Default::default()
+            };
+            let mut detector = BeatDetector::with_config(44100.0, config);
+            let detected = detector.detect(&audio).expect("beats detected").bpm;
+            assert!(
+                (detected - bpm).abs() < 2.0,
+                "Expected ~{} BPM from beat emphasis, got {}",
+                bpm,
+                detected
+            );
+        }
+    }
+
+    #[test]
+    fn test_emphasis_reference_tempo_overrides_scan() {
+        let config = BeatDetectorConfig {
+            emphasis_reference_bpm: Some(90.0),
+            ..Default::default()
+        };
+        let detector = BeatDetector::with_config(44100.0, config);
+        let odf_sr = 44100.0 / 512.0;
+        let flux = vec![1.0; 1000];
+        assert_eq!(detector.emphasis_periods(&flux, odf_sr), vec![(57, 1.0)]);
+    }
+
+    #[test]
+    fn test_anchor_to_first_kick_after_silent_intro() {
+        let sample_rate = 44100.0;
+        let bpm = 120.0;
+        let beat_interval = (60.0 / bpm * sample_rate) as usize;
+        let duration_samples = sample_rate as usize * 30;
+
+        // Two silent beats, then a 60 Hz kick on every beat
+        let kick_start = beat_interval * 2;
+        let mut audio = vec![0.0f32; duration_samples];
+        let mut pos = kick_start;
+        while pos < duration_samples {
+            for i in 0..(0.15 * sample_rate) as usize {
+                if pos + i < duration_samples {
+                    let t = i as f32 / sample_rate;
+                    audio[pos + i] = 0.8 * (2.0 * PI * 60.0 * t).sin() * (-t / 0.05).exp();
+                }
+            }
+            pos += beat_interval;
+        }
+
+        let config = BeatDetectorConfig {
+            anchor_to_first_kick: true,
+            ..Default::default()
+        };
+        let mut detector = BeatDetector::with_config(sample_rate, config);
+        let result = detector.detect(&audio).expect("beats detected");
+
+        let kick_time = kick_start as f32 / sample_rate;
+        assert!(
+            (result.beats[0] - kick_time).abs() < 0.01,
+            "Expected beat 1 on the first kick at {}s, got {}s",
+            kick_time,
+            result.beats[0]
+        );
+    }
 }
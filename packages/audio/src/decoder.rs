@@ -1,20 +1,45 @@
 //! MP3 audio decoder using symphonia with BPM detection and structure analysis
 //!
 //! This module provides:
-//! - MP3 decoding to PCM (stereo + mono)
-//! - BPM detection using onset detection and autocorrelation
+//! - MP3 decoding to PCM (stereo + mono), or at the source rate/channels (passthrough)
+//! - Header-only probing (codec, format, duration) for fast library scans
+//! - Parallel batch decoding across a bounded pool of threads
+//! - DC offset removal and optional leading/trailing silence trimming
+//! - Optional peak or loudness normalization (single makeup gain)
+//! - Optional whole-track tempo warping (SoundTouch) at load
+//! - BPM detection using onset detection and autocorrelation, with a confidence score
 //! - Track structure analysis (intro/main/outro sections)
 
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Task};
 use napi_derive::napi;
+use soundtouch::{Setting, SoundTouch};
+use std::borrow::Cow;
 use std::fs::File;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::DecoderOptions;
-use symphonia::core::formats::FormatOptions;
+use symphonia::core::formats::{FormatOptions, FormatReader, Track};
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+use crate::loudness::integrated_loudness;
+
+/// Level below which a sample counts as silence (-60 dBFS)
+const SILENCE_THRESHOLD: f32 = 0.001;
+/// Audio kept on either side of the detected content when trimming (seconds)
+const SILENCE_GUARD_SECONDS: f64 = 0.01;
+/// Cutoff of the DC-blocking high-pass (Hz)
+const DC_BLOCK_CUTOFF_HZ: f64 = 5.0;
+/// Mono frames per bin of the band-colored waveform (~11.6 ms at 44.1 kHz)
+const WAVEFORM_BIN_FRAMES: usize = 512;
+/// Rate BPM and structure analysis run at when the source rate is passed through
+const ANALYSIS_SAMPLE_RATE: u32 = 44100;
+
 /// Track section (intro, main, or outro)
 #[napi(object)]
 pub struct TrackSection {
@@ -40,26 +65,531 @@ pub struct DecodeResult {
     /// Interleaved stereo PCM data (Float32)
     pub pcm: Buffer,
     /// Mono PCM data for waveform display (Float32)
+    /// The channel average, or the per-frame channel peak with decodeAudio's peakWaveform
     pub mono: Buffer,
     /// Detected BPM (if successful)
     pub bpm: Option<f64>,
+    /// How sure the BPM detection is, 0-1: the autocorrelation peak's prominence combined with
+    /// how well the onsets line up with the detected grid. Clear beats score about 0.7 and up;
+    /// below about 0.5 the tempo is doubtful and worth verifying or tapping. Half/double tempo
+    /// mistakes still line up with the grid, so they aren't caught by this score
+    pub bpm_confidence: Option<f64>,
     /// Track structure analysis (if BPM detected)
     pub structure: Option<TrackStructure>,
-    /// Output sample rate
+    /// Output sample rate (the source rate when targetSampleRate is 0)
     pub sample_rate: u32,
-    /// Number of channels (always 2 for stereo output)
+    /// Number of channels (the source channel count when targetChannels is 0)
     pub channels: u32,
+    /// Tempo ratio applied by decodeAudioWarped (target BPM / source BPM)
+    pub stretch_ratio: Option<f64>,
+    /// Seconds of leading silence removed by trimSilence (add to times to map back to the file)
+    pub trim_offset: Option<f64>,
+    /// Makeup gain applied by normalization (dB)
+    pub normalize_gain_db: Option<f64>,
+    /// Low band (below 250 Hz) peak per waveform bin (Float32)
+    pub waveform_low: Buffer,
+    /// Mid band (250 Hz - 5 kHz) peak per waveform bin (Float32)
+    pub waveform_mid: Buffer,
+    /// High band (above 5 kHz) peak per waveform bin (Float32)
+    pub waveform_high: Buffer,
+    /// Mono frames per waveform bin (bin i starts at frame i * waveformBinFrames)
+    pub waveform_bin_frames: u32,
+}
+
+/// Decode-time normalization target; set one of the two
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct NormalizeMode {
+    /// Normalize the sample peak to this level (dBFS)
+    pub peak_to: Option<f64>,
+    /// Normalize integrated loudness to this level (LUFS); takes precedence over peakTo
+    /// The gain is capped so the sample peak stays at or below 0 dBFS
+    pub loudness_to: Option<f64>,
+}
+
+/// Intro/outro conventions for structure detection; unset fields keep the defaults
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct StructureOptions {
+    /// Intro/outro length used when no energy change marks the boundary (beats, default 16)
+    pub section_beats: Option<u32>,
+    /// How far from the start (and back from the end) to look for the intro end and outro start
+    /// (beats, default twice sectionBeats)
+    pub search_beats: Option<u32>,
+}
+
+/// PCM post-processing and analysis options shared by the decode functions (all off by default)
+#[napi(object)]
+#[derive(Clone, Copy, Default)]
+pub struct DecodeOptions {
+    /// Strip leading/trailing silence before analysis (offset reported as trimOffset)
+    pub trim_silence: Option<bool>,
+    /// High-pass the PCM at 5 Hz to remove any DC offset
+    pub remove_dc: Option<bool>,
+    /// Apply one makeup gain (reported as normalizeGainDb)
+    pub normalize: Option<NormalizeMode>,
+    /// Fill `mono` with each frame's largest-magnitude channel sample instead of the channel
+    /// average, so out-of-phase stereo doesn't cancel in the display (analysis is unchanged)
+    pub peak_waveform: Option<bool>,
+    /// Intro/outro length and search window for sections and hot cues
+    pub structure: Option<StructureOptions>,
+}
+
+/// Stream info read from the container headers by probeAudio
+#[napi(object)]
+pub struct AudioProbe {
+    /// Codec short name (e.g. "mp3", "flac", "vorbis")
+    pub codec: String,
+    /// Source sample rate (Hz)
+    pub sample_rate: Option<u32>,
+    /// Source channel count
+    pub channels: Option<u32>,
+    /// Duration in seconds (absent if the stream doesn't declare its length)
+    pub duration: Option<f64>,
+    /// Average bitrate from file size and duration (kbit/s)
+    pub bitrate: Option<f64>,
+}
+
+/// Machine-readable decode failure, surfaced as the JS error's `code`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeErrorCode {
+    FileNotFound,
+    /// The file exists but couldn't be opened (permissions, a directory, ...)
+    Unreadable,
+    UnsupportedFormat,
+    CorruptData,
+    NoAudioTrack,
+    EmptyDecode,
+    Cancelled,
+    TempoNotDetected,
+    /// A parameter is out of range (e.g. a non-positive target BPM)
+    InvalidArgument,
+}
+
+impl AsRef<str> for DecodeErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            DecodeErrorCode::FileNotFound => "FileNotFound",
+            DecodeErrorCode::Unreadable => "Unreadable",
+            DecodeErrorCode::UnsupportedFormat => "UnsupportedFormat",
+            DecodeErrorCode::CorruptData => "CorruptData",
+            DecodeErrorCode::NoAudioTrack => "NoAudioTrack",
+            DecodeErrorCode::EmptyDecode => "EmptyDecode",
+            DecodeErrorCode::Cancelled => "Cancelled",
+            DecodeErrorCode::TempoNotDetected => "TempoNotDetected",
+            DecodeErrorCode::InvalidArgument => "InvalidArgument",
+        }
+    }
 }
 
 /// Decode an MP3 file and return PCM data with BPM and structure analysis
+/// A target_sample_rate or target_channels of 0 keeps the source's (passthrough, no resampling
+/// or remapping); analysis then runs on a 44.1 kHz copy of the mono mixdown
+/// `options` turns on silence trimming, DC removal, normalization, the peak waveform and
+/// sets the structure conventions (see DecodeOptions)
+/// Errors carry a `code`: FileNotFound, Unreadable, UnsupportedFormat, CorruptData, NoAudioTrack
+/// or EmptyDecode
 #[napi]
 pub fn decode_audio(
     mp3_path: String,
     target_sample_rate: u32,
     target_channels: u32,
-) -> Result<DecodeResult> {
-    // Open the file
-    let file = File::open(&mp3_path).map_err(|e| Error::from_reason(format!("Failed to open file: {}", e)))?;
+    options: Option<DecodeOptions>,
+) -> Result<DecodeResult, DecodeErrorCode> {
+    let options = options.unwrap_or_default();
+    decode(&mp3_path, target_sample_rate, target_channels, options, None)
+}
+
+/// Check that a file is decodable and read its format and duration without decoding audio
+/// Only the container/stream headers are parsed, so this is fast enough for scanning a library
+/// Errors carry the same `code`s as decodeAudio (FileNotFound, Unreadable, UnsupportedFormat,
+/// NoAudioTrack)
+#[napi]
+pub fn probe_audio(path: String) -> Result<AudioProbe, DecodeErrorCode> {
+    let format = open_format(&path)?;
+    let params = &audio_track(format.as_ref())?.codec_params;
+
+    let codec = symphonia::default::get_codecs()
+        .get_codec(params.codec)
+        .map(|descriptor| descriptor.short_name.to_string())
+        .ok_or_else(|| Error::new(DecodeErrorCode::UnsupportedFormat, "Unsupported codec"))?;
+
+    let duration = params.n_frames.and_then(|frames| match (params.time_base, params.sample_rate) {
+        (Some(time_base), _) => {
+            let time = time_base.calc_time(frames);
+            Some(time.seconds as f64 + time.frac)
+        }
+        (None, Some(sample_rate)) => Some(frames as f64 / sample_rate as f64),
+        (None, None) => None,
+    });
+    let file_size = std::fs::metadata(&path).map(|m| m.len()).ok();
+    let bitrate = match (file_size, duration) {
+        (Some(bytes), Some(seconds)) if seconds > 0.0 => {
+            Some(bytes as f64 * 8.0 / seconds / 1000.0)
+        }
+        _ => None,
+    };
+
+    Ok(AudioProbe {
+        codec,
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|c| c.count() as u32),
+        duration,
+        bitrate,
+    })
+}
+
+/// Decode and time-stretch the whole track to `target_bpm` with pitch preserved
+/// The result's `bpm` is the target tempo and `stretchRatio` the applied ratio;
+/// fails with code TempoNotDetected when the source BPM can't be found, or InvalidArgument for
+/// a target BPM that isn't a positive number. `options` apply as in decodeAudio, ahead of the
+/// stretch (the peak waveform and structure are taken from the stretched audio)
+#[napi]
+pub fn decode_audio_warped(
+    mp3_path: String,
+    target_bpm: f64,
+    target_sample_rate: u32,
+    target_channels: u32,
+    options: Option<DecodeOptions>,
+) -> Result<DecodeResult, DecodeErrorCode> {
+    let options = options.unwrap_or_default();
+    if !(target_bpm > 0.0 && target_bpm.is_finite()) {
+        return Err(Error::new(
+            DecodeErrorCode::InvalidArgument,
+            format!("Invalid target BPM: {}", target_bpm),
+        ));
+    }
+
+    let DecodedPcm { mut pcm, mut mono, sample_rate, channels } =
+        decode_samples(&mp3_path, target_sample_rate, target_channels, None)?;
+    let (trim_offset, applied_gain_db) =
+        process_pcm(&mut pcm, &mut mono, channels, sample_rate, &options);
+    let (analysis, analysis_rate) = analysis_signal(&mono, sample_rate, target_sample_rate);
+    let source_tempo = detect_bpm(&analysis, analysis_rate).ok_or_else(|| {
+        Error::new(DecodeErrorCode::TempoNotDetected, "Could not detect source BPM")
+    })?;
+
+    let stretch_ratio = target_bpm / source_tempo.bpm;
+    let pcm = time_stretch(&pcm, sample_rate, channels, stretch_ratio);
+    let mono: Vec<f32> = pcm
+        .chunks_exact(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    let (analysis, analysis_rate) = analysis_signal(&mono, sample_rate, target_sample_rate);
+    let mean_squares = frame_mean_squares(&analysis);
+    let structure_options = options.structure.unwrap_or_default();
+    let structure = detect_structure(
+        &analysis,
+        &mean_squares,
+        analysis_rate,
+        target_bpm,
+        structure_options,
+    );
+    let mut result =
+        to_decode_result(&pcm, &mono, Some(target_bpm), Some(structure), sample_rate, channels);
+    // The stretch is only as right as the source tempo it was based on
+    result.bpm_confidence = Some(source_tempo.confidence);
+    result.stretch_ratio = Some(stretch_ratio);
+    // The trim offset is in source time, before the stretch
+    result.trim_offset = trim_offset;
+    result.normalize_gain_db = applied_gain_db;
+    apply_peak_waveform(&mut result, &pcm, channels, &options);
+    Ok(result)
+}
+
+/// Cancellation handle for decodeAudioCancellable()
+#[napi]
+#[derive(Default)]
+pub struct DecodeCancelHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[napi]
+impl DecodeCancelHandle {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation; a running decode rejects with code "Cancelled"
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Background decode for decodeAudioCancellable()
+pub struct DecodeTask {
+    path: String,
+    target_sample_rate: u32,
+    target_channels: u32,
+    options: DecodeOptions,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Task for DecodeTask {
+    /// Decode failures are resolved as values so they reject with their own code in resolve()
+    type Output = Result<DecodeResult, DecodeErrorCode>;
+    type JsValue = DecodeResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Ok(decode(
+            &self.path,
+            self.target_sample_rate,
+            self.target_channels,
+            self.options,
+            Some(&self.cancelled),
+        ))
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        // Task errors are Status-typed; build the JS error here so it keeps the decode `code`
+        output.map_err(|e| JsError::from(e).into_unknown(env).into())
+    }
+}
+
+/// Decode off the JS thread; cancel() on the handle aborts between packets
+/// Rejects with code "Cancelled" once cancelled; other failures carry decodeAudio's `code`s
+/// `options` as in decodeAudio
+#[napi]
+pub fn decode_audio_cancellable(
+    mp3_path: String,
+    target_sample_rate: u32,
+    target_channels: u32,
+    handle: &DecodeCancelHandle,
+    options: Option<DecodeOptions>,
+) -> AsyncTask<DecodeTask> {
+    AsyncTask::new(DecodeTask {
+        path: mp3_path,
+        target_sample_rate,
+        target_channels,
+        options: options.unwrap_or_default(),
+        cancelled: Arc::clone(&handle.cancelled),
+    })
+}
+
+/// One file's outcome, passed to the decodeAudioBatch() progress callback
+#[napi(object)]
+pub struct BatchDecodeProgress {
+    /// Position of the file in `paths`
+    pub index: u32,
+    pub path: String,
+    /// Decoded track (absent on failure)
+    pub result: Option<DecodeResult>,
+    /// Decode error code (see decodeAudio; absent on success)
+    pub error_code: Option<String>,
+    /// Decode error message (absent on success)
+    pub error: Option<String>,
+}
+
+type BatchProgressCallback =
+    ThreadsafeFunction<BatchDecodeProgress, (), BatchDecodeProgress, Status, false>;
+
+/// Background worker pool for decodeAudioBatch()
+pub struct BatchDecodeTask {
+    paths: Vec<String>,
+    target_sample_rate: u32,
+    target_channels: u32,
+    options: DecodeOptions,
+    concurrency: usize,
+    callback: BatchProgressCallback,
+}
+
+impl Task for BatchDecodeTask {
+    type Output = u32;
+    type JsValue = u32;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        // Workers pull the next unclaimed path until the list is exhausted
+        let next = AtomicUsize::new(0);
+        let decoded = AtomicUsize::new(0);
+        let workers = self.concurrency.min(self.paths.len());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = self.paths.get(index) else {
+                        break;
+                    };
+
+                    let result = decode(
+                        path,
+                        self.target_sample_rate,
+                        self.target_channels,
+                        self.options,
+                        None,
+                    );
+                    // A failure is reported for its file only; the batch carries on
+                    let (result, error_code, error) = match result {
+                        Ok(result) => {
+                            decoded.fetch_add(1, Ordering::Relaxed);
+                            (Some(result), None, None)
+                        }
+                        Err(e) => {
+                            let code = e.status.as_ref().to_string();
+                            (None, Some(code), Some(e.reason.clone()))
+                        }
+                    };
+                    let progress = BatchDecodeProgress {
+                        index: index as u32,
+                        path: path.clone(),
+                        result,
+                        error_code,
+                        error,
+                    };
+                    self.callback.call(progress, ThreadsafeFunctionCallMode::Blocking);
+                });
+            }
+        });
+
+        Ok(decoded.into_inner() as u32)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Decode many files on up to `concurrency` threads (0 = one per CPU core)
+/// `callback` runs once per file as it completes, in completion order, with its result or error;
+/// a failing file doesn't stop the others. Resolves with the number of files decoded successfully
+/// `options` as in decodeAudio, for every file
+#[napi]
+pub fn decode_audio_batch(
+    paths: Vec<String>,
+    target_sample_rate: u32,
+    target_channels: u32,
+    concurrency: u32,
+    #[napi(ts_arg_type = "(progress: BatchDecodeProgress) => void")] callback: Function<
+        BatchDecodeProgress,
+        (),
+    >,
+    options: Option<DecodeOptions>,
+) -> Result<AsyncTask<BatchDecodeTask>> {
+    let concurrency = match concurrency {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n as usize,
+    };
+    let callback = callback
+        .build_threadsafe_function()
+        .callee_handled::<false>()
+        .build()?;
+
+    Ok(AsyncTask::new(BatchDecodeTask {
+        paths,
+        target_sample_rate,
+        target_channels,
+        options: options.unwrap_or_default(),
+        concurrency,
+        callback,
+    }))
+}
+
+/// Shared decode path; partially decoded buffers are dropped on early return
+fn decode(
+    mp3_path: &str,
+    target_sample_rate: u32,
+    target_channels: u32,
+    options: DecodeOptions,
+    cancelled: Option<&AtomicBool>,
+) -> Result<DecodeResult, DecodeErrorCode> {
+    let DecodedPcm { mut pcm, mut mono, sample_rate, channels } =
+        decode_samples(mp3_path, target_sample_rate, target_channels, cancelled)?;
+
+    if cancelled.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+        return Err(Error::new(DecodeErrorCode::Cancelled, "Decode cancelled"));
+    }
+
+    let (trim_offset, applied_gain_db) =
+        process_pcm(&mut pcm, &mut mono, channels, sample_rate, &options);
+
+    // Frame energies are shared by BPM detection and the structure's energy envelope
+    let (analysis, analysis_rate) = analysis_signal(&mono, sample_rate, target_sample_rate);
+    let mean_squares = frame_mean_squares(&analysis);
+
+    // Detect BPM
+    let tempo = find_tempo(&detect_onsets(&mean_squares), analysis_rate);
+    let bpm = tempo.as_ref().map(|tempo| tempo.bpm);
+
+    // Detect track structure if BPM was found
+    let structure_options = options.structure.unwrap_or_default();
+    let structure = bpm.map(|detected_bpm| {
+        detect_structure(
+            &analysis,
+            &mean_squares,
+            analysis_rate,
+            detected_bpm,
+            structure_options,
+        )
+    });
+
+    let mut result = to_decode_result(&pcm, &mono, bpm, structure, sample_rate, channels);
+    result.bpm_confidence = tempo.map(|tempo| tempo.confidence);
+    result.trim_offset = trim_offset;
+    result.normalize_gain_db = applied_gain_db;
+    apply_peak_waveform(&mut result, &pcm, channels, &options);
+    Ok(result)
+}
+
+/// DC removal, silence trimming and normalization, in that order, as `options` enable them
+/// Returns the trim offset (seconds) and the applied normalization gain (dB)
+fn process_pcm(
+    pcm: &mut Vec<f32>,
+    mono: &mut Vec<f32>,
+    channels: u32,
+    sample_rate: u32,
+    options: &DecodeOptions,
+) -> (Option<f64>, Option<f64>) {
+    // Remove DC first so an offset doesn't read as signal when trimming silence
+    if options.remove_dc.unwrap_or(false) {
+        remove_dc_offset(pcm, channels as usize, sample_rate);
+        remove_dc_offset(mono, 1, sample_rate);
+    }
+
+    // Trim before analysis so beat grids and sections refer to the trimmed audio
+    let trim_offset = options.trim_silence.unwrap_or(false).then(|| {
+        let head_frames = trim_silent_edges(pcm, mono, channels as usize, sample_rate);
+        head_frames as f64 / sample_rate as f64
+    });
+
+    // Level the trimmed audio so silence doesn't affect the measurement
+    let applied_gain_db = options.normalize.and_then(|mode| {
+        let gain_db = normalize_gain_db(pcm, channels as usize, sample_rate, mode)?;
+        let gain = 10f64.powf(gain_db / 20.0) as f32;
+        pcm.iter_mut().chain(mono.iter_mut()).for_each(|s| *s *= gain);
+        Some(gain_db)
+    });
+
+    (trim_offset, applied_gain_db)
+}
+
+/// Replace the result's display waveform with the per-frame channel peak when requested
+/// Display only: the summed mono still drives the analysis
+fn apply_peak_waveform(
+    result: &mut DecodeResult,
+    pcm: &[f32],
+    channels: u32,
+    options: &DecodeOptions,
+) {
+    if options.peak_waveform.unwrap_or(false) {
+        let peaks = peak_waveform(pcm, channels as usize);
+        let peak_bytes: Vec<u8> = peaks.iter().flat_map(|s| s.to_le_bytes()).collect();
+        result.mono = peak_bytes.into();
+    }
+}
+
+/// Open a file and probe its container format
+fn open_format(path: &str) -> Result<Box<dyn FormatReader>, DecodeErrorCode> {
+    // Open the file; only a missing file is FileNotFound (permissions etc. are Unreadable)
+    let file = File::open(path).map_err(|e| {
+        let code = match e.kind() {
+            std::io::ErrorKind::NotFound => DecodeErrorCode::FileNotFound,
+            _ => DecodeErrorCode::Unreadable,
+        };
+        Error::new(code, format!("Failed to open file: {}", e))
+    })?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
     // Create a hint for the format
@@ -71,31 +601,73 @@ pub fn decode_audio(
     let metadata_opts = MetadataOptions::default();
     let probed = symphonia::default::get_probe()
         .format(&hint, mss, &format_opts, &metadata_opts)
-        .map_err(|e| Error::from_reason(format!("Failed to probe format: {}", e)))?;
+        .map_err(|e| Error::new(DecodeErrorCode::UnsupportedFormat, format!("Failed to probe format: {}", e)))?;
 
-    let mut format = probed.format;
+    Ok(probed.format)
+}
 
-    // Find the audio track
-    let track = format
+/// First track with a known codec
+fn audio_track(format: &dyn FormatReader) -> Result<&Track, DecodeErrorCode> {
+    format
         .tracks()
         .iter()
         .find(|t| t.codec_params.codec != symphonia::core::codecs::CODEC_TYPE_NULL)
-        .ok_or_else(|| Error::from_reason("No audio track found"))?;
+        .ok_or_else(|| Error::new(DecodeErrorCode::NoAudioTrack, "No audio track found"))
+}
+
+/// Decoded PCM and the format it ended up in
+struct DecodedPcm {
+    /// Interleaved PCM
+    pcm: Vec<f32>,
+    /// Channel average
+    mono: Vec<f32>,
+    sample_rate: u32,
+    channels: u32,
+}
+
+/// Decode to interleaved PCM and a mono mixdown at the target rate/channels
+/// A target of 0 keeps the source's value
+fn decode_samples(
+    mp3_path: &str,
+    target_sample_rate: u32,
+    target_channels: u32,
+    cancelled: Option<&AtomicBool>,
+) -> Result<DecodedPcm, DecodeErrorCode> {
+    let check_cancelled = || {
+        if cancelled.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            Err(Error::new(DecodeErrorCode::Cancelled, "Decode cancelled"))
+        } else {
+            Ok(())
+        }
+    };
+
+    let mut format = open_format(mp3_path)?;
+    let track = audio_track(format.as_ref())?;
 
     let track_id = track.id;
     let source_sample_rate = track.codec_params.sample_rate.unwrap_or(44100);
     let source_channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(2);
+    let target_sample_rate = match target_sample_rate {
+        0 => source_sample_rate,
+        rate => rate,
+    };
+    let target_channels = match target_channels {
+        0 => source_channels as u32,
+        channels => channels,
+    };
 
     // Create a decoder
     let decoder_opts = DecoderOptions::default();
     let mut decoder = symphonia::default::get_codecs()
         .make(&track.codec_params, &decoder_opts)
-        .map_err(|e| Error::from_reason(format!("Failed to create decoder: {}", e)))?;
+        .map_err(|e| Error::new(DecodeErrorCode::UnsupportedFormat, format!("Failed to create decoder: {}", e)))?;
 
     // Collect all decoded samples
     let mut all_samples: Vec<f32> = Vec::new();
 
     loop {
+        check_cancelled()?;
+
         match format.next_packet() {
             Ok(packet) => {
                 if packet.track_id() != track_id {
@@ -111,16 +683,16 @@ pub fn decode_audio(
                         all_samples.extend_from_slice(sample_buf.samples());
                     }
                     Err(symphonia::core::errors::Error::DecodeError(_)) => continue,
-                    Err(e) => return Err(Error::from_reason(format!("Decode error: {}", e))),
+                    Err(e) => return Err(Error::new(DecodeErrorCode::CorruptData, format!("Decode error: {}", e))),
                 }
             }
             Err(symphonia::core::errors::Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-            Err(e) => return Err(Error::from_reason(format!("Format error: {}", e))),
+            Err(e) => return Err(Error::new(DecodeErrorCode::CorruptData, format!("Format error: {}", e))),
         }
     }
 
     if all_samples.is_empty() {
-        return Err(Error::from_reason("No samples decoded"));
+        return Err(Error::new(DecodeErrorCode::EmptyDecode, "No samples decoded"));
     }
 
     // Calculate frame count
@@ -159,26 +731,176 @@ pub fn decode_audio(
         mono[frame] = mono_accum / target_channels as f32;
     }
 
-    // Detect BPM
-    let bpm = detect_bpm(&mono, target_sample_rate);
+    Ok(DecodedPcm {
+        pcm,
+        mono,
+        sample_rate: target_sample_rate,
+        channels: target_channels,
+    })
+}
 
-    // Detect track structure if BPM was found
-    let structure = bpm.map(|detected_bpm| {
-        detect_structure(&mono, target_sample_rate, detected_bpm)
-    });
+/// Mono signal and rate the analysis runs on
+/// With the source rate passed through (target 0), the mono is resampled to
+/// ANALYSIS_SAMPLE_RATE so detection doesn't depend on the file's rate
+fn analysis_signal(
+    mono: &[f32],
+    sample_rate: u32,
+    target_sample_rate: u32,
+) -> (Cow<'_, [f32]>, u32) {
+    if target_sample_rate != 0 || sample_rate == ANALYSIS_SAMPLE_RATE || mono.is_empty() {
+        return (Cow::Borrowed(mono), sample_rate);
+    }
 
+    let ratio = sample_rate as f64 / ANALYSIS_SAMPLE_RATE as f64;
+    let frames = (mono.len() as f64 * ANALYSIS_SAMPLE_RATE as f64 / sample_rate as f64) as usize;
+    let resampled = (0..frames)
+        .map(|frame| mono[((frame as f64 * ratio) as usize).min(mono.len() - 1)])
+        .collect();
+    (Cow::Owned(resampled), ANALYSIS_SAMPLE_RATE)
+}
+
+/// Pack PCM and analysis into the JS result
+fn to_decode_result(
+    pcm: &[f32],
+    mono: &[f32],
+    bpm: Option<f64>,
+    structure: Option<TrackStructure>,
+    sample_rate: u32,
+    channels: u32,
+) -> DecodeResult {
     // Convert to buffers
     let pcm_bytes: Vec<u8> = pcm.iter().flat_map(|s| s.to_le_bytes()).collect();
     let mono_bytes: Vec<u8> = mono.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let [low, mid, high] =
+        crate::eq_processor::band_peaks(mono, sample_rate as f32, WAVEFORM_BIN_FRAMES);
+    let band_bytes = |peaks: Vec<f32>| -> Buffer {
+        peaks.iter().flat_map(|s| s.to_le_bytes()).collect::<Vec<u8>>().into()
+    };
 
-    Ok(DecodeResult {
+    DecodeResult {
         pcm: pcm_bytes.into(),
         mono: mono_bytes.into(),
         bpm,
+        bpm_confidence: None,
         structure,
-        sample_rate: target_sample_rate,
-        channels: target_channels,
-    })
+        sample_rate,
+        channels,
+        stretch_ratio: None,
+        trim_offset: None,
+        normalize_gain_db: None,
+        waveform_low: band_bytes(low),
+        waveform_mid: band_bytes(mid),
+        waveform_high: band_bytes(high),
+        waveform_bin_frames: WAVEFORM_BIN_FRAMES as u32,
+    }
+}
+
+/// Makeup gain in dB that brings the PCM to the normalization target
+/// None when there is no target or nothing to measure (silence, too short for loudness)
+fn normalize_gain_db(
+    pcm: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    mode: NormalizeMode,
+) -> Option<f64> {
+    let peak = pcm.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+    if peak <= 0.0 {
+        return None;
+    }
+    let peak_db = 20.0 * (peak as f64).log10();
+
+    if let Some(target_lufs) = mode.loudness_to {
+        let lufs = integrated_loudness(pcm, channels, sample_rate)?;
+        // Single gain, no limiting: don't push the peak past full scale
+        Some((target_lufs - lufs).min(-peak_db))
+    } else {
+        mode.peak_to.map(|target_db| target_db - peak_db)
+    }
+}
+
+/// Per-frame sample with the largest magnitude across channels (sign kept)
+/// Unlike the channel average, out-of-phase channels don't cancel each other
+fn peak_waveform(pcm: &[f32], channels: usize) -> Vec<f32> {
+    let louder = |peak: f32, &s: &f32| if s.abs() > peak.abs() { s } else { peak };
+    pcm.chunks_exact(channels).map(|frame| frame.iter().fold(0f32, louder)).collect()
+}
+
+/// DC-blocking one-pole high-pass on interleaved PCM: y[n] = x[n] - x[n-1] + r * y[n-1]
+fn remove_dc_offset(samples: &mut [f32], channels: usize, sample_rate: u32) {
+    let r = (-2.0 * std::f64::consts::PI * DC_BLOCK_CUTOFF_HZ / sample_rate as f64).exp() as f32;
+
+    for ch in 0..channels {
+        let (mut prev_x, mut prev_y) = (0f32, 0f32);
+        for sample in samples.iter_mut().skip(ch).step_by(channels) {
+            let x = *sample;
+            let y = x - prev_x + r * prev_y;
+            prev_x = x;
+            prev_y = y;
+            *sample = y;
+        }
+    }
+}
+
+/// Strip leading and trailing silence (keeping a short guard), returning the frames cut at the head
+/// All-silent audio is left untouched
+fn trim_silent_edges(
+    pcm: &mut Vec<f32>,
+    mono: &mut Vec<f32>,
+    channels: usize,
+    sample_rate: u32,
+) -> usize {
+    let is_audible = |frame: &[f32]| frame.iter().any(|s| s.abs() > SILENCE_THRESHOLD);
+    let first = pcm.chunks_exact(channels).position(is_audible);
+    let last = pcm.chunks_exact(channels).rposition(is_audible);
+    let (Some(first), Some(last)) = (first, last) else {
+        return 0;
+    };
+
+    let guard = (SILENCE_GUARD_SECONDS * sample_rate as f64) as usize;
+    let start = first.saturating_sub(guard);
+    let end = (last + 1 + guard).min(mono.len());
+
+    pcm.truncate(end * channels);
+    pcm.drain(..start * channels);
+    mono.truncate(end);
+    mono.drain(..start);
+    start
+}
+
+/// Time-stretch interleaved PCM by `tempo` (>1 = faster) with pitch preserved
+fn time_stretch(pcm: &[f32], sample_rate: u32, channels: u32, tempo: f64) -> Vec<f32> {
+    let channels = channels as usize;
+    let mut soundtouch = SoundTouch::new();
+    soundtouch
+        .set_channels(channels as u32)
+        .set_sample_rate(sample_rate)
+        .set_tempo(tempo)
+        // Offline: favour quality over speed
+        .set_setting(Setting::UseQuickseek, 0);
+
+    let chunk_frames = 8192;
+    let expected_frames = ((pcm.len() / channels) as f64 / tempo).round() as usize;
+    let mut output = Vec::with_capacity((expected_frames + chunk_frames) * channels);
+    let mut buffer = vec![0f32; chunk_frames * channels];
+
+    let mut drain = |soundtouch: &mut SoundTouch, output: &mut Vec<f32>| loop {
+        let received = soundtouch.receive_samples(&mut buffer, chunk_frames);
+        if received == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..received * channels]);
+    };
+
+    for chunk in pcm.chunks(chunk_frames * channels) {
+        soundtouch.put_samples(chunk, chunk.len() / channels);
+        drain(&mut soundtouch, &mut output);
+    }
+    soundtouch.flush();
+    drain(&mut soundtouch, &mut output);
+
+    // Flush pads with silence; trim to the expected stretched length
+    output.truncate(expected_frames * channels);
+    output
 }
 
 // ============================================================================
@@ -186,34 +908,42 @@ pub fn decode_audio(
 // ============================================================================
 
 /// Detect BPM from mono audio data using onset detection and autocorrelation
-fn detect_bpm(mono: &[f32], sample_rate: u32) -> Option<f64> {
-    let onsets = detect_onsets(mono);
+fn detect_bpm(mono: &[f32], sample_rate: u32) -> Option<TempoEstimate> {
+    let onsets = detect_onsets(&frame_mean_squares(mono));
     find_tempo(&onsets, sample_rate)
 }
 
-/// Detect onsets using energy-based approach with smoothing
-fn detect_onsets(data: &[f32]) -> Vec<f32> {
-    const HOP_SIZE: usize = 512;
-    const FRAME_SIZE: usize = 2048;
+const ENERGY_HOP_SIZE: usize = 512;
+const ENERGY_FRAME_SIZE: usize = 2048;
 
-    if data.len() < FRAME_SIZE {
+/// Mean square of each 2048-sample frame at a 512-sample hop
+fn frame_mean_squares(data: &[f32]) -> Vec<f32> {
+    if data.len() < ENERGY_FRAME_SIZE {
         return Vec::new();
     }
 
-    let num_frames = (data.len() - FRAME_SIZE) / HOP_SIZE;
+    let num_frames = (data.len() - ENERGY_FRAME_SIZE) / ENERGY_HOP_SIZE;
+    (0..num_frames)
+        .map(|i| {
+            let start = i * ENERGY_HOP_SIZE;
+            data[start..start + ENERGY_FRAME_SIZE]
+                .iter()
+                .map(|s| s * s)
+                .sum::<f32>()
+                / ENERGY_FRAME_SIZE as f32
+        })
+        .collect()
+}
+
+/// Detect onsets using energy-based approach with smoothing
+fn detect_onsets(mean_squares: &[f32]) -> Vec<f32> {
+    let num_frames = mean_squares.len();
     let mut onset_strength = vec![0f32; num_frames];
     let mut prev_energy = 0f32;
 
     for i in 0..num_frames {
-        let start = i * HOP_SIZE;
-
-        // Calculate frame energy (RMS)
-        let energy: f32 = data[start..start + FRAME_SIZE]
-            .iter()
-            .map(|s| s * s)
-            .sum::<f32>()
-            / FRAME_SIZE as f32;
-        let energy = energy.sqrt();
+        // Frame energy (RMS)
+        let energy = mean_squares[i].sqrt();
 
         // Spectral flux: positive difference from previous frame
         let flux = (energy - prev_energy).max(0.0);
@@ -251,8 +981,15 @@ fn detect_onsets(data: &[f32]) -> Vec<f32> {
     smoothed
 }
 
+/// Tempo found by find_tempo
+struct TempoEstimate {
+    bpm: f64,
+    /// 0-1, see DecodeResult::bpm_confidence
+    confidence: f64,
+}
+
 /// Find tempo using autocorrelation on onset envelope
-fn find_tempo(onsets: &[f32], sample_rate: u32) -> Option<f64> {
+fn find_tempo(onsets: &[f32], sample_rate: u32) -> Option<TempoEstimate> {
     if onsets.is_empty() {
         return None;
     }
@@ -305,8 +1042,9 @@ fn find_tempo(onsets: &[f32], sample_rate: u32) -> Option<f64> {
         let (best_idx, &best_corr) = correlations.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap())?;
         if best_corr > 0.0 {
             let lag = best_idx + min_lag;
-            let bpm = 60.0 / (lag as f64 / onset_sample_rate);
-            return Some(refine_bpm(bpm));
+            let bpm = refine_bpm(60.0 / (lag as f64 / onset_sample_rate));
+            let prominence = peak_prominence(&correlations, best_corr);
+            return Some(tempo_estimate(onsets, onset_sample_rate, bpm, prominence));
         }
         return None;
     }
@@ -314,7 +1052,7 @@ fn find_tempo(onsets: &[f32], sample_rate: u32) -> Option<f64> {
     // Sort by correlation strength
     peaks.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
 
-    let mut bpm = peaks[0].2;
+    let (mut bpm, mut peak_correlation) = (peaks[0].2, peaks[0].1);
 
     // Consider harmonic relationships
     for i in 1..peaks.len().min(3) {
@@ -326,13 +1064,65 @@ fn find_tempo(onsets: &[f32], sample_rate: u32) -> Option<f64> {
                 && peaks[i].2 <= 140.0
                 && (peaks[0].2 < 100.0 || peaks[0].2 > 140.0)
             {
-                bpm = peaks[i].2;
+                (bpm, peak_correlation) = (peaks[i].2, peaks[i].1);
                 break;
             }
         }
     }
 
-    Some(refine_bpm(bpm))
+    let bpm = refine_bpm(bpm);
+    let prominence = peak_prominence(&correlations, peak_correlation);
+    Some(tempo_estimate(onsets, onset_sample_rate, bpm, prominence))
+}
+
+/// Combine the coarse evidence (autocorrelation peak over the whole track) with the fine
+/// evidence (per-frame onset alignment to the final grid) into one confidence
+fn tempo_estimate(
+    onsets: &[f32],
+    onset_sample_rate: f64,
+    bpm: f64,
+    prominence: f64,
+) -> TempoEstimate {
+    // A grid at half the true tempo puts every other beat on the off-phase, so also try double
+    let period = 60.0 / bpm * onset_sample_rate;
+    let alignment = grid_alignment(onsets, period).max(grid_alignment(onsets, period / 2.0));
+    TempoEstimate {
+        bpm,
+        confidence: (prominence * alignment).sqrt(),
+    }
+}
+
+/// How far the chosen autocorrelation peak stands above the lag range's mean (0-1)
+fn peak_prominence(correlations: &[f32], peak: f32) -> f64 {
+    let mean = correlations.iter().sum::<f32>() / correlations.len() as f32;
+    if peak <= 0.0 {
+        return 0.0;
+    }
+    ((peak - mean) / peak).clamp(0.0, 1.0) as f64
+}
+
+/// How tightly the onsets line up with a grid of `period` frames (0-1)
+/// Per 8-beat window, the length of the onset-weighted mean phase vector (1 when all onset
+/// energy falls on the grid, near 0 when it is spread over the beat), averaged by window energy
+/// Short windows keep a rounding-sized tempo error from drifting the grid out of phase
+fn grid_alignment(onsets: &[f32], period: f64) -> f64 {
+    let window = (period * 8.0).round().max(1.0) as usize;
+    let (mut aligned, mut total) = (0f64, 0f64);
+    for (w, chunk) in onsets.chunks(window).enumerate() {
+        let (mut re, mut im) = (0f64, 0f64);
+        for (i, &onset) in chunk.iter().enumerate() {
+            let phase = 2.0 * std::f64::consts::PI * (w * window + i) as f64 / period;
+            re += onset as f64 * phase.cos();
+            im += onset as f64 * phase.sin();
+        }
+        aligned += re.hypot(im);
+        total += chunk.iter().map(|&onset| onset as f64).sum::<f64>();
+    }
+    if total > 0.0 {
+        aligned / total
+    } else {
+        0.0
+    }
 }
 
 /// Refine BPM to common ranges
@@ -350,16 +1140,22 @@ fn refine_bpm(mut bpm: f64) -> f64 {
 // ============================================================================
 
 /// Detect track structure (intro/main/outro sections)
-fn detect_structure(mono: &[f32], sample_rate: u32, bpm: f64) -> TrackStructure {
+fn detect_structure(
+    mono: &[f32],
+    mean_squares: &[f32],
+    sample_rate: u32,
+    bpm: f64,
+    options: StructureOptions,
+) -> TrackStructure {
     let duration = mono.len() as f64 / sample_rate as f64;
     let beat_duration = 60.0 / bpm;
 
     // Calculate energy envelope
-    let energy_envelope = calculate_energy_envelope(mono);
+    let energy_envelope = calculate_energy_envelope(mean_squares);
 
     // Detect boundaries
     let (intro_end, outro_start) =
-        detect_section_boundaries(&energy_envelope, sample_rate, bpm, duration);
+        detect_section_boundaries(&energy_envelope, sample_rate, bpm, duration, options);
 
     // Calculate beats for each section
     let intro_beats = (intro_end / beat_duration).round() as i32;
@@ -373,9 +1169,10 @@ fn detect_structure(mono: &[f32], sample_rate: u32, bpm: f64) -> TrackStructure
     }
     hot_cues.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
-    // Detect beats using the beat detector
-    let beats = crate::detect_beats(mono.to_vec().into(), sample_rate as f64)
-        .map(|result| result.beats)
+    // Detect beats using the beat detector, straight on the mono buffer without a copy
+    let beats = crate::beat_detector::BeatDetector::new(sample_rate as f32)
+        .detect(mono)
+        .map(|result| result.beats.iter().map(|&beat| beat as f64).collect())
         .unwrap_or_default();
 
     TrackStructure {
@@ -400,26 +1197,18 @@ fn detect_structure(mono: &[f32], sample_rate: u32, bpm: f64) -> TrackStructure
     }
 }
 
-/// Calculate energy envelope of the audio
-fn calculate_energy_envelope(mono: &[f32]) -> Vec<f32> {
-    const HOP_SIZE: usize = 2048;
-    const FRAME_SIZE: usize = 4096;
-
-    if mono.len() < FRAME_SIZE {
-        return Vec::new();
-    }
+/// Calculate energy envelope (4096-sample frames, 2048 hop) from the shared frame energies
+fn calculate_energy_envelope(mean_squares: &[f32]) -> Vec<f32> {
+    // A 4096-sample frame is two back-to-back 2048-sample frames, and both the
+    // frame halves and the 2048 hop are this many 512-sample hops apart
+    const HOPS: usize = 2048 / ENERGY_HOP_SIZE;
 
-    let num_frames = (mono.len() - FRAME_SIZE) / HOP_SIZE;
+    let num_frames = mean_squares.len().saturating_sub(HOPS) / HOPS;
     let mut energy = vec![0f32; num_frames];
 
     for i in 0..num_frames {
-        let start = i * HOP_SIZE;
-        let rms: f32 = mono[start..start + FRAME_SIZE]
-            .iter()
-            .map(|s| s * s)
-            .sum::<f32>()
-            / FRAME_SIZE as f32;
-        energy[i] = rms.sqrt();
+        let first = i * HOPS;
+        energy[i] = ((mean_squares[first] + mean_squares[first + HOPS]) / 2.0).sqrt();
     }
 
     // Smooth the envelope
@@ -450,13 +1239,16 @@ fn detect_section_boundaries(
     sample_rate: u32,
     bpm: f64,
     duration: f64,
+    options: StructureOptions,
 ) -> (f64, f64) {
     const HOP_SIZE: usize = 2048;
     let beat_duration = 60.0 / bpm;
+    let section_beats = options.section_beats.unwrap_or(16) as f64;
+    let search_beats = options.search_beats.map_or(2.0 * section_beats, |beats| beats as f64);
 
-    // Default 16 beats for intro/outro
-    let default_intro_end = 16.0 * beat_duration;
-    let default_outro_start = duration - 16.0 * beat_duration;
+    // Intro/outro length when no boundary is found
+    let default_intro_end = section_beats * beat_duration;
+    let default_outro_start = duration - section_beats * beat_duration;
 
     if energy_envelope.is_empty() {
         return (default_intro_end.max(0.0), default_outro_start.max(default_intro_end));
@@ -466,7 +1258,9 @@ fn detect_section_boundaries(
     let mean_energy: f32 = energy_envelope.iter().sum::<f32>() / energy_envelope.len() as f32;
 
     // Find intro end
-    let intro_search_end = ((32.0 * beat_duration * sample_rate as f64) / HOP_SIZE as f64) as usize;
+    let search_frames =
+        (search_beats * beat_duration * sample_rate as f64 / HOP_SIZE as f64) as usize;
+    let intro_search_end = search_frames;
     let intro_search_end = intro_search_end.min(energy_envelope.len());
 
     let mut intro_end = default_intro_end;
@@ -482,9 +1276,7 @@ fn detect_section_boundaries(
     }
 
     // Find outro start
-    let outro_search_start = energy_envelope.len().saturating_sub(
-        ((32.0 * beat_duration * sample_rate as f64) / HOP_SIZE as f64) as usize,
-    );
+    let outro_search_start = energy_envelope.len().saturating_sub(search_frames);
 
     let mut outro_start = default_outro_start;
     for i in (outro_search_start..energy_envelope.len().saturating_sub(10)).rev() {
@@ -507,3 +1299,119 @@ fn detect_section_boundaries(
 
     (intro_end.max(0.0), outro_start.max(intro_end + min_section))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_is_file_not_found() {
+        let missing = open_format("/nonexistent/track.mp3").err().unwrap();
+        assert_eq!(missing.status, DecodeErrorCode::FileNotFound);
+    }
+
+    #[test]
+    fn test_invalid_target_bpm_is_invalid_argument() {
+        let options = DecodeOptions { trim_silence: Some(true), ..Default::default() };
+        for bpm in [0.0, -120.0, f64::NAN, f64::INFINITY] {
+            let path = "/nonexistent/track.mp3".to_string();
+            let err = decode_audio_warped(path, bpm, 44100, 2, Some(options)).err().unwrap();
+            assert_eq!(err.status, DecodeErrorCode::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_remove_dc_offset() {
+        let sample_rate = 44_100;
+        let frames = sample_rate as usize * 10;
+        let tone = |i: usize| {
+            (2.0 * std::f32::consts::PI * 100.0 * i as f32 / sample_rate as f32).sin() * 0.5
+        };
+
+        // Stereo: 100 Hz tone on the left, silence on the right, both offset by +0.1
+        let mut pcm: Vec<f32> = (0..frames).flat_map(|i| [tone(i) + 0.1, 0.1]).collect();
+        remove_dc_offset(&mut pcm, 2, sample_rate);
+
+        for ch in 0..2 {
+            let mean = pcm.iter().skip(ch).step_by(2).sum::<f32>() / frames as f32;
+            assert!(mean.abs() < 1e-3, "channel {ch} mean {mean}");
+        }
+
+        // The tone itself passes (after the filter settles)
+        let settled = &pcm[sample_rate as usize * 2..];
+        let rms = (settled.iter().step_by(2).map(|s| s * s).sum::<f32>()
+            / (settled.len() / 2) as f32)
+            .sqrt();
+        let expected = 0.5 / std::f32::consts::SQRT_2;
+        assert!((rms / expected - 1.0).abs() < 0.01, "tone RMS {rms}, expected {expected}");
+    }
+
+    #[test]
+    fn test_peak_waveform_survives_out_of_phase_stereo() {
+        // Right channel is the inverted left: the channel average is silent
+        let pcm: Vec<f32> = [0.5f32, -0.8, 0.2].iter().flat_map(|&s| [s, -s]).collect();
+        assert_eq!(peak_waveform(&pcm, 2), vec![0.5, -0.8, 0.2]);
+
+        // Louder channel wins, with its sign
+        assert_eq!(peak_waveform(&[0.1, -0.4, 0.3, 0.2], 2), vec![-0.4, 0.3]);
+    }
+
+    #[test]
+    fn test_grid_alignment_separates_steady_from_scattered_onsets() {
+        // One onset every 40 frames sits on a 40-frame grid, even with a slightly off period
+        let steady: Vec<f32> = (0..4000).map(|i| if i % 40 == 0 { 1.0 } else { 0.0 }).collect();
+        assert!(grid_alignment(&steady, 40.0) > 0.99);
+        assert!(grid_alignment(&steady, 40.4) > 0.9);
+
+        // Onset energy spread evenly over the beat cancels out
+        assert!(grid_alignment(&vec![1.0; 4000], 40.0) < 0.05);
+    }
+
+    #[test]
+    fn test_section_boundaries_follow_configured_intro_length() {
+        // 120 BPM, 200 s: quiet for the first 32 beats (16 s), loud from there on
+        let frame_secs = 2048.0 / 44_100.0;
+        let envelope: Vec<f32> = (0..(200.0 / frame_secs) as usize)
+            .map(|i| if i as f64 * frame_secs < 16.0 { 0.1 } else { 1.0 })
+            .collect();
+        let boundaries =
+            |options| detect_section_boundaries(&envelope, 44_100, 120.0, 200.0, options);
+
+        // The default 32-beat search stops short of the rise: 16-beat defaults
+        assert_eq!(boundaries(StructureOptions::default()), (8.0, 192.0));
+
+        // 32-beat sections search 64 beats and find the rise
+        let options = StructureOptions { section_beats: Some(32), search_beats: None };
+        assert_eq!(boundaries(options), (16.0, 184.0));
+    }
+
+    #[test]
+    fn test_passthrough_analysis_runs_at_fixed_rate() {
+        let mono: Vec<f32> = (0..96_000).map(|i| i as f32).collect();
+
+        // Source rate kept: one second at 96 kHz becomes one second at 44.1 kHz
+        let (analysis, rate) = analysis_signal(&mono, 96_000, 0);
+        assert_eq!(rate, ANALYSIS_SAMPLE_RATE);
+        assert_eq!(analysis.len(), 44_100);
+        assert_eq!(analysis[44_099], ((44_099.0 * 96_000.0 / 44_100.0) as usize) as f32);
+
+        // An explicit target rate is analyzed as is
+        let (analysis, rate) = analysis_signal(&mono, 96_000, 96_000);
+        assert_eq!(rate, 96_000);
+        assert!(matches!(analysis, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_energy_envelope_from_shared_frame_energies() {
+        // Ten seconds of silence, then ten seconds at a constant 0.5
+        let mono: Vec<f32> =
+            (0..44_100 * 20).map(|i| if i < 441_000 { 0.0 } else { 0.5 }).collect();
+        let envelope = calculate_energy_envelope(&frame_mean_squares(&mono));
+
+        // Same frame count as 4096-sample frames at a 2048 hop
+        assert_eq!(envelope.len(), (mono.len() - 4096) / 2048);
+        let frame_at = |secs: f64| (secs * 44_100.0 / 2048.0) as usize;
+        assert_eq!(envelope[frame_at(5.0)], 0.0);
+        assert!((envelope[frame_at(15.0)] - 0.5).abs() < 1e-4, "{}", envelope[frame_at(15.0)]);
+    }
+}
@@ -0,0 +1,603 @@
+//! Cue points and beat grids from DJ software tags
+//!
+//! Serato stores its analysis in ID3v2 GEOB frames: "Serato Markers2" (hot cues,
+//! base64 text inside the frame) and "Serato BeatGrid" (grid anchors). symphonia
+//! 0.5 skips GEOB and PRIV frames when it reads ID3v2 metadata, so the tag is
+//! walked here.
+//!
+//! Rekordbox writes its analysis in the ANLZ format. When that data is embedded in
+//! a GEOB or PRIV frame (recognized by the "PMAI" file header) its cue lists (memory
+//! and hot cues) and beat grid are read too; Serato data wins where both exist.
+//!
+//! Anything missing or malformed is skipped: the result is empty, never an error.
+
+use std::fs::File;
+use std::io::Read;
+
+use napi_derive::napi;
+
+/// GEOB descriptions of the Serato frames
+const SERATO_MARKERS: &str = "Serato Markers2";
+const SERATO_BEATGRID: &str = "Serato BeatGrid";
+/// Magic at the start of a Rekordbox ANLZ file
+const ANLZ_MAGIC: &[u8] = b"PMAI";
+
+/// ANLZ cue list type of the hot cue list (the other, 0, holds memory cues)
+const ANLZ_HOT_CUES: u32 = 1;
+
+/// Hot or memory cue read from the file's tags
+#[napi(object)]
+pub struct DjCue {
+  /// Hot cue slot (0-based); memory cues are numbered in time order
+  pub index: u32,
+  /// Position in seconds
+  pub position: f64,
+  /// Label (empty when unnamed)
+  pub name: String,
+  /// Display color as 0xRRGGBB (0 when the tag has none)
+  pub color: u32,
+}
+
+/// Beat grid anchor: a downbeat position and the tempo from there to the next anchor
+#[napi(object)]
+pub struct DjGridAnchor {
+  /// Position in seconds
+  pub position: f64,
+  pub bpm: f64,
+}
+
+/// Cues and beat grid recovered from DJ software tags by readDjTags
+#[napi(object)]
+#[derive(Default)]
+pub struct DjTags {
+  /// Hot cues sorted by slot
+  pub hot_cues: Vec<DjCue>,
+  /// Memory cues in time order
+  pub memory_cues: Vec<DjCue>,
+  /// Beat grid anchors in time order
+  pub beat_grid: Vec<DjGridAnchor>,
+}
+
+/// Read Serato and Rekordbox hot cues, memory cues and beat grid anchors from a file's
+/// ID3v2 tag. Returns empty lists when the file has no such tags or they can't be parsed
+#[napi]
+pub fn read_dj_tags(path: String) -> DjTags {
+  let frames = read_id3_object_frames(&path).unwrap_or_default();
+  dj_tags_from_frames(&frames)
+}
+
+/// Collect the cues and grid of every recognized frame
+fn dj_tags_from_frames(frames: &[(String, Vec<u8>)]) -> DjTags {
+  let mut serato = DjTags::default();
+  let mut rekordbox = DjTags::default();
+  for (description, data) in frames {
+    match description.as_str() {
+      SERATO_MARKERS => serato.hot_cues = parse_serato_markers(data).unwrap_or_default(),
+      SERATO_BEATGRID => serato.beat_grid = parse_serato_beatgrid(data).unwrap_or_default(),
+      _ if data.starts_with(ANLZ_MAGIC) => {
+        if let Some(anlz) = parse_anlz(data) {
+          rekordbox = anlz;
+        }
+      }
+      _ => {}
+    }
+  }
+
+  let mut tags = DjTags {
+    hot_cues: pick(serato.hot_cues, rekordbox.hot_cues),
+    memory_cues: rekordbox.memory_cues,
+    beat_grid: pick(serato.beat_grid, rekordbox.beat_grid),
+  };
+  tags.hot_cues.sort_by_key(|cue| cue.index);
+  tags
+}
+
+/// The preferred list unless it is empty
+fn pick<T>(preferred: Vec<T>, fallback: Vec<T>) -> Vec<T> {
+  if preferred.is_empty() {
+    fallback
+  } else {
+    preferred
+  }
+}
+
+/// GEOB and PRIV frames of the file's leading ID3v2 tag as (description or owner, data)
+fn read_id3_object_frames(path: &str) -> Option<Vec<(String, Vec<u8>)>> {
+  let mut file = File::open(path).ok()?;
+  let file_len = file.metadata().ok()?.len();
+  let mut header = [0u8; 10];
+  file.read_exact(&mut header).ok()?;
+  if &header[..3] != b"ID3" {
+    return None;
+  }
+  // A corrupt size can't make us allocate more than the file holds
+  let size = synchsafe(&header[6..10]) as u64;
+  if size > file_len.saturating_sub(header.len() as u64) {
+    return None;
+  }
+  let mut tag = vec![0u8; size as usize];
+  file.read_exact(&mut tag).ok()?;
+  object_frames(&tag, header[3], header[5])
+}
+
+/// Walk the frames of an ID3v2.3 / 2.4 tag body (after the 10-byte header)
+fn object_frames(tag: &[u8], version: u8, flags: u8) -> Option<Vec<(String, Vec<u8>)>> {
+  // 2.2 uses three-character frame IDs; tag-wide unsynchronisation isn't undone here
+  if !(3..=4).contains(&version) || flags & 0x80 != 0 {
+    return None;
+  }
+
+  let mut offset = 0;
+  if flags & 0x40 != 0 {
+    // Extended header: 2.3 gives its size excluding the size field, 2.4 including it
+    let size = tag.get(..4)?;
+    offset = if version == 3 {
+      u32::from_be_bytes(size.try_into().ok()?) as usize + 4
+    } else {
+      synchsafe(size) as usize
+    };
+  }
+
+  let mut frames = Vec::new();
+  while let Some(frame_header) = tag.get(offset..offset + 10) {
+    // Padding
+    if frame_header[0] == 0 {
+      break;
+    }
+    let size = if version == 3 {
+      u32::from_be_bytes(frame_header[4..8].try_into().ok()?)
+    } else {
+      synchsafe(&frame_header[4..8])
+    } as usize;
+    let body = tag.get(offset + 10..offset + 10 + size)?;
+    let frame = match &frame_header[..4] {
+      b"GEOB" => parse_geob(body),
+      b"PRIV" => parse_priv(body),
+      _ => None,
+    };
+    frames.extend(frame);
+    offset += 10 + size;
+  }
+  Some(frames)
+}
+
+/// 28-bit ID3 synchsafe integer (7 bits per byte)
+fn synchsafe(bytes: &[u8]) -> u32 {
+  bytes
+    .iter()
+    .fold(0, |value, &byte| (value << 7) | (byte & 0x7f) as u32)
+}
+
+/// GEOB body: encoding, MIME type, file name, description, then the object
+fn parse_geob(body: &[u8]) -> Option<(String, Vec<u8>)> {
+  let (&encoding, rest) = body.split_first()?;
+  let wide = encoding == 1 || encoding == 2;
+  let (_mime, rest) = split_terminated(rest, false)?;
+  let (_file_name, rest) = split_terminated(rest, wide)?;
+  let (description, data) = split_terminated(rest, wide)?;
+
+  let description = if wide {
+    let units: Vec<u16> = description
+      .chunks_exact(2)
+      .map(|pair| match encoding {
+        // UTF-16 with BOM (assume little-endian unless marked big-endian)
+        1 if description.starts_with(&[0xfe, 0xff]) => u16::from_be_bytes([pair[0], pair[1]]),
+        1 => u16::from_le_bytes([pair[0], pair[1]]),
+        _ => u16::from_be_bytes([pair[0], pair[1]]),
+      })
+      .filter(|&unit| unit != 0xfeff)
+      .collect();
+    String::from_utf16_lossy(&units)
+  } else {
+    String::from_utf8_lossy(description).into_owned()
+  };
+  Some((description, data.to_vec()))
+}
+
+/// PRIV body: Latin-1 owner identifier, then the data
+fn parse_priv(body: &[u8]) -> Option<(String, Vec<u8>)> {
+  let (owner, data) = split_terminated(body, false)?;
+  Some((String::from_utf8_lossy(owner).into_owned(), data.to_vec()))
+}
+
+/// Split at the first null terminator (two aligned zero bytes for UTF-16)
+fn split_terminated(bytes: &[u8], wide: bool) -> Option<(&[u8], &[u8])> {
+  if wide {
+    let end = bytes.chunks_exact(2).position(|pair| pair == [0, 0])? * 2;
+    Some((&bytes[..end], &bytes[end + 2..]))
+  } else {
+    let end = bytes.iter().position(|&b| b == 0)?;
+    Some((&bytes[..end], &bytes[end + 1..]))
+  }
+}
+
+/// "Serato Markers2": version bytes, then base64 of (version bytes, tagged entries)
+/// Each entry is a null-terminated type, a big-endian length and its payload
+fn parse_serato_markers(data: &[u8]) -> Option<Vec<DjCue>> {
+  let encoded = data.get(2..)?;
+  let end = encoded
+    .iter()
+    .position(|&b| b == 0)
+    .unwrap_or(encoded.len());
+  let decoded = decode_base64(&encoded[..end])?;
+
+  let mut cues = Vec::new();
+  let mut rest = decoded.get(2..)?;
+  while let Some((kind, after)) = split_terminated(rest, false) {
+    if kind.is_empty() {
+      break;
+    }
+    let length = u32::from_be_bytes(after.get(..4)?.try_into().ok()?) as usize;
+    let payload = after.get(4..4 + length)?;
+    if kind == b"CUE" {
+      cues.extend(parse_serato_cue(payload));
+    }
+    rest = &after[4 + length..];
+  }
+  Some(cues)
+}
+
+/// CUE payload: pad, index, position (ms), pad, RGB color, two pad bytes, name
+fn parse_serato_cue(payload: &[u8]) -> Option<DjCue> {
+  let index = *payload.get(1)? as u32;
+  let position_ms = u32::from_be_bytes(payload.get(2..6)?.try_into().ok()?);
+  let rgb = payload.get(7..10)?;
+  let name = payload
+    .get(12..)
+    .and_then(|name| split_terminated(name, false))
+    .map_or_else(String::new, |(name, _)| {
+      String::from_utf8_lossy(name).into_owned()
+    });
+  Some(DjCue {
+    index,
+    position: position_ms as f64 / 1000.0,
+    name,
+    color: u32::from_be_bytes([0, rgb[0], rgb[1], rgb[2]]),
+  })
+}
+
+/// "Serato BeatGrid": version bytes, marker count, then 8 bytes per marker
+/// Every marker starts with its position (f32 seconds); the last one ends with the BPM
+/// (f32), the others with the number of beats (u32) up to the next marker
+fn parse_serato_beatgrid(data: &[u8]) -> Option<Vec<DjGridAnchor>> {
+  let count = u32::from_be_bytes(data.get(2..6)?.try_into().ok()?) as usize;
+  let markers = data.get(6..6 + count.checked_mul(8)?)?;
+  let field = |marker: &[u8], at: usize| -> [u8; 4] {
+    [marker[at], marker[at + 1], marker[at + 2], marker[at + 3]]
+  };
+  let positions: Vec<f64> = markers
+    .chunks_exact(8)
+    .map(|marker| f32::from_be_bytes(field(marker, 0)) as f64)
+    .collect();
+
+  let mut anchors = Vec::with_capacity(count);
+  for (i, marker) in markers.chunks_exact(8).enumerate() {
+    let bpm = match positions.get(i + 1) {
+      Some(&next) => {
+        let beats = u32::from_be_bytes(field(marker, 4)) as f64;
+        let span = next - positions[i];
+        if span <= 0.0 {
+          return None;
+        }
+        beats * 60.0 / span
+      }
+      None => f32::from_be_bytes(field(marker, 4)) as f64,
+    };
+    if !bpm.is_finite() || bpm <= 0.0 {
+      return None;
+    }
+    anchors.push(DjGridAnchor {
+      position: positions[i],
+      bpm,
+    });
+  }
+  Some(anchors)
+}
+
+/// Rekordbox ANLZ data: a "PMAI" header, then tagged sections, each starting with a
+/// four-character type, its header length and its total length (big-endian u32s)
+/// Reads the cue lists ("PCOB") and the beat grid ("PQTZ")
+fn parse_anlz(data: &[u8]) -> Option<DjTags> {
+  let mut tags = DjTags::default();
+  let mut offset = be_u32(data, 4)? as usize;
+  while let Some(kind) = data.get(offset..offset + 4) {
+    let header_len = be_u32(data, offset + 4)? as usize;
+    let section_len = be_u32(data, offset + 8)? as usize;
+    if section_len < 12 || header_len > section_len {
+      return None;
+    }
+    let section = data.get(offset..offset + section_len)?;
+    match kind {
+      b"PCOB" => {
+        let (hot, cues) = parse_anlz_cues(section, header_len)?;
+        if hot {
+          tags.hot_cues = cues;
+        } else {
+          tags.memory_cues = cues;
+        }
+      }
+      b"PQTZ" => tags.beat_grid = parse_anlz_beat_grid(section, header_len)?,
+      _ => {}
+    }
+    offset += section_len;
+  }
+  Some(tags)
+}
+
+/// PCOB: list type (u32 at 12), cue count (u16 at 18), then one "PCPT" entry per cue
+/// Each entry has the hot cue number (u32 at 12, 1 = A, 0 for a memory cue) and the
+/// position in ms (u32 at 32). Returns (is the hot cue list, cues)
+fn parse_anlz_cues(section: &[u8], header_len: usize) -> Option<(bool, Vec<DjCue>)> {
+  let hot = be_u32(section, 12)? == ANLZ_HOT_CUES;
+  let count = u16::from_be_bytes(section.get(18..20)?.try_into().ok()?) as usize;
+
+  let mut cues = Vec::with_capacity(count.min(section.len() / 12));
+  let mut offset = header_len;
+  for _ in 0..count {
+    if section.get(offset..offset + 4)? != b"PCPT" {
+      return None;
+    }
+    let entry_len = be_u32(section, offset + 8)? as usize;
+    let entry = section.get(offset..offset + entry_len)?;
+    let hot_cue = be_u32(entry, 12)?;
+    let position_ms = be_u32(entry, 32)?;
+    cues.push(DjCue {
+      index: hot_cue.saturating_sub(1),
+      position: position_ms as f64 / 1000.0,
+      name: String::new(),
+      color: 0,
+    });
+    offset += entry_len;
+  }
+
+  if !hot {
+    cues.sort_by(|a, b| a.position.total_cmp(&b.position));
+    for (index, cue) in cues.iter_mut().enumerate() {
+      cue.index = index as u32;
+    }
+  }
+  Some((hot, cues))
+}
+
+/// PQTZ: 8 bytes per beat from the end of the header: beat in bar (u16), tempo in
+/// BPM x 100 (u16) and position in ms (u32). An anchor starts at the first beat and
+/// at every tempo change
+fn parse_anlz_beat_grid(section: &[u8], header_len: usize) -> Option<Vec<DjGridAnchor>> {
+  let beats = section.get(header_len..)?;
+  let mut anchors: Vec<DjGridAnchor> = Vec::new();
+  for beat in beats.chunks_exact(8) {
+    let bpm = u16::from_be_bytes([beat[2], beat[3]]) as f64 / 100.0;
+    if bpm <= 0.0 {
+      return None;
+    }
+    if anchors.last().is_some_and(|anchor| anchor.bpm == bpm) {
+      continue;
+    }
+    let position_ms = u32::from_be_bytes([beat[4], beat[5], beat[6], beat[7]]);
+    anchors.push(DjGridAnchor {
+      position: position_ms as f64 / 1000.0,
+      bpm,
+    });
+  }
+  Some(anchors)
+}
+
+/// Big-endian u32 at byte `at` (None when out of bounds)
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+  Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Standard base64, skipping line breaks and tolerating missing padding
+fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+  let mut output = Vec::with_capacity(encoded.len() * 3 / 4);
+  let mut buffer = 0u32;
+  let mut bits = 0;
+  for &byte in encoded {
+    let value = match byte {
+      b'A'..=b'Z' => byte - b'A',
+      b'a'..=b'z' => byte - b'a' + 26,
+      b'0'..=b'9' => byte - b'0' + 52,
+      b'+' => 62,
+      b'/' => 63,
+      b'=' | b'\n' | b'\r' => continue,
+      _ => return None,
+    };
+    buffer = (buffer << 6) | value as u32;
+    bits += 6;
+    if bits >= 8 {
+      bits -= 8;
+      output.push((buffer >> bits) as u8);
+      buffer &= (1 << bits) - 1;
+    }
+  }
+  Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Standard base64 with padding, wrapped at 72 columns like Serato writes it
+  fn encode_base64(data: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = Vec::new();
+    for chunk in data.chunks(3) {
+      let bytes = [0, 1, 2].map(|i| chunk.get(i).copied().unwrap_or(0));
+      let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+      for i in 0..4 {
+        if i <= chunk.len() {
+          output.push(ALPHABET[(value >> (18 - 6 * i)) as usize & 63]);
+        } else {
+          output.push(b'=');
+        }
+      }
+    }
+    output
+      .chunks(72)
+      .flat_map(|line| line.iter().copied().chain([b'\n']))
+      .collect()
+  }
+
+  fn geob_frame(description: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = vec![0u8];
+    body.extend(b"application/octet-stream\0\0");
+    body.extend(description.as_bytes());
+    body.push(0);
+    body.extend(data);
+    let mut frame = b"GEOB".to_vec();
+    frame.extend((body.len() as u32).to_be_bytes());
+    frame.extend([0, 0]);
+    frame.extend(body);
+    frame
+  }
+
+  #[test]
+  fn test_serato_tags_from_id3_frames() {
+    // Markers2: two hot cues (stored out of order), plus a loop entry to skip
+    let cue = |index: u8, ms: u32, rgb: [u8; 3], name: &str| {
+      let mut payload = vec![0, index];
+      payload.extend(ms.to_be_bytes());
+      payload.push(0);
+      payload.extend(rgb);
+      payload.extend([0, 0]);
+      payload.extend(name.as_bytes());
+      payload.push(0);
+      let mut entry = b"CUE\0".to_vec();
+      entry.extend((payload.len() as u32).to_be_bytes());
+      entry.extend(payload);
+      entry
+    };
+    let mut markers = vec![1, 1];
+    markers.extend(cue(3, 61_500, [0x00, 0xcc, 0x00], "Drop"));
+    markers.extend(b"LOOP\0\0\0\0\x02\xab\xcd");
+    markers.extend(cue(0, 250, [0xcc, 0x00, 0x00], ""));
+    markers.push(0);
+    let mut markers_frame = vec![1, 1];
+    markers_frame.extend(encode_base64(&markers));
+    markers_frame.push(0);
+
+    // BeatGrid: 64 beats at 125 BPM from 0.1 s, then 128 BPM onwards
+    let mut grid = vec![1, 0, 0, 0, 0, 2];
+    grid.extend(0.1f32.to_be_bytes());
+    grid.extend(64u32.to_be_bytes());
+    grid.extend(30.82f32.to_be_bytes());
+    grid.extend(128.0f32.to_be_bytes());
+    grid.push(0);
+
+    let mut tag = geob_frame(SERATO_MARKERS, &markers_frame);
+    tag.extend(geob_frame(SERATO_BEATGRID, &grid));
+    tag.extend([0; 16]);
+
+    let tags = dj_tags_from_frames(&object_frames(&tag, 3, 0).unwrap());
+
+    assert_eq!(tags.hot_cues.len(), 2);
+    assert_eq!(tags.hot_cues[0].position, 0.25);
+    let drop = &tags.hot_cues[1];
+    assert_eq!((drop.index, drop.position), (3, 61.5));
+    assert_eq!((drop.name.as_str(), drop.color), ("Drop", 0x00cc00));
+    assert!(tags.memory_cues.is_empty());
+
+    assert_eq!(tags.beat_grid.len(), 2);
+    assert!((tags.beat_grid[0].position - 0.1).abs() < 1e-6);
+    assert!((tags.beat_grid[0].bpm - 125.0).abs() < 0.01);
+    assert_eq!(tags.beat_grid[1].bpm, 128.0);
+  }
+
+  /// ANLZ section: type, header length, total length, then the rest of the header and body
+  fn anlz_section(kind: &[u8], header: &[u8], body: &[u8]) -> Vec<u8> {
+    let header_len = 12 + header.len() as u32;
+    let mut section = kind.to_vec();
+    section.extend(header_len.to_be_bytes());
+    section.extend((header_len + body.len() as u32).to_be_bytes());
+    section.extend(header);
+    section.extend(body);
+    section
+  }
+
+  fn anlz_cue_list(list_type: u32, cues: &[(u32, u32)]) -> Vec<u8> {
+    let mut header = list_type.to_be_bytes().to_vec();
+    header.extend([0, 0]);
+    header.extend((cues.len() as u16).to_be_bytes());
+    header.extend([0xff; 4]);
+    let mut body = Vec::new();
+    for &(hot_cue, ms) in cues {
+      let mut entry = hot_cue.to_be_bytes().to_vec();
+      entry.extend([0; 16]);
+      entry.extend(ms.to_be_bytes());
+      entry.extend([0; 20]);
+      body.extend(anlz_section(b"PCPT", &entry[..16], &entry[16..]));
+    }
+    anlz_section(b"PCOB", &header, &body)
+  }
+
+  #[test]
+  fn test_rekordbox_anlz_in_priv_frame() {
+    // Beats at 120 BPM from 50 ms, switching to 125 BPM at 2.05 s
+    let mut beats = Vec::new();
+    for (i, (bpm, ms)) in [
+      (12000u16, 50u32),
+      (12000, 550),
+      (12500, 2050),
+      (12500, 2530),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      beats.extend((i as u16 % 4 + 1).to_be_bytes());
+      beats.extend(bpm.to_be_bytes());
+      beats.extend(ms.to_be_bytes());
+    }
+    let mut anlz = ANLZ_MAGIC.to_vec();
+    anlz.extend(28u32.to_be_bytes());
+    anlz.extend(0u32.to_be_bytes());
+    anlz.extend([0; 16]);
+    anlz.extend(anlz_cue_list(ANLZ_HOT_CUES, &[(2, 30_000), (1, 1_000)]));
+    anlz.extend(anlz_cue_list(0, &[(0, 90_000), (0, 45_500)]));
+    anlz.extend(anlz_section(b"PQTZ", &[0; 12], &beats));
+
+    let mut frame = b"PRIV".to_vec();
+    let owner = b"rekordbox\0";
+    frame.extend(((owner.len() + anlz.len()) as u32).to_be_bytes());
+    frame.extend([0, 0]);
+    frame.extend(owner);
+    frame.extend(&anlz);
+
+    let tags = dj_tags_from_frames(&object_frames(&frame, 3, 0).unwrap());
+    let hot: Vec<(u32, f64)> = tags
+      .hot_cues
+      .iter()
+      .map(|c| (c.index, c.position))
+      .collect();
+    assert_eq!(hot, [(0, 1.0), (1, 30.0)]);
+    let memory: Vec<(u32, f64)> = tags
+      .memory_cues
+      .iter()
+      .map(|c| (c.index, c.position))
+      .collect();
+    assert_eq!(memory, [(0, 45.5), (1, 90.0)]);
+    let grid: Vec<(f64, f64)> = tags.beat_grid.iter().map(|a| (a.position, a.bpm)).collect();
+    assert_eq!(grid, [(0.05, 120.0), (2.05, 125.0)]);
+  }
+
+  #[test]
+  fn test_oversized_tag_is_rejected() {
+    let path = std::env::temp_dir().join(format!("sujay-dj-tags-{}.mp3", std::process::id()));
+    // Claims a 256 MB tag in a 20-byte file
+    let mut file = b"ID3\x03\x00\x00\x7f\x7f\x7f\x7f".to_vec();
+    file.extend([0; 10]);
+    std::fs::write(&path, file).unwrap();
+    let frames = read_id3_object_frames(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert!(frames.is_none());
+  }
+
+  #[test]
+  fn test_malformed_serato_data_is_skipped() {
+    assert!(parse_serato_markers(b"\x01\x01!!not base64!!").is_none());
+    // Claims three markers but carries one
+    assert!(parse_serato_beatgrid(&[1, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0]).is_none());
+    assert!(read_dj_tags("/nonexistent/track.mp3".to_string())
+      .hot_cues
+      .is_empty());
+  }
+}
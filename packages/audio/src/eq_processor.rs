@@ -1,22 +1,69 @@
-//! 3-Band EQ Processor with Kill Switches
+//! 3-/4-Band EQ Processor with Kill Switches
 //!
 //! Implements DJ-style frequency isolation matching Pioneer DJM mixers.
-//! Frequency bands:
+//! 3-band frequency bands (default crossovers, adjustable via `set_crossovers`):
 //! - LOW: Below 250 Hz (lowpass)
 //! - MID: 250 Hz to 5 kHz (bandpass)
 //! - HIGH: Above 5 kHz (highpass)
 //!
-//! Uses overlapping filters for smooth transitions, similar to analog DJ mixers.
+//! 4-band isolator mode splits at 120 Hz, 500 Hz and 4 kHz
+//! (LOW / LOW-MID / HIGH-MID / HIGH).
+//!
+//! Bands are split with 4th-order Linkwitz-Riley crossovers (two cascaded
+//! Butterworth sections). Each band also passes through the remaining splits
+//! (highpass below, allpass above) so their phases line up and the bands sum
+//! back to a flat, allpass response when nothing is cut.
+//!
+//! `TrimFilters` adds a fixed-slope high-pass / low-pass pair per deck for
+//! cleanup ahead of the EQ (e.g. rolling off the lows of a layered track).
+//!
+//! `MasterEq` is a gentle shelf / peak / shelf tone control for the master bus
+//! (room correction), unrelated to the isolator bands.
+//!
+//! `band_peaks` reuses the crossovers offline to measure per-band waveform peaks;
+//! `EqProcessor::band_levels` is the live counterpart (per-chunk band RMS).
+//!
+//! Denormals: a filter fed silence decays into the subnormal float range, which
+//! is very slow on CPUs without flush-to-zero. Rather than setting FTZ/DAZ on the
+//! audio thread (MXCSR on x86, FPCR on ARM: unsafe and per-platform), each biquad
+//! flushes outputs below `DENORMAL_THRESHOLD` to zero, which works everywhere.
+
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
 
-use std::f32::consts::PI;
+use crate::simd;
 
 const SAMPLE_RATE: f32 = 44100.0;
 
-// DJ mixer style frequency bands (overlapping for smooth transitions)
+// DJ mixer style frequency bands
 const FREQ_LOW: f32 = 250.0;
-const FREQ_MID_LOW: f32 = 250.0;
 const FREQ_MID_HIGH: f32 = 5000.0;
-const FREQ_HIGH: f32 = 5000.0;
+
+// 4-band isolator splits: low | low-mid | high-mid | high
+const FOUR_BAND_SPLITS: [f32; 3] = [120.0, 500.0, 4000.0];
+
+// Band gain range (dB)
+const GAIN_DB_RANGE: (f32, f32) = (-26.0, 6.0);
+
+// Kill attenuation (dB): DJM-style isolator floor by default; -inf is a true kill
+const DEFAULT_KILL_DEPTH_DB: f32 = -26.0;
+const MIN_KILL_DEPTH_DB: f32 = -60.0;
+
+// Trim filter cutoff range (Hz); 0 bypasses
+const TRIM_FILTER_RANGE: (f32, f32) = (20.0, 20000.0);
+
+// Master tone control: shelf / peak / shelf corner frequencies (Hz) and gain range (dB)
+const MASTER_LOW_SHELF_HZ: f32 = 100.0;
+const MASTER_MID_HZ: f32 = 1000.0;
+const MASTER_MID_Q: f32 = 0.7;
+const MASTER_HIGH_SHELF_HZ: f32 = 10000.0;
+const MASTER_GAIN_DB_RANGE: (f32, f32) = (-12.0, 12.0);
+
+// Biquad outputs below this magnitude (about -300 dBFS) are flushed to zero
+const DENORMAL_THRESHOLD: f32 = 1e-15;
+
+// Allowed crossover ranges (disjoint so low/mid always sits below mid/high)
+const LOW_MID_RANGE: (f32, f32) = (40.0, 800.0);
+const MID_HIGH_RANGE: (f32, f32) = (1000.0, 16000.0);
 
 /// Biquad filter coefficients (Direct Form I)
 /// Transfer function: H(z) = (b0 + b1*z^-1 + b2*z^-2) / (1 + a1*z^-1 + a2*z^-2)
@@ -45,6 +92,12 @@ impl BiquadFilterChannel {
     let output = coeffs.b0 * input + coeffs.b1 * self.x1 + coeffs.b2 * self.x2
       - coeffs.a1 * self.y1
       - coeffs.a2 * self.y2;
+    // Keep a decaying tail out of the subnormal range (see module docs)
+    let output = if output.abs() < DENORMAL_THRESHOLD {
+      0.0
+    } else {
+      output
+    };
 
     // Update delay line
     self.x2 = self.x1;
@@ -82,7 +135,7 @@ impl BiquadFilter {
 
 /// Calculate 2nd-order Butterworth lowpass filter coefficients
 fn calculate_butterworth_lowpass(fc: f32, sample_rate: f32) -> BiquadCoefficients {
-  let q = 0.7071067811865476_f32; // 1/sqrt(2) for Butterworth
+  let q = FRAC_1_SQRT_2; // 1/sqrt(2) for Butterworth
 
   let w0 = 2.0 * PI * fc / sample_rate;
   let cos_w0 = w0.cos();
@@ -101,7 +154,7 @@ fn calculate_butterworth_lowpass(fc: f32, sample_rate: f32) -> BiquadCoefficient
 
 /// Calculate 2nd-order Butterworth highpass filter coefficients
 fn calculate_butterworth_highpass(fc: f32, sample_rate: f32) -> BiquadCoefficients {
-  let q = 0.7071067811865476_f32; // 1/sqrt(2) for Butterworth
+  let q = FRAC_1_SQRT_2; // 1/sqrt(2) for Butterworth
 
   let w0 = 2.0 * PI * fc / sample_rate;
   let cos_w0 = w0.cos();
@@ -118,162 +171,858 @@ fn calculate_butterworth_highpass(fc: f32, sample_rate: f32) -> BiquadCoefficien
   }
 }
 
+/// Calculate 2nd-order allpass coefficients (Q = 1/sqrt(2))
+/// Matches the phase of an LR4 lowpass + highpass pair at the same frequency
+fn calculate_allpass(fc: f32, sample_rate: f32) -> BiquadCoefficients {
+  let q = FRAC_1_SQRT_2;
+
+  let w0 = 2.0 * PI * fc / sample_rate;
+  let cos_w0 = w0.cos();
+  let sin_w0 = w0.sin();
+  let alpha = sin_w0 / (2.0 * q);
+
+  let a0 = 1.0 + alpha;
+  BiquadCoefficients {
+    b0: (1.0 - alpha) / a0,
+    b1: -2.0 * cos_w0 / a0,
+    b2: (1.0 + alpha) / a0,
+    a1: -2.0 * cos_w0 / a0,
+    a2: (1.0 - alpha) / a0,
+  }
+}
+
+/// Calculate low or high shelf coefficients (RBJ cookbook, shelf slope 1)
+fn calculate_shelf(fc: f32, gain_db: f32, high: bool, sample_rate: f32) -> BiquadCoefficients {
+  let a = 10.0f32.powf(gain_db / 40.0);
+  let w0 = 2.0 * PI * fc / sample_rate;
+  let cos_w0 = w0.cos();
+  let alpha = w0.sin() * FRAC_1_SQRT_2;
+  let beta = 2.0 * a.sqrt() * alpha;
+  // A high shelf is the low shelf with the sign of cos(w0) flipped
+  let (sign, cos_w0) = if high { (-1.0, -cos_w0) } else { (1.0, cos_w0) };
+
+  let a0 = (a + 1.0) + (a - 1.0) * cos_w0 + beta;
+  BiquadCoefficients {
+    b0: a * ((a + 1.0) - (a - 1.0) * cos_w0 + beta) / a0,
+    b1: sign * 2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0) / a0,
+    b2: a * ((a + 1.0) - (a - 1.0) * cos_w0 - beta) / a0,
+    a1: sign * -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0) / a0,
+    a2: ((a + 1.0) + (a - 1.0) * cos_w0 - beta) / a0,
+  }
+}
+
+/// Calculate peaking EQ coefficients (RBJ cookbook)
+fn calculate_peaking(fc: f32, q: f32, gain_db: f32, sample_rate: f32) -> BiquadCoefficients {
+  let a = 10.0f32.powf(gain_db / 40.0);
+  let w0 = 2.0 * PI * fc / sample_rate;
+  let cos_w0 = w0.cos();
+  let alpha = w0.sin() / (2.0 * q);
+
+  let a0 = 1.0 + alpha / a;
+  BiquadCoefficients {
+    b0: (1.0 + alpha * a) / a0,
+    b1: -2.0 * cos_w0 / a0,
+    b2: (1.0 - alpha * a) / a0,
+    a1: -2.0 * cos_w0 / a0,
+    a2: (1.0 - alpha / a) / a0,
+  }
+}
+
 /// EQ cut state (kill switches)
+/// Bands that don't exist in the current mode read as false
 #[derive(Clone, Copy, Default)]
 pub struct EqCutState {
   pub low: bool,
+  pub low_mid: bool,
   pub mid: bool,
+  pub high_mid: bool,
   pub high: bool,
 }
 
-/// DJ-style 3-band EQ with overlapping filters
+/// EQ layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqMode {
+  /// Low / mid / high (default)
+  ThreeBand,
+  /// Low / low-mid / high-mid / high isolator
+  FourBand,
+}
+
+impl EqMode {
+  /// Parse "3band" or "4band"
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "3band" => Some(EqMode::ThreeBand),
+      "4band" => Some(EqMode::FourBand),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      EqMode::ThreeBand => "3band",
+      EqMode::FourBand => "4band",
+    }
+  }
+
+  /// Bands from lowest to highest
+  fn bands(self) -> &'static [EqBand] {
+    match self {
+      EqMode::ThreeBand => &[EqBand::Low, EqBand::Mid, EqBand::High],
+      EqMode::FourBand => &[EqBand::Low, EqBand::LowMid, EqBand::HighMid, EqBand::High],
+    }
+  }
+}
+
+/// Response of a filter stage
+#[derive(Clone, Copy)]
+enum StageKind {
+  /// LR4 lowpass (2x Butterworth)
+  Lowpass,
+  /// LR4 highpass (2x Butterworth)
+  Highpass,
+  /// Phase compensation for a split the band doesn't cross
+  Allpass,
+}
+
+/// Filter stage at one crossover split
+#[derive(Clone)]
+struct FilterStage {
+  kind: StageKind,
+  /// Index into the mode's split frequencies
+  split: usize,
+  coeffs: BiquadCoefficients,
+  /// Cascaded sections sharing the coefficients
+  filters: Vec<BiquadFilter>,
+}
+
+impl FilterStage {
+  fn new(kind: StageKind, split: usize) -> Self {
+    let sections = match kind {
+      StageKind::Allpass => 1,
+      StageKind::Lowpass | StageKind::Highpass => 2,
+    };
+    Self {
+      kind,
+      split,
+      coeffs: BiquadCoefficients::default(),
+      filters: vec![BiquadFilter::default(); sections],
+    }
+  }
+
+  fn process_interleaved(&mut self, buffer: &mut [f32], frames: usize) {
+    for filter in &mut self.filters {
+      filter.process_interleaved(buffer, frames, &self.coeffs);
+    }
+  }
+}
+
+/// One isolator band
+///
+/// Band k of N is HPF at every split below it, LPF at the split above it and
+/// allpass at the splits further up, so all bands share the same phase response.
+struct Band {
+  id: EqBand,
+  stages: Vec<FilterStage>,
+  /// Kill switch
+  cut: bool,
+  /// Band gain (linear)
+  gain: f32,
+  /// Temporary buffer for band processing
+  buffer: Vec<f32>,
+}
+
+/// DJ-style 3-/4-band EQ with Linkwitz-Riley crossovers
 ///
 /// Each band uses independent filters for smooth, musical response
 pub struct EqProcessor {
-  // Low band: 2x Butterworth LPF at 250Hz
-  low_filter1: BiquadFilter,
-  low_filter2: BiquadFilter,
-  low_coeffs: BiquadCoefficients,
-
-  // Mid band: Bandpass 250Hz to 5kHz (HPF + LPF)
-  mid_filter_low1: BiquadFilter,
-  mid_filter_low2: BiquadFilter,
-  mid_filter_high1: BiquadFilter,
-  mid_filter_high2: BiquadFilter,
-  mid_coeffs_low: BiquadCoefficients,
-  mid_coeffs_high: BiquadCoefficients,
-
-  // High band: 2x Butterworth HPF at 5kHz
-  high_filter1: BiquadFilter,
-  high_filter2: BiquadFilter,
-  high_coeffs: BiquadCoefficients,
-
-  // Kill states
-  cut_state: EqCutState,
-
-  // Temporary buffers for band processing
-  low_buffer: Vec<f32>,
-  mid_buffer: Vec<f32>,
-  high_buffer: Vec<f32>,
+  mode: EqMode,
+  bands: Vec<Band>,
+
+  // 3-band crossover frequencies (Hz)
+  low_mid_hz: f32,
+  mid_high_hz: f32,
+
+  /// Band gain while cut (linear, 0.0 = true kill)
+  kill_gain: f32,
+
+  /// Low / mid / high RMS of the last chunk (see `band_levels`)
+  band_levels: [f32; 3],
+
+  max_frames: usize,
 }
 
 impl EqProcessor {
   pub fn new(max_frames: usize) -> Self {
-    // Low band: 2x Butterworth LPF at 250Hz
-    let low_coeffs = calculate_butterworth_lowpass(FREQ_LOW, SAMPLE_RATE);
-
-    // Mid band: Bandpass created by HPF (250Hz) + LPF (5kHz)
-    let mid_coeffs_low = calculate_butterworth_highpass(FREQ_MID_LOW, SAMPLE_RATE);
-    let mid_coeffs_high = calculate_butterworth_lowpass(FREQ_MID_HIGH, SAMPLE_RATE);
-
-    // High band: 2x Butterworth HPF at 5kHz
-    let high_coeffs = calculate_butterworth_highpass(FREQ_HIGH, SAMPLE_RATE);
+    let mut eq = Self {
+      mode: EqMode::ThreeBand,
+      bands: Vec::new(),
+      low_mid_hz: FREQ_LOW,
+      mid_high_hz: FREQ_MID_HIGH,
+      kill_gain: 10.0f32.powf(DEFAULT_KILL_DEPTH_DB / 20.0),
+      band_levels: [0.0; 3],
+      max_frames,
+    };
+    eq.build_bands();
+    eq
+  }
 
-    Self {
-      low_filter1: BiquadFilter::default(),
-      low_filter2: BiquadFilter::default(),
-      low_coeffs,
+  /// Switch between 3-band and 4-band layouts
+  /// Low/high cut and gain carry over; filter state restarts with the new layout
+  pub fn set_mode(&mut self, mode: EqMode) {
+    if mode == self.mode {
+      return;
+    }
 
-      mid_filter_low1: BiquadFilter::default(),
-      mid_filter_low2: BiquadFilter::default(),
-      mid_filter_high1: BiquadFilter::default(),
-      mid_filter_high2: BiquadFilter::default(),
-      mid_coeffs_low,
-      mid_coeffs_high,
+    let previous: Vec<(EqBand, bool, f32)> =
+      self.bands.iter().map(|b| (b.id, b.cut, b.gain)).collect();
+    self.mode = mode;
+    self.build_bands();
 
-      high_filter1: BiquadFilter::default(),
-      high_filter2: BiquadFilter::default(),
-      high_coeffs,
+    for band in &mut self.bands {
+      if let Some(&(_, cut, gain)) = previous.iter().find(|(id, _, _)| *id == band.id) {
+        band.cut = cut;
+        band.gain = gain;
+      }
+    }
+  }
 
-      cut_state: EqCutState::default(),
+  pub fn mode(&self) -> EqMode {
+    self.mode
+  }
 
-      low_buffer: vec![0.0; max_frames * 2],
-      mid_buffer: vec![0.0; max_frames * 2],
-      high_buffer: vec![0.0; max_frames * 2],
+  /// Move the 3-band low/mid and mid/high crossover points (Hz) and recompute all coefficients
+  ///
+  /// Frequencies are clamped to 40-800 Hz and 1-16 kHz. Filter delay lines are kept,
+  /// so the response glides to the new split instead of restarting from silence.
+  /// In 4-band mode the values are stored and take effect when switching back.
+  pub fn set_crossovers(&mut self, low_mid_hz: f32, mid_high_hz: f32) {
+    self.low_mid_hz = low_mid_hz.clamp(LOW_MID_RANGE.0, LOW_MID_RANGE.1);
+    self.mid_high_hz = mid_high_hz.clamp(MID_HIGH_RANGE.0, MID_HIGH_RANGE.1);
+    if self.mode == EqMode::ThreeBand {
+      self.update_coefficients();
     }
   }
 
   /// Set kill state for a specific band
-  pub fn set_cut(&mut self, band: EqBand, enabled: bool) {
-    match band {
-      EqBand::Low => self.cut_state.low = enabled,
-      EqBand::Mid => self.cut_state.mid = enabled,
-      EqBand::High => self.cut_state.high = enabled,
-    }
+  /// Returns false if the band doesn't exist in the current mode
+  pub fn set_cut(&mut self, band: EqBand, enabled: bool) -> bool {
+    self.band_mut(band).map(|b| b.cut = enabled).is_some()
+  }
+
+  /// Set how far a cut band is attenuated (dB, default -26)
+  /// Finite values are clamped to -60..0; -inf silences cut bands completely
+  pub fn set_kill_depth_db(&mut self, depth_db: f32) {
+    self.kill_gain = if depth_db == f32::NEG_INFINITY {
+      0.0
+    } else {
+      10.0f32.powf(depth_db.clamp(MIN_KILL_DEPTH_DB, 0.0) / 20.0)
+    };
+  }
+
+  /// Set gain for a specific band (dB, clamped to -26..+6)
+  /// Returns false if the band doesn't exist in the current mode
+  pub fn set_gain_db(&mut self, band: EqBand, gain_db: f32) -> bool {
+    let gain = 10.0f32.powf(gain_db.clamp(GAIN_DB_RANGE.0, GAIN_DB_RANGE.1) / 20.0);
+    self.band_mut(band).map(|b| b.gain = gain).is_some()
   }
 
   /// Get current cut state
   pub fn get_cut_state(&self) -> EqCutState {
-    self.cut_state
+    let cut = |id: EqBand| self.bands.iter().any(|b| b.id == id && b.cut);
+    EqCutState {
+      low: cut(EqBand::Low),
+      low_mid: cut(EqBand::LowMid),
+      mid: cut(EqBand::Mid),
+      high_mid: cut(EqBand::HighMid),
+      high: cut(EqBand::High),
+    }
   }
 
-  /// Process audio buffer with 3-band EQ and kill switches
-  /// Uses independent overlapping filters for each band
-  pub fn process(&mut self, buffer: &mut [f32], frames: usize) {
-    let EqCutState { low, mid, high } = self.cut_state;
+  /// Band gains in dB, lowest band first
+  pub fn gains_db(&self) -> Vec<f32> {
+    self.bands.iter().map(|b| 20.0 * b.gain.log10()).collect()
+  }
+
+  /// Low / mid / high RMS of the last processed chunk, after band gains and kills
+  ///
+  /// The 4-band layout folds low-mid and high-mid into mid. While the EQ is flat it
+  /// is bypassed and the bands aren't split, so each reads an equal share of the
+  /// chunk's energy (chunk RMS / sqrt(3)) instead.
+  pub fn band_levels(&self) -> [f32; 3] {
+    self.band_levels
+  }
+
+  fn band_mut(&mut self, id: EqBand) -> Option<&mut Band> {
+    self.bands.iter_mut().find(|b| b.id == id)
+  }
 
-    // Optimization: bypass EQ if all bands are enabled (no kills active)
-    if !low && !mid && !high {
+  /// Split frequencies between adjacent bands for the current mode
+  fn splits(&self) -> Vec<f32> {
+    match self.mode {
+      EqMode::ThreeBand => vec![self.low_mid_hz, self.mid_high_hz],
+      EqMode::FourBand => FOUR_BAND_SPLITS.to_vec(),
+    }
+  }
+
+  /// Create fresh bands (and filter state) for the current mode
+  fn build_bands(&mut self) {
+    let ids = self.mode.bands();
+    self.bands = ids
+      .iter()
+      .enumerate()
+      .map(|(i, &id)| Band {
+        id,
+        stages: (0..ids.len() - 1)
+          .map(|split| {
+            let kind = match split {
+              s if s < i => StageKind::Highpass,
+              s if s == i => StageKind::Lowpass,
+              _ => StageKind::Allpass,
+            };
+            FilterStage::new(kind, split)
+          })
+          .collect(),
+        cut: false,
+        gain: 1.0,
+        buffer: vec![0.0; self.max_frames * 2],
+      })
+      .collect();
+    self.update_coefficients();
+  }
+
+  /// Recompute coefficients in place, keeping filter delay lines
+  fn update_coefficients(&mut self) {
+    let splits = self.splits();
+    for stage in self.bands.iter_mut().flat_map(|b| b.stages.iter_mut()) {
+      let fc = splits[stage.split];
+      stage.coeffs = match stage.kind {
+        StageKind::Lowpass => calculate_butterworth_lowpass(fc, SAMPLE_RATE),
+        StageKind::Highpass => calculate_butterworth_highpass(fc, SAMPLE_RATE),
+        StageKind::Allpass => calculate_allpass(fc, SAMPLE_RATE),
+      };
+    }
+  }
+
+  /// Process audio buffer with the band EQ and kill switches
+  pub fn process(&mut self, buffer: &mut [f32], frames: usize) {
+    // Optimization: bypass EQ if every band is flat (no kills or gain changes)
+    if self.bands.iter().all(|b| !b.cut && b.gain == 1.0) {
+      self.band_levels = [rms(&buffer[..frames * 2]) / 3.0f32.sqrt(); 3];
       return;
     }
 
-    // Optimization: complete silence if all bands are killed
-    if low && mid && high {
+    // Optimization: complete silence if all bands are fully killed
+    if self.kill_gain == 0.0 && self.bands.iter().all(|b| b.cut) {
       buffer[..frames * 2].fill(0.0);
+      self.band_levels = [0.0; 3];
       return;
     }
 
-    let samples = frames * 2;
+    self.process_bands(buffer, frames);
+  }
 
-    // Copy input to all band buffers
-    self.low_buffer[..samples].copy_from_slice(&buffer[..samples]);
-    self.mid_buffer[..samples].copy_from_slice(&buffer[..samples]);
-    self.high_buffer[..samples].copy_from_slice(&buffer[..samples]);
+  /// Split into bands and sum them back with kill switches and gains applied
+  fn process_bands(&mut self, buffer: &mut [f32], frames: usize) {
+    let samples = frames * 2;
 
     // Apply filters to each band independently
-    // Low: 2x LPF at 250Hz
-    self
-      .low_filter1
-      .process_interleaved(&mut self.low_buffer, frames, &self.low_coeffs);
-    self
-      .low_filter2
-      .process_interleaved(&mut self.low_buffer, frames, &self.low_coeffs);
-
-    // Mid: HPF at 250Hz then LPF at 5kHz (creates bandpass)
-    self
-      .mid_filter_low1
-      .process_interleaved(&mut self.mid_buffer, frames, &self.mid_coeffs_low);
-    self
-      .mid_filter_low2
-      .process_interleaved(&mut self.mid_buffer, frames, &self.mid_coeffs_low);
-    self
-      .mid_filter_high1
-      .process_interleaved(&mut self.mid_buffer, frames, &self.mid_coeffs_high);
-    self
-      .mid_filter_high2
-      .process_interleaved(&mut self.mid_buffer, frames, &self.mid_coeffs_high);
-
-    // High: 2x HPF at 5kHz
-    self
-      .high_filter1
-      .process_interleaved(&mut self.high_buffer, frames, &self.high_coeffs);
-    self
-      .high_filter2
-      .process_interleaved(&mut self.high_buffer, frames, &self.high_coeffs);
-
-    // Mix bands with kill switches applied
-    for i in 0..samples {
-      buffer[i] = if low { 0.0 } else { self.low_buffer[i] }
-        + if mid { 0.0 } else { self.mid_buffer[i] }
-        + if high { 0.0 } else { self.high_buffer[i] };
+    for band in &mut self.bands {
+      band.buffer[..samples].copy_from_slice(&buffer[..samples]);
+      for stage in &mut band.stages {
+        stage.process_interleaved(&mut band.buffer, frames);
+      }
+    }
+
+    // Mix bands with kill switches and gains applied
+    let kill_gain = self.kill_gain;
+    let band_gain = |band: &Band| if band.cut { kill_gain } else { band.gain };
+    let [low, mid, high, rest @ ..] = self.bands.as_slice() else {
+      unreachable!("every EQ mode has at least three bands");
+    };
+    simd::sum_three(
+      &mut buffer[..samples],
+      &low.buffer[..samples],
+      band_gain(low),
+      &mid.buffer[..samples],
+      band_gain(mid),
+      &high.buffer[..samples],
+      band_gain(high),
+    );
+    for band in rest {
+      simd::add_scaled(
+        &mut buffer[..samples],
+        &band.buffer[..samples],
+        band_gain(band),
+      );
     }
+
+    // Band levels as heard; the inner bands of the 4-band layout count as mid
+    let last = self.bands.len() - 1;
+    let mut levels = [0.0f32; 3];
+    for (i, band) in self.bands.iter().enumerate() {
+      let slot = match i {
+        0 => 0,
+        i if i == last => 2,
+        _ => 1,
+      };
+      levels[slot] = levels[slot].hypot(rms(&band.buffer[..samples]) * band_gain(band));
+    }
+    self.band_levels = levels;
   }
 }
 
+/// Root mean square of a buffer (0 when empty)
+fn rms(samples: &[f32]) -> f32 {
+  if samples.is_empty() {
+    return 0.0;
+  }
+  (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// One trim filter stage (bypassed when the cutoff is 0)
+#[derive(Default)]
+struct TrimStage {
+  cutoff_hz: f32,
+  coeffs: BiquadCoefficients,
+  filter: BiquadFilter,
+}
+
+impl TrimStage {
+  fn set_cutoff(&mut self, hz: f32, design: fn(f32, f32) -> BiquadCoefficients) {
+    let hz = if hz > 0.0 {
+      // Keep the cutoff below Nyquist so the design stays stable
+      hz.clamp(
+        TRIM_FILTER_RANGE.0,
+        TRIM_FILTER_RANGE.1.min(SAMPLE_RATE * 0.45),
+      )
+    } else {
+      0.0
+    };
+    // Coming out of bypass, don't resume from a stale delay line
+    if self.cutoff_hz == 0.0 {
+      self.filter = BiquadFilter::default();
+    }
+    self.cutoff_hz = hz;
+    if hz > 0.0 {
+      self.coeffs = design(hz, SAMPLE_RATE);
+    }
+  }
+
+  fn process(&mut self, buffer: &mut [f32], frames: usize) {
+    if self.cutoff_hz > 0.0 {
+      self
+        .filter
+        .process_interleaved(buffer, frames, &self.coeffs);
+    }
+  }
+}
+
+/// Per-deck high-pass and low-pass trim filters (12 dB/octave Butterworth)
+///
+/// Separate from the EQ bands; meant for surgical cleanup early in the chain
+#[derive(Default)]
+pub struct TrimFilters {
+  highpass: TrimStage,
+  lowpass: TrimStage,
+}
+
+impl TrimFilters {
+  /// High-pass cutoff in Hz (20 to 20000), or 0 to bypass
+  pub fn set_highpass_hz(&mut self, hz: f32) {
+    self.highpass.set_cutoff(hz, calculate_butterworth_highpass);
+  }
+
+  /// Low-pass cutoff in Hz (20 to 20000), or 0 to bypass
+  pub fn set_lowpass_hz(&mut self, hz: f32) {
+    self.lowpass.set_cutoff(hz, calculate_butterworth_lowpass);
+  }
+
+  /// Current high-pass cutoff (0 = bypassed)
+  pub fn highpass_hz(&self) -> f32 {
+    self.highpass.cutoff_hz
+  }
+
+  /// Current low-pass cutoff (0 = bypassed)
+  pub fn lowpass_hz(&self) -> f32 {
+    self.lowpass.cutoff_hz
+  }
+
+  /// Filter a stereo interleaved buffer in place
+  pub fn process(&mut self, buffer: &mut [f32], frames: usize) {
+    self.highpass.process(buffer, frames);
+    self.lowpass.process(buffer, frames);
+  }
+}
+
+/// Master bus tone control: low shelf, mid peak and high shelf
+///
+/// Gains glide with the filter delay lines kept; bypassed when every band is at 0 dB
+pub struct MasterEq {
+  /// Band gains in dB (low, mid, high)
+  gains_db: [f32; 3],
+  coeffs: [BiquadCoefficients; 3],
+  filters: [BiquadFilter; 3],
+  bypassed: bool,
+}
+
+impl Default for MasterEq {
+  fn default() -> Self {
+    let mut eq = Self {
+      gains_db: [0.0; 3],
+      coeffs: [BiquadCoefficients::default(); 3],
+      filters: Default::default(),
+      bypassed: false,
+    };
+    eq.set_gains_db(0.0, 0.0, 0.0);
+    eq
+  }
+}
+
+impl MasterEq {
+  /// Set the low shelf, mid peak and high shelf gains (dB, clamped to -12..+12)
+  /// Ignored if any gain is non-finite, which would leave the filters stuck at NaN
+  pub fn set_gains_db(&mut self, low_db: f32, mid_db: f32, high_db: f32) {
+    if ![low_db, mid_db, high_db].iter().all(|db| db.is_finite()) {
+      return;
+    }
+    let clamp = |db: f32| db.clamp(MASTER_GAIN_DB_RANGE.0, MASTER_GAIN_DB_RANGE.1);
+    let (low_db, mid_db, high_db) = (clamp(low_db), clamp(mid_db), clamp(high_db));
+    // Coming out of the flat bypass, don't resume from stale delay lines
+    if self.is_flat() {
+      self.filters = Default::default();
+    }
+    self.gains_db = [low_db, mid_db, high_db];
+    self.coeffs = [
+      calculate_shelf(MASTER_LOW_SHELF_HZ, low_db, false, SAMPLE_RATE),
+      calculate_peaking(MASTER_MID_HZ, MASTER_MID_Q, mid_db, SAMPLE_RATE),
+      calculate_shelf(MASTER_HIGH_SHELF_HZ, high_db, true, SAMPLE_RATE),
+    ];
+  }
+
+  /// Band gains in dB (low, mid, high)
+  pub fn gains_db(&self) -> [f32; 3] {
+    self.gains_db
+  }
+
+  /// Bypass the EQ regardless of the band gains (kept for when it is re-enabled)
+  pub fn set_bypassed(&mut self, bypassed: bool) {
+    if self.bypassed && !bypassed {
+      self.filters = Default::default();
+    }
+    self.bypassed = bypassed;
+  }
+
+  pub fn bypassed(&self) -> bool {
+    self.bypassed
+  }
+
+  fn is_flat(&self) -> bool {
+    self.gains_db.iter().all(|&db| db == 0.0)
+  }
+
+  /// Filter a stereo interleaved buffer in place
+  pub fn process(&mut self, buffer: &mut [f32], frames: usize) {
+    if self.bypassed || self.is_flat() {
+      return;
+    }
+    for (filter, coeffs) in self.filters.iter_mut().zip(&self.coeffs) {
+      filter.process_interleaved(buffer, frames, coeffs);
+    }
+  }
+}
+
+/// Peak magnitude per bin of a mono signal in the low / mid / high bands
+///
+/// Splits at the default 3-band crossovers with Linkwitz-Riley sections like the
+/// isolator (without the allpass phase alignment). Analysis only, for colored
+/// waveforms; the last bin may cover fewer than `bin_frames` samples.
+pub fn band_peaks(mono: &[f32], sample_rate: f32, bin_frames: usize) -> [Vec<f32>; 3] {
+  let low_lp = calculate_butterworth_lowpass(FREQ_LOW, sample_rate);
+  let low_hp = calculate_butterworth_highpass(FREQ_LOW, sample_rate);
+  let high_lp = calculate_butterworth_lowpass(FREQ_MID_HIGH, sample_rate);
+  let high_hp = calculate_butterworth_highpass(FREQ_MID_HIGH, sample_rate);
+
+  // Two cascaded Butterworth sections per LR4 filter
+  let lr4 = |sections: &mut [BiquadFilterChannel; 2], x: f32, coeffs: &BiquadCoefficients| {
+    sections
+      .iter_mut()
+      .fold(x, |x, section| section.process(x, coeffs))
+  };
+  let mut low = <[BiquadFilterChannel; 2]>::default();
+  let mut mid_hp = <[BiquadFilterChannel; 2]>::default();
+  let mut mid_lp = <[BiquadFilterChannel; 2]>::default();
+  let mut high = <[BiquadFilterChannel; 2]>::default();
+
+  let bins = mono.len().div_ceil(bin_frames.max(1));
+  let mut peaks: [Vec<f32>; 3] = Default::default();
+  for band in &mut peaks {
+    band.reserve_exact(bins);
+  }
+  for chunk in mono.chunks(bin_frames.max(1)) {
+    let mut bin = [0.0f32; 3];
+    for &x in chunk {
+      let mid = lr4(&mut mid_lp, lr4(&mut mid_hp, x, &low_hp), &high_lp);
+      bin[0] = bin[0].max(lr4(&mut low, x, &low_lp).abs());
+      bin[1] = bin[1].max(mid.abs());
+      bin[2] = bin[2].max(lr4(&mut high, x, &high_hp).abs());
+    }
+    for (band, peak) in peaks.iter_mut().zip(bin) {
+      band.push(peak);
+    }
+  }
+  peaks
+}
+
 /// EQ band identifiers
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EqBand {
   Low,
+  /// 4-band mode only
+  LowMid,
+  /// 3-band mode only
   Mid,
+  /// 4-band mode only
+  HighMid,
   High,
 }
+
+impl EqBand {
+  /// Parse "low", "lowmid", "mid", "highmid" or "high"
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "low" => Some(EqBand::Low),
+      "lowmid" => Some(EqBand::LowMid),
+      "mid" => Some(EqBand::Mid),
+      "highmid" => Some(EqBand::HighMid),
+      "high" => Some(EqBand::High),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rustfft::{num_complex::Complex, FftPlanner};
+
+  /// Averaged transfer magnitude (dB) per third-octave band for white noise
+  /// through all bands summed with no cuts
+  fn noise_response_db(eq: &mut EqProcessor) -> Vec<(f32, f32)> {
+    const FFT_SIZE: usize = 8192;
+    const CHUNK: usize = 1024;
+    const BLOCKS: usize = 64;
+
+    let total = FFT_SIZE * BLOCKS;
+    let mut state = 1u32;
+    let input: Vec<f32> = (0..total)
+      .map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+      })
+      .collect();
+
+    // Run the left channel through the full band split/sum path
+    let mut output = Vec::with_capacity(total);
+    let mut buffer = vec![0.0f32; CHUNK * 2];
+    for chunk in input.chunks(CHUNK) {
+      for (frame, &x) in chunk.iter().enumerate() {
+        buffer[frame * 2] = x;
+        buffer[frame * 2 + 1] = x;
+      }
+      eq.process_bands(&mut buffer, CHUNK);
+      output.extend(buffer.chunks(2).map(|frame| frame[0]));
+    }
+
+    // Welch-style averaged input and output power spectra
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+    let window: Vec<f32> = (0..FFT_SIZE)
+      .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / FFT_SIZE as f32).cos()))
+      .collect();
+    let mut power_in = vec![0.0f32; FFT_SIZE / 2];
+    let mut power_out = vec![0.0f32; FFT_SIZE / 2];
+    // Skip the first block so the filters have settled
+    for block in 1..BLOCKS {
+      for (signal, power) in [(&input, &mut power_in), (&output, &mut power_out)] {
+        let start = block * FFT_SIZE;
+        let mut spectrum: Vec<Complex<f32>> = signal[start..start + FFT_SIZE]
+          .iter()
+          .zip(&window)
+          .map(|(&x, &w)| Complex::new(x * w, 0.0))
+          .collect();
+        fft.process(&mut spectrum);
+        for (p, c) in power.iter_mut().zip(&spectrum) {
+          *p += c.norm_sqr();
+        }
+      }
+    }
+
+    let bin_hz = SAMPLE_RATE / FFT_SIZE as f32;
+    let mut bands = Vec::new();
+    let mut center = 31.5f32;
+    while center < 16000.0 {
+      let lo = (center / 2f32.powf(1.0 / 6.0) / bin_hz) as usize;
+      let hi = (center * 2f32.powf(1.0 / 6.0) / bin_hz) as usize;
+      let p_in: f32 = power_in[lo..=hi].iter().sum();
+      let p_out: f32 = power_out[lo..=hi].iter().sum();
+      bands.push((center, 10.0 * (p_out / p_in).log10()));
+      center *= 2f32.powf(1.0 / 3.0);
+    }
+    bands
+  }
+
+  #[test]
+  fn test_bands_sum_flat_for_white_noise() {
+    for mode in [EqMode::ThreeBand, EqMode::FourBand] {
+      let mut eq = EqProcessor::new(1024);
+      eq.set_mode(mode);
+
+      for (center, db) in noise_response_db(&mut eq) {
+        assert!(
+          db.abs() <= 0.5,
+          "{} EQ summed to {:.2} dB at {:.0} Hz",
+          mode.name(),
+          db,
+          center
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_trim_filters_roll_off_outside_cutoffs() {
+    // Steady-state RMS of a stereo sine after the filters
+    let rms_through = |filters: &mut TrimFilters, hz: f32| {
+      let frames = SAMPLE_RATE as usize;
+      let mut buffer: Vec<f32> = (0..frames)
+        .flat_map(|i| {
+          let s = (2.0 * PI * hz * i as f32 / SAMPLE_RATE).sin();
+          [s, s]
+        })
+        .collect();
+      filters.process(&mut buffer, frames);
+      let tail = &buffer[frames..];
+      (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt()
+    };
+    let sine_rms = FRAC_1_SQRT_2;
+
+    let mut filters = TrimFilters::default();
+    assert!((rms_through(&mut filters, 50.0) - sine_rms).abs() < 1e-3);
+
+    filters.set_highpass_hz(400.0);
+    filters.set_lowpass_hz(4000.0);
+    // Two octaves out: about -24 dB at 12 dB/octave
+    assert!(rms_through(&mut filters, 100.0) < sine_rms * 0.1);
+    assert!(rms_through(&mut filters, 16000.0) < sine_rms * 0.1);
+    assert!(rms_through(&mut filters, 1265.0) > sine_rms * 0.9);
+
+    filters.set_highpass_hz(0.0);
+    assert_eq!(filters.highpass_hz(), 0.0);
+  }
+
+  #[test]
+  fn test_master_eq_shelves_and_bypass() {
+    // Steady-state gain (dB) of a stereo sine through the EQ
+    let gain_db_through = |eq: &mut MasterEq, hz: f32| {
+      let frames = SAMPLE_RATE as usize;
+      let mut buffer: Vec<f32> = (0..frames)
+        .flat_map(|i| {
+          let s = (2.0 * PI * hz * i as f32 / SAMPLE_RATE).sin();
+          [s, s]
+        })
+        .collect();
+      eq.process(&mut buffer, frames);
+      let tail = &buffer[frames..];
+      let rms = (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt();
+      20.0 * (rms / FRAC_1_SQRT_2).log10()
+    };
+
+    let mut eq = MasterEq::default();
+    eq.set_gains_db(6.0, 0.0, -6.0);
+    assert!((gain_db_through(&mut eq, 30.0) - 6.0).abs() < 0.5);
+    assert!(gain_db_through(&mut eq, 1000.0).abs() < 0.5);
+    assert!((gain_db_through(&mut eq, 18000.0) + 6.0).abs() < 1.0);
+
+    eq.set_gains_db(0.0, 4.0, 0.0);
+    assert!((gain_db_through(&mut eq, 1000.0) - 4.0).abs() < 0.1);
+
+    eq.set_bypassed(true);
+    assert!(gain_db_through(&mut eq, 1000.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_master_eq_ignores_non_finite_gains() {
+    let mut eq = MasterEq::default();
+    eq.set_gains_db(3.0, 0.0, 0.0);
+    eq.set_gains_db(f32::NAN, 0.0, 0.0);
+    eq.set_gains_db(0.0, f32::INFINITY, 0.0);
+    assert_eq!(eq.gains_db(), [3.0, 0.0, 0.0]);
+
+    let frames = 1024;
+    let mut buffer: Vec<f32> = (0..frames * 2).map(|i| (i as f32 * 0.01).sin()).collect();
+    eq.process(&mut buffer, frames);
+    assert!(buffer.iter().all(|s| s.is_finite()));
+  }
+
+  #[test]
+  fn test_band_peaks_follow_tone_frequency() {
+    let tone = |hz: f32| -> Vec<f32> {
+      (0..SAMPLE_RATE as usize)
+        .map(|i| (2.0 * PI * hz * i as f32 / SAMPLE_RATE).sin())
+        .collect()
+    };
+
+    let [low, mid, high] = band_peaks(&tone(60.0), SAMPLE_RATE, 1024);
+    assert_eq!(low.len(), (SAMPLE_RATE as usize).div_ceil(1024));
+    // Skip the first bins while the filters settle
+    assert!(low[10..].iter().all(|&p| p > 0.9));
+    assert!(mid[10..].iter().all(|&p| p < 0.1));
+    assert!(high[10..].iter().all(|&p| p < 0.01));
+
+    let [low, mid, high] = band_peaks(&tone(15000.0), SAMPLE_RATE, 1024);
+    assert!(high[10..].iter().all(|&p| p > 0.9));
+    assert!(low[10..].iter().all(|&p| p < 0.01));
+    assert!(mid[10..].iter().all(|&p| p < 0.2));
+  }
+
+  #[test]
+  fn test_band_levels_track_band_energy() {
+    // Six whole cycles of 60 Hz, so the chunk RMS is the tone's
+    let frames = 4410;
+    let tone: Vec<f32> = (0..frames * 2)
+      .map(|i| (2.0 * PI * 60.0 * (i / 2) as f32 / SAMPLE_RATE).sin())
+      .collect();
+    let tone_rms = FRAC_1_SQRT_2;
+
+    // Flat EQ is bypassed: an equal split of the chunk energy
+    let mut eq = EqProcessor::new(frames);
+    eq.process(&mut tone.clone(), frames);
+    for level in eq.band_levels() {
+      assert!((level - tone_rms / 3.0f32.sqrt()).abs() < 0.01, "{level}");
+    }
+
+    // Active EQ measures the bands: a 60 Hz tone sits in the low band
+    eq.set_cut(EqBand::High, true);
+    for _ in 0..10 {
+      eq.process(&mut tone.clone(), frames);
+    }
+    let [low, mid, high] = eq.band_levels();
+    assert!((low - tone_rms).abs() < 0.05, "{low}");
+    assert!(mid < 0.1 && high < 0.01, "{mid} {high}");
+  }
+
+  #[test]
+  fn test_biquad_tail_flushes_to_zero() {
+    let coeffs = calculate_butterworth_lowpass(FREQ_LOW, SAMPLE_RATE);
+    let mut channel = BiquadFilterChannel::default();
+    channel.process(1.0, &coeffs);
+
+    // Ten seconds of silence: the ringing decays to exactly zero, never subnormal
+    for _ in 0..SAMPLE_RATE as usize * 10 {
+      let output = channel.process(0.0, &coeffs);
+      assert!(output == 0.0 || output.is_normal());
+    }
+    assert_eq!(channel.y1, 0.0);
+    assert_eq!(channel.y2, 0.0);
+  }
+}
@@ -61,6 +61,125 @@ pub fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>> {
   Ok(devices)
 }
 
+/// One supported stream configuration: a channel count and sample format over a rate range
+#[napi(object)]
+pub struct SupportedConfigInfo {
+  pub channels: u32,
+  /// "f32", "i16", "u16", ...
+  pub sample_format: String,
+  pub min_sample_rate: u32,
+  pub max_sample_rate: u32,
+  /// Buffer size range in frames (absent when the platform can't tell before opening)
+  pub min_buffer_size: Option<u32>,
+  pub max_buffer_size: Option<u32>,
+}
+
+/// Capabilities of one direction (input or output) of a device
+#[napi(object)]
+pub struct StreamCapabilities {
+  /// Common sample rates inside any supported range, plus the range ends, ascending
+  pub sample_rates: Vec<u32>,
+  /// Supported channel counts, ascending
+  pub channel_counts: Vec<u32>,
+  /// Supported sample formats
+  pub sample_formats: Vec<String>,
+  /// The configurations themselves, for picking valid combinations
+  pub configs: Vec<SupportedConfigInfo>,
+}
+
+#[napi(object)]
+pub struct DeviceCapabilities {
+  pub name: String,
+  pub input: StreamCapabilities,
+  pub output: StreamCapabilities,
+}
+
+/// Sample rates listed when a device reports a continuous range
+const COMMON_SAMPLE_RATES: [u32; 11] = [
+  8_000, 11_025, 16_000, 22_050, 32_000, 44_100, 48_000, 88_200, 96_000, 176_400, 192_000,
+];
+
+/// Full capability list of a device (default output device when no ID is given)
+/// A device without input (or output) reports empty lists for that direction
+#[napi]
+pub fn get_device_capabilities(device_id: Option<String>) -> Result<DeviceCapabilities> {
+  let host = cpal::default_host();
+  let device = match device_id {
+    Some(id) => host
+      .devices()
+      .map_err(map_err)?
+      .find(|device| device.name().is_ok_and(|name| name == id))
+      .ok_or_else(|| Error::from_reason(format!("Device '{}' not found", id)))?,
+    None => host
+      .default_output_device()
+      .ok_or_else(|| Error::from_reason("No default output device available"))?,
+  };
+
+  let input = device
+    .supported_input_configs()
+    .map(|configs| stream_capabilities(configs.collect()))
+    .unwrap_or_else(|_| stream_capabilities(Vec::new()));
+  let output = device
+    .supported_output_configs()
+    .map(|configs| stream_capabilities(configs.collect()))
+    .unwrap_or_else(|_| stream_capabilities(Vec::new()));
+
+  Ok(DeviceCapabilities {
+    name: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+    input,
+    output,
+  })
+}
+
+fn stream_capabilities(ranges: Vec<cpal::SupportedStreamConfigRange>) -> StreamCapabilities {
+  let mut sample_rates = Vec::new();
+  let mut channel_counts = Vec::new();
+  let mut sample_formats = Vec::new();
+  let mut configs = Vec::new();
+
+  for range in &ranges {
+    let min_rate = range.min_sample_rate().0;
+    let max_rate = range.max_sample_rate().0;
+    sample_rates.extend([min_rate, max_rate]);
+    sample_rates.extend(
+      COMMON_SAMPLE_RATES
+        .iter()
+        .filter(|&&rate| (min_rate..=max_rate).contains(&rate)),
+    );
+    channel_counts.push(range.channels() as u32);
+
+    let sample_format = range.sample_format().to_string();
+    if !sample_formats.contains(&sample_format) {
+      sample_formats.push(sample_format.clone());
+    }
+
+    let (min_buffer_size, max_buffer_size) = match *range.buffer_size() {
+      cpal::SupportedBufferSize::Range { min, max } => (Some(min), Some(max)),
+      cpal::SupportedBufferSize::Unknown => (None, None),
+    };
+    configs.push(SupportedConfigInfo {
+      channels: range.channels() as u32,
+      sample_format,
+      min_sample_rate: min_rate,
+      max_sample_rate: max_rate,
+      min_buffer_size,
+      max_buffer_size,
+    });
+  }
+
+  sample_rates.sort_unstable();
+  sample_rates.dedup();
+  channel_counts.sort_unstable();
+  channel_counts.dedup();
+
+  StreamCapabilities {
+    sample_rates,
+    channel_counts,
+    sample_formats,
+    configs,
+  }
+}
+
 fn map_err<E: ToString>(err: E) -> Error {
   Error::from_reason(err.to_string())
 }
@@ -75,6 +194,19 @@ mod beat_detector;
 // Beat Detection - Multi-feature beat tracker (Zapata et al. 2014)
 // ============================================================================
 
+/// Beat detection options
+#[napi(object)]
+#[derive(Default)]
+pub struct BeatDetectionOptions {
+  /// Shift the grid so a downbeat lands on the first kick (default: false)
+  pub anchor_to_first_kick: Option<bool>,
+  /// ODF weights: complex, energy, mel, beat-emphasis, info-gain (default: equal).
+  /// Renormalized; ignored unless exactly 5 values are given
+  pub odf_weights: Option<Vec<f64>>,
+  /// Fixed beat-emphasis reference tempo in BPM (default: scan the track's strongest periods)
+  pub emphasis_reference_bpm: Option<f64>,
+}
+
 #[napi(object)]
 pub struct BeatDetectionResultJs {
   /// Detected BPM
@@ -85,25 +217,99 @@ pub struct BeatDetectionResultJs {
   pub confidence: f64,
 }
 
+/// Beat detection result plus the intermediate data behind it
+#[napi(object)]
+pub struct BeatDetectionDebugJs {
+  /// Detection result (absent if detection failed)
+  pub result: Option<BeatDetectionResultJs>,
+  /// Combined onset detection function (normalized 0-1)
+  pub odf: Vec<f64>,
+  /// ODF frame rate (frames per second)
+  pub odf_sample_rate: f64,
+  /// Tempo (BPM) of each autocorrelation lag
+  pub autocorrelation_bpms: Vec<f64>,
+  /// Autocorrelation of the ODF at each lag
+  pub autocorrelation: Vec<f64>,
+  /// Candidate tempos from autocorrelation peaks, strongest first
+  pub candidate_bpms: Vec<f64>,
+  /// Autocorrelation strength of each candidate
+  pub candidate_strengths: Vec<f64>,
+}
+
+fn detector_config(options: Option<BeatDetectionOptions>) -> beat_detector::BeatDetectorConfig {
+  let options = options.unwrap_or_default();
+  let mut config = beat_detector::BeatDetectorConfig {
+    anchor_to_first_kick: options.anchor_to_first_kick.unwrap_or(false),
+    emphasis_reference_bpm: options.emphasis_reference_bpm.map(|bpm| bpm as f32),
+    ..Default::default()
+  };
+  if let Some(weights) = options.odf_weights.filter(|w| w.len() == 5) {
+    config.odf_weights = std::array::from_fn(|i| weights[i] as f32);
+  }
+  config
+}
+
+fn to_js_result(result: beat_detector::BeatDetectionResult) -> BeatDetectionResultJs {
+  BeatDetectionResultJs {
+    bpm: result.bpm as f64,
+    beats: result.beats.iter().map(|&b| b as f64).collect(),
+    confidence: result.confidence as f64,
+  }
+}
+
 /// Detect BPM and beat positions from mono audio data.
 /// Based on: J. Zapata, M. Davies and E. Gómez, "Multi-feature beat tracker,"
 /// IEEE/ACM Transactions on Audio, Speech and Language Processing, 22(4), 816-825, 2014
 #[napi]
-pub fn detect_beats(audio: Float32Array, sample_rate: f64) -> Option<BeatDetectionResultJs> {
-  let mut detector = beat_detector::BeatDetector::new(sample_rate as f32);
-  let result = detector.detect(audio.as_ref())?;
+pub fn detect_beats(
+  audio: Float32Array,
+  sample_rate: f64,
+  options: Option<BeatDetectionOptions>,
+) -> Option<BeatDetectionResultJs> {
+  let config = detector_config(options);
+  let mut detector = beat_detector::BeatDetector::with_config(sample_rate as f32, config);
+  detector.detect(audio.as_ref()).map(to_js_result)
+}
 
-  Some(BeatDetectionResultJs {
-    bpm: result.bpm as f64,
-    beats: result.beats.iter().map(|&b| b as f64).collect(),
-    confidence: result.confidence as f64,
-  })
+/// Same as detectBeats, but also returns the combined ODF, its autocorrelation
+/// and the candidate tempo peaks for plotting and diagnosing mis-detections
+#[napi]
+pub fn detect_beats_debug(
+  audio: Float32Array,
+  sample_rate: f64,
+  options: Option<BeatDetectionOptions>,
+) -> BeatDetectionDebugJs {
+  let config = detector_config(options);
+  let mut detector = beat_detector::BeatDetector::with_config(sample_rate as f32, config);
+  let (result, debug) = detector.detect_with_debug(audio.as_ref());
+  let to_f64 = |values: &[f32]| values.iter().map(|&v| v as f64).collect::<Vec<_>>();
+  let (candidate_bpms, candidate_strengths) = debug
+    .candidates
+    .iter()
+    .map(|&(bpm, strength)| (bpm as f64, strength as f64))
+    .unzip();
+
+  BeatDetectionDebugJs {
+    result: result.map(to_js_result),
+    odf: to_f64(&debug.combined_odf),
+    odf_sample_rate: debug.odf_sample_rate as f64,
+    autocorrelation_bpms: to_f64(&debug.autocorrelation_bpms),
+    autocorrelation: to_f64(&debug.autocorrelation),
+    candidate_bpms,
+    candidate_strengths,
+  }
 }
 
 mod audio_engine;
 mod decoder;
+mod dj_tags;
 mod eq_processor;
+mod limiter;
+mod loudness;
 mod recorder;
+mod sampler;
+mod simd;
 pub use audio_engine::*;
 pub use decoder::*;
+pub use dj_tags::*;
 pub use recorder::*;
@@ -0,0 +1,179 @@
+//! Stereo peak limiter
+//!
+//! Gain computer linked across both channels so the stereo image doesn't
+//! shift under gain reduction, with exponential attack and release.
+//! An input gain ahead of the gain computer sets how hard it is driven.
+//!
+//! With a lookahead the signal is delayed so the gain starts falling before a
+//! peak reaches the output; each frame is still clamped to its own required
+//! gain, so nothing passes above the ceiling whatever the attack time.
+
+use std::collections::VecDeque;
+
+/// Default limiter release time (seconds)
+const DEFAULT_RELEASE: f32 = 0.1;
+
+pub struct PeakLimiter {
+  /// Gain applied before limiting (linear)
+  input_gain: f32,
+  /// Output ceiling (linear)
+  ceiling: f32,
+  /// Per-sample attack coefficient (1.0 = instant)
+  attack_coeff: f32,
+  /// Per-sample release coefficient
+  release_coeff: f32,
+  /// Current gain (1.0 = no reduction)
+  gain: f32,
+  /// Lowest gain since the last take_gain_reduction_db()
+  min_gain: f32,
+  /// Lookahead in frames (the added latency)
+  lookahead: usize,
+  /// Delayed frames with the gain each one needs to stay under the ceiling
+  delay: VecDeque<(f32, f32, f32)>,
+  /// Sliding minimum of the required gain over the lookahead window: (frame, gain)
+  required: VecDeque<(u64, f32)>,
+  /// Frames processed (window bookkeeping)
+  frame: u64,
+}
+
+impl PeakLimiter {
+  pub fn new(ceiling_db: f32, sample_rate: u32) -> Self {
+    let mut limiter = Self {
+      input_gain: 1.0,
+      ceiling: 1.0,
+      attack_coeff: 1.0,
+      release_coeff: 0.0,
+      gain: 1.0,
+      min_gain: 1.0,
+      lookahead: 0,
+      delay: VecDeque::new(),
+      required: VecDeque::new(),
+      frame: 0,
+    };
+    limiter.set_ceiling_db(ceiling_db);
+    limiter.set_release(DEFAULT_RELEASE, sample_rate);
+    limiter.set_lookahead(0.0, sample_rate);
+    limiter
+  }
+
+  /// Set the output ceiling in dBFS (clamped to -40..0)
+  pub fn set_ceiling_db(&mut self, ceiling_db: f32) {
+    self.ceiling = 10.0f32.powf(ceiling_db.clamp(-40.0, 0.0) / 20.0);
+  }
+
+  /// Set the gain applied before limiting in dB (clamped to -24..+24)
+  pub fn set_input_gain_db(&mut self, gain_db: f32) {
+    self.input_gain = 10.0f32.powf(gain_db.clamp(-24.0, 24.0) / 20.0);
+  }
+
+  /// Deepest gain reduction in dB (positive) since the last call
+  pub fn take_gain_reduction_db(&mut self) -> f32 {
+    let reduction = -20.0 * self.min_gain.log10();
+    self.min_gain = self.gain;
+    reduction
+  }
+
+  /// Set the attack time in seconds (0 = instant)
+  pub fn set_attack(&mut self, attack_seconds: f32, sample_rate: u32) {
+    self.attack_coeff = if attack_seconds > 0.0 {
+      1.0 - (-1.0 / (attack_seconds * sample_rate as f32)).exp()
+    } else {
+      1.0
+    };
+  }
+
+  /// Set the release time in seconds
+  pub fn set_release(&mut self, release_seconds: f32, sample_rate: u32) {
+    self.release_coeff = 1.0 - (-1.0 / (release_seconds.max(0.001) * sample_rate as f32)).exp();
+  }
+
+  /// Set the lookahead in seconds, resizing (and clearing) the delay line
+  pub fn set_lookahead(&mut self, lookahead_seconds: f32, sample_rate: u32) {
+    self.lookahead = (lookahead_seconds.max(0.0) * sample_rate as f32).round() as usize;
+    self.delay.clear();
+    self.delay.resize(self.lookahead, (0.0, 0.0, 1.0));
+    self.required.clear();
+  }
+
+  /// Delay added by the lookahead (frames)
+  pub fn latency_frames(&self) -> usize {
+    self.lookahead
+  }
+
+  /// Limit one stereo frame (the output is `latency_frames()` behind the input)
+  #[inline]
+  pub fn process_frame(&mut self, left: f32, right: f32) -> (f32, f32) {
+    let (left, right) = (left * self.input_gain, right * self.input_gain);
+    let peak = left.abs().max(right.abs());
+    let needed = if peak > self.ceiling {
+      self.ceiling / peak
+    } else {
+      1.0
+    };
+
+    // Smallest required gain from the delayed output frame up to this input frame
+    while let Some(&(_, gain)) = self.required.back() {
+      if gain < needed {
+        break;
+      }
+      self.required.pop_back();
+    }
+    self.required.push_back((self.frame, needed));
+    let window_start = self.frame.saturating_sub(self.lookahead as u64);
+    while let Some(&(frame, _)) = self.required.front() {
+      if frame >= window_start {
+        break;
+      }
+      self.required.pop_front();
+    }
+    let target = self.required.front().map_or(1.0, |&(_, gain)| gain);
+    self.frame += 1;
+
+    // Ramp toward the window's requirement
+    let coeff = if target < self.gain {
+      self.attack_coeff
+    } else {
+      self.release_coeff
+    };
+    self.gain += (target - self.gain) * coeff;
+
+    self.delay.push_back((left, right, needed));
+    let (left, right, needed) = self.delay.pop_front().unwrap_or((left, right, needed));
+
+    // Never let a frame through above the ceiling, even mid-attack
+    let gain = self.gain.min(needed);
+    self.min_gain = self.min_gain.min(gain);
+    (left * gain, right * gain)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_lookahead_catches_transient_without_overshoot() {
+    let sample_rate = 44_100;
+    let mut limiter = PeakLimiter::new(0.0, sample_rate);
+    limiter.set_attack(0.002, sample_rate);
+    limiter.set_lookahead(0.005, sample_rate);
+    let latency = limiter.latency_frames();
+    assert_eq!(latency, 221);
+
+    // Quiet signal, then a single-sample spike 6 dB over the ceiling
+    let spike_at = 1000;
+    let output: Vec<f32> = (0..4000)
+      .map(|i| {
+        let sample = if i == spike_at { 2.0 } else { 0.25 };
+        limiter.process_frame(sample, sample).0
+      })
+      .collect();
+
+    assert!(output.iter().all(|&s| s <= 1.0 + 1e-6));
+    // The spike comes out delayed by the lookahead, right at the ceiling
+    assert!((output[spike_at + latency] - 1.0).abs() < 1e-6);
+    // The attack had ramped the gain down before the spike arrived
+    assert!(output[spike_at + latency - 1] < 0.25 * 0.75);
+    assert!(limiter.take_gain_reduction_db() > 5.9);
+  }
+}
@@ -0,0 +1,151 @@
+//! Integrated loudness measurement (ITU-R BS.1770 / EBU R128)
+//!
+//! K-weighting (high shelf + high-pass), mean square over 400 ms blocks with
+//! 75% overlap, then the -70 LUFS absolute and -10 LU relative gates.
+
+use std::f64::consts::PI;
+
+/// Gating block length (seconds)
+const BLOCK_SECONDS: f64 = 0.4;
+/// Block hop (75% overlap)
+const BLOCK_STEP_SECONDS: f64 = 0.1;
+/// Absolute gate (LUFS)
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Relative gate below the absolute-gated loudness (LU)
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Direct Form I biquad (f64 for accurate mean squares)
+#[derive(Clone, Copy)]
+struct Biquad {
+  b: [f64; 3],
+  a: [f64; 2],
+  x: [f64; 2],
+  y: [f64; 2],
+}
+
+impl Biquad {
+  fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+    Self {
+      b,
+      a,
+      x: [0.0; 2],
+      y: [0.0; 2],
+    }
+  }
+
+  #[inline]
+  fn process(&mut self, input: f64) -> f64 {
+    let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+      - self.a[0] * self.y[0]
+      - self.a[1] * self.y[1];
+    self.x = [input, self.x[0]];
+    self.y = [output, self.y[0]];
+    output
+  }
+}
+
+/// K-weighting filter pair for a sample rate (BS.1770 stage 1 shelf, stage 2 high-pass)
+fn k_weighting(sample_rate: u32) -> (Biquad, Biquad) {
+  let fs = sample_rate as f64;
+
+  // Stage 1: high shelf, +4 dB above ~1.7 kHz (head diffraction)
+  let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+  let k = (PI * f0 / fs).tan();
+  let vh = 10f64.powf(gain_db / 20.0);
+  let vb = vh.powf(0.4996667741545416);
+  let a0 = 1.0 + k / q + k * k;
+  let shelf = Biquad::new(
+    [
+      (vh + vb * k / q + k * k) / a0,
+      2.0 * (k * k - vh) / a0,
+      (vh - vb * k / q + k * k) / a0,
+    ],
+    [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+  );
+
+  // Stage 2: high-pass at ~38 Hz
+  let (f0, q) = (38.13547087602444, 0.5003270373238773);
+  let k = (PI * f0 / fs).tan();
+  let a0 = 1.0 + k / q + k * k;
+  let highpass = Biquad::new(
+    [1.0, -2.0, 1.0],
+    [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+  );
+
+  (shelf, highpass)
+}
+
+/// Loudness of a mean square (summed over channels)
+fn to_lufs(mean_square: f64) -> f64 {
+  -0.691 + 10.0 * mean_square.max(1e-12).log10()
+}
+
+/// Integrated loudness of interleaved PCM in LUFS (None if shorter than one block or all gated)
+/// All channels are weighted equally (no surround weighting)
+pub fn integrated_loudness(pcm: &[f32], channels: usize, sample_rate: u32) -> Option<f64> {
+  let frames = pcm.len() / channels.max(1);
+  let step = (BLOCK_STEP_SECONDS * sample_rate as f64) as usize;
+  let block = step * (BLOCK_SECONDS / BLOCK_STEP_SECONDS).round() as usize;
+  if channels == 0 || step == 0 || frames < block {
+    return None;
+  }
+
+  // Sum of K-weighted squares per 100 ms step, across channels
+  let steps = frames / step;
+  let mut step_energy = vec![0f64; steps];
+  for ch in 0..channels {
+    let (mut shelf, mut highpass) = k_weighting(sample_rate);
+    for (i, &sample) in pcm.iter().skip(ch).step_by(channels).enumerate() {
+      let weighted = highpass.process(shelf.process(sample as f64));
+      if let Some(energy) = step_energy.get_mut(i / step) {
+        *energy += weighted * weighted;
+      }
+    }
+  }
+
+  // 400 ms blocks = four consecutive steps
+  let steps_per_block = block / step;
+  let blocks: Vec<f64> = step_energy
+    .windows(steps_per_block)
+    .map(|w| w.iter().sum::<f64>() / block as f64)
+    .filter(|&ms| to_lufs(ms) > ABSOLUTE_GATE)
+    .collect();
+  if blocks.is_empty() {
+    return None;
+  }
+
+  let relative_gate = to_lufs(blocks.iter().sum::<f64>() / blocks.len() as f64) + RELATIVE_GATE;
+  let gated: Vec<f64> = blocks
+    .into_iter()
+    .filter(|&ms| to_lufs(ms) > relative_gate)
+    .collect();
+  if gated.is_empty() {
+    return None;
+  }
+
+  Some(to_lufs(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sine_loudness() {
+    // 997 Hz sine at -20 dBFS on both channels: -23.01 LUFS per channel, +3.01 for two
+    let sample_rate = 48_000;
+    let pcm: Vec<f32> = (0..sample_rate as usize * 5)
+      .flat_map(|i| {
+        let s = 0.1 * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / sample_rate as f32).sin();
+        [s, s]
+      })
+      .collect();
+
+    let lufs = integrated_loudness(&pcm, 2, sample_rate).unwrap();
+    assert!((lufs + 20.0).abs() < 0.1, "loudness {lufs} LUFS");
+
+    // Silence is gated out entirely
+    let silence = [0.0; 96_000];
+    assert_eq!(integrated_loudness(&silence, 2, sample_rate), None);
+  }
+}
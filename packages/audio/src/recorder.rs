@@ -1,8 +1,11 @@
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use napi::Result;
+use parking_lot::Mutex;
 use vorbis_rs::{VorbisEncoder, VorbisEncoderBuilder};
 use std::num::{NonZeroU32, NonZeroU8};
 use napi_derive::napi;
@@ -13,19 +16,129 @@ pub enum RecordingFormat {
     Ogg,
 }
 
+/// Bus captured by the recorder
+#[napi]
+pub enum RecordingTarget {
+    Master,
+    Cue,
+}
+
+/// Optional tags embedded in the recorded file (unset fields are omitted)
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct RecordingMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub date: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl RecordingMetadata {
+    /// Set fields as (Vorbis comment name, RIFF INFO id, value)
+    fn fields(&self) -> Vec<(&'static str, &'static [u8; 4], &str)> {
+        [
+            ("TITLE", b"INAM", &self.title),
+            ("ARTIST", b"IART", &self.artist),
+            ("DATE", b"ICRD", &self.date),
+            ("COMMENT", b"ICMT", &self.comment),
+        ]
+        .into_iter()
+        .filter_map(|(name, id, value)| value.as_deref().map(|v| (name, id, v)))
+        .collect()
+    }
+}
+
+/// What to record, kept by the thread to open follow-on files
+struct RecordingSpec {
+    path: String,
+    format: RecordingFormat,
+    metadata: RecordingMetadata,
+    /// Engine sample rate, written to every part's header
+    sample_rate: u32,
+    /// Split into a new file after this many frames
+    max_frames: Option<u64>,
+    /// TPDF-dither the 16-bit WAV conversion
+    dither: bool,
+}
+
+/// The file being written and its place in a split recording
+struct RecordingPart {
+    writer: Box<dyn AudioWriter>,
+    /// 1-based part number
+    index: u32,
+    /// Frames written to this part
+    frames: u64,
+}
+
+/// Path of a follow-on file: "set.wav" -> "set-002.wav"
+fn part_path(path: &str, index: u32) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{:03}.{}", stem, index, ext.to_string_lossy()),
+        None => format!("{}-{:03}", stem, index),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
 enum RecordingMessage {
-    Start { path: String, format: RecordingFormat },
     AudioData(Vec<f32>),
     Stop,
 }
+/// TPDF dither: the sum of two uniform values, ±1 LSB peak
+/// Turns the quantization error of a 16-bit conversion into signal-independent noise
+/// instead of distortion on quiet passages
+struct TpdfDither {
+    state: u32,
+}
+
+impl TpdfDither {
+    fn new() -> Self {
+        Self { state: 0x9E37_79B9 }
+    }
+
+    /// Uniform in [-0.5, 0.5) LSB (xorshift32)
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+    }
+
+    /// Triangular noise in LSBs
+    fn next(&mut self) -> f32 {
+        self.uniform() + self.uniform()
+    }
+}
+
+/// Convert a sample to 16-bit: dithered and rounded with `dither`, truncated without
+fn to_i16(sample: f32, dither: Option<&mut TpdfDither>) -> i16 {
+    let scaled = sample * i16::MAX as f32;
+    let scaled = match dither {
+        Some(dither) => (scaled + dither.next()).round(),
+        None => scaled,
+    };
+    scaled.clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Channels written to WAV recordings (interleaved stereo)
+const WAV_CHANNELS: u16 = 2;
 
 trait AudioWriter {
     fn write_samples(&mut self, samples: &[f32]) -> Result<()>;
     fn finalize(self: Box<Self>) -> Result<()>;
 }
 
+/// 16-bit stereo PCM WAV writer
+/// The LIST/INFO metadata chunk is written ahead of the data chunk when the file is
+/// created; finalize patches the RIFF and data sizes
 struct WavWriter {
-    writer: hound::WavWriter<BufWriter<File>>,
+    writer: BufWriter<File>,
+    /// Offset of the data chunk's size field
+    data_size_offset: u64,
+    /// Sample bytes written so far
+    data_bytes: u64,
+    dither: Option<TpdfDither>,
 }
 
 struct OggWriter {
@@ -33,7 +146,7 @@ struct OggWriter {
 }
 
 impl OggWriter {
-    fn new(path: &str, sample_rate: u32) -> Result<Self> {
+    fn new(path: &str, sample_rate: u32, metadata: &RecordingMetadata) -> Result<Self> {
         let f = File::create(path)
             .map_err(|e| napi::Error::from_reason(format!("Failed to create OGG file: {}", e)))?;
         let writer = BufWriter::new(f);
@@ -42,6 +155,9 @@ impl OggWriter {
         let channels = NonZeroU8::new(2).ok_or_else(|| napi::Error::from_reason("Invalid channel count"))?;
 
         let mut builder = VorbisEncoderBuilder::new_with_serial(sampling_frequency, channels, writer, 0);
+        for (name, _, value) in metadata.fields() {
+            builder.comment_tag(name, value);
+        }
         let encoder = builder.build()
             .map_err(|e| napi::Error::from_reason(format!("Failed to create Vorbis encoder: {}", e)))?;
         Ok(Self { encoder })
@@ -49,33 +165,110 @@ impl OggWriter {
 }
 
 impl WavWriter {
-    fn new(path: &str, sample_rate: u32) -> Result<Self> {
-        let spec = hound::WavSpec {
-            channels: 2,
-            sample_rate,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-        let writer = hound::WavWriter::create(path, spec)
+    fn new(
+        path: &str,
+        sample_rate: u32,
+        metadata: &RecordingMetadata,
+        dither: bool,
+    ) -> Result<Self> {
+        let mut writer = File::create(path).map(BufWriter::new)
+            .map_err(|e| napi::Error::from_reason(format!("Failed to create WAV file: {}", e)))?;
+        let data_size_offset = Self::write_header(&mut writer, sample_rate, metadata)
             .map_err(|e| napi::Error::from_reason(format!("Failed to create WAV file: {}", e)))?;
-        Ok(Self { writer })
+        Ok(Self {
+            writer,
+            data_size_offset,
+            data_bytes: 0,
+            dither: dither.then(TpdfDither::new),
+        })
+    }
+
+    /// Write the RIFF header, fmt chunk, INFO chunk (if any metadata is set) and the data
+    /// chunk header with placeholder sizes; returns the offset of the data size field
+    fn write_header(
+        writer: &mut BufWriter<File>,
+        sample_rate: u32,
+        metadata: &RecordingMetadata,
+    ) -> std::io::Result<u64> {
+        let block_align = WAV_CHANNELS * 2;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // integer PCM
+        writer.write_all(&WAV_CHANNELS.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+
+        if let Some(info) = Self::info_chunk(metadata) {
+            writer.write_all(&info)?;
+        }
+
+        writer.write_all(b"data")?;
+        let data_size_offset = writer.stream_position()?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(data_size_offset)
+    }
+
+    /// LIST/INFO chunk holding the set metadata fields (None when none are set)
+    fn info_chunk(metadata: &RecordingMetadata) -> Option<Vec<u8>> {
+        let fields = metadata.fields();
+        if fields.is_empty() {
+            return None;
+        }
+
+        let mut info = b"INFO".to_vec();
+        for (_, id, value) in fields {
+            let mut data = value.as_bytes().to_vec();
+            data.push(0);
+            info.extend_from_slice(id);
+            info.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            info.extend_from_slice(&data);
+            if data.len() % 2 == 1 {
+                info.push(0);
+            }
+        }
+
+        let mut chunk = b"LIST".to_vec();
+        chunk.extend_from_slice(&(info.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(&info);
+        Some(chunk)
+    }
+
+    /// Fill in the RIFF and data chunk sizes now that the length is known
+    fn patch_sizes(&mut self) -> std::io::Result<()> {
+        let riff_size = self.data_size_offset + 4 + self.data_bytes - 8;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(riff_size as u32).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(self.data_size_offset))?;
+        self.writer.write_all(&(self.data_bytes as u32).to_le_bytes())?;
+        self.writer.flush()
     }
 }
 
 impl AudioWriter for WavWriter {
     fn write_samples(&mut self, samples: &[f32]) -> Result<()> {
+        // The RIFF size field is 32 bits
+        let bytes = samples.len() as u64 * 2;
+        if self.data_size_offset + 4 + self.data_bytes + bytes > u32::MAX as u64 {
+            return Err(napi::Error::from_reason("Failed to write WAV sample: file exceeds 4 GB"));
+        }
         for &sample in samples {
-            let clamped = (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-            self.writer.write_sample(clamped)
+            let clamped = to_i16(sample, self.dither.as_mut());
+            self.writer.write_all(&clamped.to_le_bytes())
                 .map_err(|e| napi::Error::from_reason(format!("Failed to write WAV sample: {}", e)))?;
         }
+        self.data_bytes += bytes;
         Ok(())
     }
 
-    fn finalize(self: Box<Self>) -> Result<()> {
-        self.writer.finalize()
-            .map_err(|e| napi::Error::from_reason(format!("Failed to finalize WAV file: {}", e)))?;
-        Ok(())
+    fn finalize(mut self: Box<Self>) -> Result<()> {
+        self.patch_sizes()
+            .map_err(|e| napi::Error::from_reason(format!("Failed to finalize WAV file: {}", e)))
     }
 }
 
@@ -106,8 +299,13 @@ impl AudioWriter for OggWriter {
 }
 
 pub struct RecordingThread {
-    thread: Option<JoinHandle<()>>,
+    /// Joins to the finalize result (errors as strings: napi errors aren't Send)
+    thread: Option<JoinHandle<std::result::Result<(), String>>>,
     sender: Option<Sender<RecordingMessage>>,
+    /// First failure while recording, set by the recording thread
+    error: Arc<Mutex<Option<String>>>,
+    /// Path of the file opened by the latest split, until taken
+    split: Arc<Mutex<Option<String>>>,
 }
 
 impl RecordingThread {
@@ -115,76 +313,165 @@ impl RecordingThread {
         Self {
             thread: None,
             sender: None,
+            error: Arc::new(Mutex::new(None)),
+            split: Arc::new(Mutex::new(None)),
         }
     }
 
-    pub fn start_recording(&mut self, path: String, format: RecordingFormat) -> Result<()> {
+    /// Start recording; returns once the file is created, or with the error if it can't be
+    /// With `max_frames`, the recording continues in "<name>-002.<ext>", "-003", ... every
+    /// `max_frames` frames; the split is sample-exact so the parts concatenate gaplessly
+    /// `dither` applies TPDF dither to the 16-bit WAV conversion (OGG ignores it)
+    /// `sample_rate` is the engine's; `max_frames` counts frames at that rate
+    pub fn start_recording(
+        &mut self,
+        path: String,
+        format: RecordingFormat,
+        metadata: RecordingMetadata,
+        sample_rate: u32,
+        max_frames: Option<u64>,
+        dither: bool,
+    ) -> Result<()> {
+        // A recording that already stopped on an error doesn't block a new one
+        if self.thread.as_ref().is_some_and(|thread| thread.is_finished()) {
+            let _ = self.stop();
+        }
         if self.thread.is_some() {
             return Err(napi::Error::from_reason("Recording already in progress"));
         }
 
         let (sender, receiver) = mpsc::channel();
-        self.sender = Some(sender);
+        let (ready_sender, ready_receiver) = mpsc::sync_channel(1);
+        *self.error.lock() = None;
+        *self.split.lock() = None;
+        let error = Arc::clone(&self.error);
+        let split = Arc::clone(&self.split);
+        let max_frames = max_frames.map(|f| f.max(1));
+        let spec = RecordingSpec { path, format, metadata, sample_rate, max_frames, dither };
 
         let thread = thread::spawn(move || {
-            Self::recording_loop(receiver);
+            let writer = match Self::create_writer(&spec.path, &spec) {
+                Ok(writer) => writer,
+                Err(e) => {
+                    let _ = ready_sender.send(Err(e.reason.clone()));
+                    return Ok(());
+                }
+            };
+            let _ = ready_sender.send(Ok(()));
+            let part = RecordingPart { writer, index: 1, frames: 0 };
+            Self::recording_loop(receiver, &spec, part, &error, &split)
         });
-        self.thread = Some(thread);
 
-        // Send start message
-        if let Some(ref sender) = self.sender {
-            sender.send(RecordingMessage::Start { path, format })
-                .map_err(|_| napi::Error::from_reason("Failed to send start message"))?;
+        // Wait for the writer so a bad path or permission error reaches the caller
+        let ready = ready_receiver
+            .recv()
+            .unwrap_or_else(|_| Err("Recording thread exited during startup".to_string()));
+        if let Err(reason) = ready {
+            let _ = thread.join();
+            return Err(napi::Error::from_reason(reason));
         }
 
+        self.sender = Some(sender);
+        self.thread = Some(thread);
         Ok(())
     }
 
     pub fn send_audio_data(&mut self, data: &[f32]) {
         if let Some(ref sender) = self.sender {
-            let _ = sender.send(RecordingMessage::AudioData(data.to_vec()));
+            if sender.send(RecordingMessage::AudioData(data.to_vec())).is_err() {
+                // The thread only exits early after recording an error; keep the first one
+                self.error.lock().get_or_insert_with(|| "Recording thread stopped".to_string());
+            }
         }
     }
 
+    /// Failure that stopped the current recording, if any (cleared by the next start)
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().clone()
+    }
+
+    /// Path of the file the recording continued in, once per split
+    pub fn take_split(&self) -> Option<String> {
+        self.split.lock().take()
+    }
+
     pub fn stop(&mut self) -> Result<()> {
         if let Some(sender) = self.sender.take() {
-            sender.send(RecordingMessage::Stop)
-                .map_err(|_| napi::Error::from_reason("Failed to send stop message"))?;
+            // The thread may already have exited after a write error
+            let _ = sender.send(RecordingMessage::Stop);
         }
 
         if let Some(thread) = self.thread.take() {
             thread.join()
-                .map_err(|_| napi::Error::from_reason("Recording thread panicked"))?;
+                .map_err(|_| napi::Error::from_reason("Recording thread panicked"))?
+                .map_err(napi::Error::from_reason)?;
         }
 
         Ok(())
     }
 
-    fn recording_loop(receiver: Receiver<RecordingMessage>) {
-        let mut writer: Option<Box<dyn AudioWriter>> = None;
-        let sample_rate = 44100; // Should match AudioEngine sample rate
+    /// Open a file at `path` (the spec's path or a split part) for the spec's format
+    fn create_writer(path: &str, spec: &RecordingSpec) -> Result<Box<dyn AudioWriter>> {
+        let sample_rate = spec.sample_rate;
+        let metadata = &spec.metadata;
 
-        while let Ok(message) = receiver.recv() {
-            match message {
-                RecordingMessage::Start { path, format } => {
-                    writer = match format {
-                            RecordingFormat::Wav => Some(Box::new(WavWriter::new(&path, sample_rate).unwrap())),
-                            RecordingFormat::Ogg => Some(Box::new(OggWriter::new(&path, sample_rate).unwrap())),
-                    };
-                }
-                RecordingMessage::AudioData(data) => {
-                    if let Some(ref mut w) = writer {
-                        let _ = w.write_samples(&data);
-                    }
-                }
-                RecordingMessage::Stop => {
-                    if let Some(w) = writer.take() {
-                        let _ = w.finalize();
-                    }
-                    break;
-                }
+        let writer: Box<dyn AudioWriter> = match spec.format {
+            RecordingFormat::Wav => {
+                Box::new(WavWriter::new(path, sample_rate, metadata, spec.dither)?)
+            }
+            RecordingFormat::Ogg => Box::new(OggWriter::new(path, sample_rate, metadata)?),
+        };
+        Ok(writer)
+    }
+
+    /// Write until Stop (or the sender is dropped), then finalize
+    /// A failure is stored in `error` and ends the recording with the current file finalized
+    fn recording_loop(
+        receiver: Receiver<RecordingMessage>,
+        spec: &RecordingSpec,
+        mut part: RecordingPart,
+        error: &Mutex<Option<String>>,
+        split: &Mutex<Option<String>>,
+    ) -> std::result::Result<(), String> {
+        while let Ok(RecordingMessage::AudioData(data)) = receiver.recv() {
+            if let Err(reason) = Self::write_split(spec, &mut part, &data, split) {
+                error.lock().get_or_insert(reason);
+                // Keep what was written so far playable
+                let _ = part.writer.finalize();
+                return Ok(());
             }
         }
+
+        part.writer.finalize().map_err(|e| e.reason.clone())
+    }
+
+    /// Write interleaved stereo, moving on to the next part exactly at `max_frames`
+    fn write_split(
+        spec: &RecordingSpec,
+        part: &mut RecordingPart,
+        mut samples: &[f32],
+        split: &Mutex<Option<String>>,
+    ) -> std::result::Result<(), String> {
+        while samples.len() >= 2 {
+            if spec.max_frames == Some(part.frames) {
+                let index = part.index + 1;
+                let path = part_path(&spec.path, index);
+                let writer = Self::create_writer(&path, spec).map_err(|e| e.reason.clone())?;
+                let finished = std::mem::replace(&mut part.writer, writer);
+                part.index = index;
+                part.frames = 0;
+                finished.finalize().map_err(|e| e.reason.clone())?;
+                *split.lock() = Some(path);
+            }
+
+            let room = spec.max_frames.map_or(u64::MAX, |max| max - part.frames);
+            let frames = ((samples.len() / 2) as u64).min(room) as usize;
+            let (head, tail) = samples.split_at(frames * 2);
+            part.writer.write_samples(head).map_err(|e| e.reason.clone())?;
+            part.frames += frames as u64;
+            samples = tail;
+        }
+        Ok(())
     }
 }
 
@@ -192,4 +479,85 @@ impl Drop for RecordingThread {
     fn drop(&mut self) {
         let _ = self.stop();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Correlation of the quantization error with the signal for a sine below 1 LSB
+    fn error_correlation(mut dither: Option<&mut TpdfDither>) -> f64 {
+        let lsb = 1.0 / i16::MAX as f32;
+        let (mut error_signal, mut error_sq, mut signal_sq) = (0.0f64, 0.0f64, 0.0f64);
+        for i in 0..441_000 {
+            let x = 0.4 * lsb * (2.0 * std::f32::consts::PI * 441.0 * i as f32 / 44100.0).sin();
+            let q = to_i16(x, dither.as_deref_mut()) as f64;
+            let (x, error) = (x as f64 / lsb as f64, q - x as f64 / lsb as f64);
+            error_signal += error * x;
+            error_sq += error * error;
+            signal_sq += x * x;
+        }
+        error_signal / (error_sq * signal_sq).sqrt()
+    }
+
+    #[test]
+    fn test_wav_info_chunk_precedes_data() {
+        let path = std::env::temp_dir().join(format!("sujay-info-{}.wav", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let metadata = RecordingMetadata {
+            title: Some("Friday set".to_string()),
+            artist: Some("Sujay".to_string()),
+            ..Default::default()
+        };
+        let mut writer: Box<dyn AudioWriter> =
+            Box::new(WavWriter::new(&path, 44100, &metadata, false).unwrap());
+        writer.write_samples(&[0.5, -0.5].repeat(100)).unwrap();
+        writer.finalize().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let find = |tag: &[u8]| bytes.windows(4).position(|w| w == tag).unwrap();
+        assert!(find(b"LIST") < find(b"data"));
+        assert!(find(b"IART") < find(b"data"));
+
+        // Sizes are patched: a standard reader sees every sample
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!((spec.channels, spec.sample_rate, spec.bits_per_sample), (2, 44100, 16));
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples.len(), 200);
+        assert_eq!(&samples[..2], &[to_i16(0.5, None), to_i16(-0.5, None)]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_split_parts_carry_the_engine_sample_rate() {
+        let path = std::env::temp_dir().join(format!("sujay-split-{}.wav", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let mut recording = RecordingThread::new();
+        let metadata = RecordingMetadata::default();
+        recording
+            .start_recording(path.clone(), RecordingFormat::Wav, metadata, 48000, Some(48), false)
+            .unwrap();
+        recording.send_audio_data(&[0.25; 100 * 2]);
+        recording.stop().unwrap();
+        assert_eq!(recording.take_split(), Some(part_path(&path, 3)));
+
+        // 100 frames at 48 per part: two full parts and the remainder, all at 48 kHz
+        for (index, frames) in [(1, 48), (2, 48), (3, 4)] {
+            let part = if index == 1 { path.clone() } else { part_path(&path, index) };
+            let reader = hound::WavReader::open(&part).unwrap();
+            assert_eq!(reader.spec().sample_rate, 48000);
+            assert_eq!(reader.duration(), frames);
+            std::fs::remove_file(&part).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dither_decorrelates_quantization_error() {
+        // Truncated, a sub-LSB sine quantizes to silence: the error is the inverted signal
+        assert!(error_correlation(None) < -0.99);
+
+        let correlation = error_correlation(Some(&mut TpdfDither::new()));
+        assert!(correlation.abs() < 0.02, "dithered error correlation {correlation}");
+    }
+}
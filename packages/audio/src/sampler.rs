@@ -0,0 +1,217 @@
+//! Sampler bank for one-shot samples (jingles, drops)
+//!
+//! A lightweight playback path separate from the decks:
+//! - Up to 8 slots of short stereo interleaved PCM
+//! - Polyphonic triggering, optionally choked by a shared choke group
+//! - Per-slot gain and loop flag
+//! - No time stretching or EQ; voices are mixed straight into the master
+
+/// Number of sample slots in the bank
+pub const SAMPLER_SLOTS: usize = 8;
+/// Maximum simultaneously playing voices (oldest voice is stolen)
+const MAX_VOICES: usize = 16;
+const CHANNELS: usize = 2;
+
+/// A loaded sample and its playback settings
+struct SampleSlot {
+  /// PCM data (stereo interleaved f32)
+  pcm: Option<Vec<f32>>,
+  /// Slot gain (0.0 to 2.0)
+  gain: f32,
+  /// Loop until stopped instead of playing once
+  looped: bool,
+  /// Triggering any slot in the same group stops the others
+  choke_group: Option<u32>,
+}
+
+impl Default for SampleSlot {
+  fn default() -> Self {
+    Self {
+      pcm: None,
+      gain: 1.0,
+      looped: false,
+      choke_group: None,
+    }
+  }
+}
+
+/// A playing instance of a slot
+struct Voice {
+  slot: usize,
+  /// Playback position in frames
+  position: usize,
+}
+
+pub struct SamplerBank {
+  slots: Vec<SampleSlot>,
+  voices: Vec<Voice>,
+}
+
+impl SamplerBank {
+  pub fn new() -> Self {
+    Self {
+      slots: (0..SAMPLER_SLOTS).map(|_| SampleSlot::default()).collect(),
+      voices: Vec::with_capacity(MAX_VOICES),
+    }
+  }
+
+  /// Load PCM into a slot, returning the previous buffer so it can be freed outside the lock
+  pub fn load(&mut self, slot: usize, pcm: Vec<f32>) -> Option<Vec<f32>> {
+    self.stop(slot);
+    self.slots[slot].pcm.replace(pcm)
+  }
+
+  pub fn set_gain(&mut self, slot: usize, gain: f32) {
+    self.slots[slot].gain = gain.clamp(0.0, 2.0);
+  }
+
+  pub fn set_loop(&mut self, slot: usize, looped: bool) {
+    self.slots[slot].looped = looped;
+  }
+
+  pub fn set_choke_group(&mut self, slot: usize, group: Option<u32>) {
+    self.slots[slot].choke_group = group;
+  }
+
+  /// Start a new voice for the slot
+  /// Looped slots restart instead of stacking; choke groups stop their other members
+  pub fn trigger(&mut self, slot: usize) {
+    if self.slots[slot].pcm.is_none() {
+      return;
+    }
+
+    if let Some(group) = self.slots[slot].choke_group {
+      let slots = &self.slots;
+      self
+        .voices
+        .retain(|voice| slots[voice.slot].choke_group != Some(group));
+    }
+    if self.slots[slot].looped {
+      self.stop(slot);
+    }
+
+    if self.voices.len() >= MAX_VOICES {
+      self.voices.remove(0);
+    }
+    self.voices.push(Voice { slot, position: 0 });
+  }
+
+  /// Stop all voices of a slot
+  pub fn stop(&mut self, slot: usize) {
+    self.voices.retain(|voice| voice.slot != slot);
+  }
+
+  /// Whether each slot currently has a playing voice
+  pub fn playing_slots(&self) -> Vec<bool> {
+    (0..SAMPLER_SLOTS)
+      .map(|slot| self.voices.iter().any(|voice| voice.slot == slot))
+      .collect()
+  }
+
+  /// Mix all playing voices into a stereo interleaved buffer
+  pub fn mix_into(&mut self, buffer: &mut [f32], frames: usize) {
+    let slots = &self.slots;
+    self.voices.retain_mut(|voice| {
+      let slot = &slots[voice.slot];
+      let Some(ref pcm) = slot.pcm else {
+        return false;
+      };
+      let total_frames = pcm.len() / CHANNELS;
+      if total_frames == 0 {
+        return false;
+      }
+
+      let mut written = 0;
+      while written < frames {
+        let count = (total_frames - voice.position).min(frames - written);
+        let out = &mut buffer[written * CHANNELS..(written + count) * CHANNELS];
+        let src = &pcm[voice.position * CHANNELS..(voice.position + count) * CHANNELS];
+        for (o, s) in out.iter_mut().zip(src) {
+          *o += s * slot.gain;
+        }
+        written += count;
+        voice.position += count;
+
+        if voice.position >= total_frames {
+          if !slot.looped {
+            return false;
+          }
+          voice.position = 0;
+        }
+      }
+      true
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Stereo sample of `frames` frames at a constant level
+  fn constant(frames: usize, level: f32) -> Vec<f32> {
+    vec![level; frames * CHANNELS]
+  }
+
+  fn render(bank: &mut SamplerBank, frames: usize) -> Vec<f32> {
+    let mut buffer = vec![0.0; frames * CHANNELS];
+    bank.mix_into(&mut buffer, frames);
+    buffer
+  }
+
+  #[test]
+  fn test_trigger_plays_once_then_ends() {
+    let mut bank = SamplerBank::new();
+    bank.load(0, constant(100, 0.25));
+    bank.trigger(0);
+    assert!(bank.playing_slots()[0]);
+
+    let buffer = render(&mut bank, 64);
+    assert!(buffer.iter().all(|&s| s == 0.25));
+
+    // The remaining 36 frames play, then silence and the voice is gone
+    let buffer = render(&mut bank, 64);
+    assert!(buffer[..36 * CHANNELS].iter().all(|&s| s == 0.25));
+    assert!(buffer[36 * CHANNELS..].iter().all(|&s| s == 0.0));
+    assert!(!bank.playing_slots()[0]);
+  }
+
+  #[test]
+  fn test_retrigger_stacks_one_shots_and_restarts_loops() {
+    let mut bank = SamplerBank::new();
+    bank.load(0, constant(100, 0.25));
+    bank.trigger(0);
+    bank.trigger(0);
+    let buffer = render(&mut bank, 10);
+    assert!(buffer.iter().all(|&s| s == 0.5));
+
+    // A looped slot restarts on retrigger instead of stacking, and wraps at its end
+    bank.stop(0);
+    bank.set_loop(0, true);
+    bank.trigger(0);
+    bank.trigger(0);
+    let buffer = render(&mut bank, 250);
+    assert!(buffer.iter().all(|&s| s == 0.25));
+    assert!(bank.playing_slots()[0]);
+  }
+
+  #[test]
+  fn test_stop_and_choke_group_silence_voices() {
+    let mut bank = SamplerBank::new();
+    bank.load(0, constant(100, 0.25));
+    bank.load(1, constant(100, 0.5));
+    bank.set_choke_group(0, Some(1));
+    bank.set_choke_group(1, Some(1));
+
+    bank.trigger(0);
+    bank.trigger(1);
+    assert_eq!(&bank.playing_slots()[..2], &[false, true]);
+    let buffer = render(&mut bank, 10);
+    assert!(buffer.iter().all(|&s| s == 0.5));
+
+    bank.stop(1);
+    assert!(!bank.playing_slots()[1]);
+    let buffer = render(&mut bank, 10);
+    assert!(buffer.iter().all(|&s| s == 0.0));
+  }
+}
@@ -0,0 +1,187 @@
+//! Mixing kernels with optional SIMD acceleration
+//!
+//! With the `simd` feature enabled these use the `wide` crate (portable SIMD on
+//! stable Rust); otherwise they fall back to the scalar reference loops.
+//! Both paths perform the same per-element multiply and add (no fused
+//! multiply-add), so SIMD results match the scalar path exactly.
+
+#[cfg(feature = "simd")]
+use wide::f32x8;
+
+#[cfg(feature = "simd")]
+const LANES: usize = 8;
+
+/// Mix two buffers with per-sample gains: `out[i] = a[i] * gain_a[i] + b[i] * gain_b[i]`
+pub fn mix_two(out: &mut [f32], a: &[f32], gain_a: &[f32], b: &[f32], gain_b: &[f32]) {
+  #[cfg(feature = "simd")]
+  {
+    let len = out
+      .len()
+      .min(a.len())
+      .min(gain_a.len())
+      .min(b.len())
+      .min(gain_b.len());
+    let vector_len = len - len % LANES;
+
+    for i in (0..vector_len).step_by(LANES) {
+      let va = load(&a[i..]);
+      let vga = load(&gain_a[i..]);
+      let vb = load(&b[i..]);
+      let vgb = load(&gain_b[i..]);
+      out[i..i + LANES].copy_from_slice(&(va * vga + vb * vgb).to_array());
+    }
+
+    scalar::mix_two(
+      &mut out[vector_len..len],
+      &a[vector_len..len],
+      &gain_a[vector_len..len],
+      &b[vector_len..len],
+      &gain_b[vector_len..len],
+    );
+  }
+
+  #[cfg(not(feature = "simd"))]
+  scalar::mix_two(out, a, gain_a, b, gain_b);
+}
+
+/// Peak absolute value of a buffer
+pub fn peak_abs(buffer: &[f32]) -> f32 {
+  #[cfg(feature = "simd")]
+  {
+    let vector_len = buffer.len() - buffer.len() % LANES;
+    let mut peaks = f32x8::splat(0.0);
+
+    for i in (0..vector_len).step_by(LANES) {
+      peaks = peaks.max(load(&buffer[i..]).abs());
+    }
+
+    let vector_peak = peaks.to_array().iter().copied().fold(0.0f32, f32::max);
+    vector_peak.max(scalar::peak_abs(&buffer[vector_len..]))
+  }
+
+  #[cfg(not(feature = "simd"))]
+  scalar::peak_abs(buffer)
+}
+
+/// Weighted sum of three buffers: `out[i] = x[i] * gx + y[i] * gy + z[i] * gz`
+pub fn sum_three(out: &mut [f32], x: &[f32], gx: f32, y: &[f32], gy: f32, z: &[f32], gz: f32) {
+  #[cfg(feature = "simd")]
+  {
+    let len = out.len().min(x.len()).min(y.len()).min(z.len());
+    let vector_len = len - len % LANES;
+    let (vgx, vgy, vgz) = (f32x8::splat(gx), f32x8::splat(gy), f32x8::splat(gz));
+
+    for i in (0..vector_len).step_by(LANES) {
+      let sum = load(&x[i..]) * vgx + load(&y[i..]) * vgy + load(&z[i..]) * vgz;
+      out[i..i + LANES].copy_from_slice(&sum.to_array());
+    }
+
+    scalar::sum_three(
+      &mut out[vector_len..len],
+      &x[vector_len..len],
+      gx,
+      &y[vector_len..len],
+      gy,
+      &z[vector_len..len],
+      gz,
+    );
+  }
+
+  #[cfg(not(feature = "simd"))]
+  scalar::sum_three(out, x, gx, y, gy, z, gz);
+}
+
+/// Accumulate a scaled buffer: `out[i] += x[i] * gx`
+pub fn add_scaled(out: &mut [f32], x: &[f32], gx: f32) {
+  #[cfg(feature = "simd")]
+  {
+    let len = out.len().min(x.len());
+    let vector_len = len - len % LANES;
+    let vgx = f32x8::splat(gx);
+
+    for i in (0..vector_len).step_by(LANES) {
+      let sum = load(&out[i..]) + load(&x[i..]) * vgx;
+      out[i..i + LANES].copy_from_slice(&sum.to_array());
+    }
+
+    scalar::add_scaled(&mut out[vector_len..len], &x[vector_len..len], gx);
+  }
+
+  #[cfg(not(feature = "simd"))]
+  scalar::add_scaled(out, x, gx);
+}
+
+/// Load the first 8 samples of a slice into a vector register
+#[cfg(feature = "simd")]
+#[inline]
+fn load(slice: &[f32]) -> f32x8 {
+  let mut lanes = [0.0f32; LANES];
+  lanes.copy_from_slice(&slice[..LANES]);
+  f32x8::from(lanes)
+}
+
+/// Scalar reference implementations (also used for SIMD remainders)
+pub mod scalar {
+  pub fn mix_two(out: &mut [f32], a: &[f32], gain_a: &[f32], b: &[f32], gain_b: &[f32]) {
+    for ((((o, &a), &ga), &b), &gb) in out.iter_mut().zip(a).zip(gain_a).zip(b).zip(gain_b) {
+      *o = a * ga + b * gb;
+    }
+  }
+
+  pub fn peak_abs(buffer: &[f32]) -> f32 {
+    buffer.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+  }
+
+  pub fn sum_three(out: &mut [f32], x: &[f32], gx: f32, y: &[f32], gy: f32, z: &[f32], gz: f32) {
+    for (((o, &x), &y), &z) in out.iter_mut().zip(x).zip(y).zip(z) {
+      *o = x * gx + y * gy + z * gz;
+    }
+  }
+
+  pub fn add_scaled(out: &mut [f32], x: &[f32], gx: f32) {
+    for (o, &x) in out.iter_mut().zip(x) {
+      *o += x * gx;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Deterministic pseudo-random test signal in [-1.5, 1.5]
+  fn signal(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 24) as f32 * 3.0 - 1.5
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_kernels_match_scalar() {
+    // Odd length to exercise the remainder path
+    let len = 2048 * 2 + 5;
+    let (a, b) = (signal(len, 1), signal(len, 2));
+    let (gain_a, gain_b) = (signal(len, 3), signal(len, 4));
+
+    let mut expected = vec![0.0f32; len];
+    let mut actual = vec![0.0f32; len];
+    scalar::mix_two(&mut expected, &a, &gain_a, &b, &gain_b);
+    mix_two(&mut actual, &a, &gain_a, &b, &gain_b);
+    assert_eq!(expected, actual);
+
+    assert_eq!(scalar::peak_abs(&a), peak_abs(&a));
+
+    let z = signal(len, 5);
+    scalar::sum_three(&mut expected, &a, 0.0, &b, 1.0, &z, 0.5);
+    sum_three(&mut actual, &a, 0.0, &b, 1.0, &z, 0.5);
+    assert_eq!(expected, actual);
+
+    scalar::add_scaled(&mut expected, &z, 0.25);
+    add_scaled(&mut actual, &z, 0.25);
+    assert_eq!(expected, actual);
+  }
+}